@@ -0,0 +1,182 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generates the parser of a grammar written in a `.oak` file from a build script, instead of expanding the `oak!` macro at each compilation.
+//! The file contains what is usually written inside `oak! { ... }`, it is analysed and compiled by the same passes as the macro, and the generated Rust module is written into `OUT_DIR`:
+//!
+//! ```no_run
+//! // build.rs
+//! extern crate oak_build;
+//!
+//! fn main() {
+//!   oak_build::compile("grammars/calc.oak").unwrap();
+//! }
+//! ```
+//!
+//! The file declares the module `calc`, it is included in the crate, which depends on `oak_runtime` as with the macro:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/calc.rs"));
+//! ```
+//!
+//! The diagnostics are printed with their positions in the grammar file, and the warnings are also reported to Cargo.
+
+extern crate oak_compiler;
+extern crate proc_macro2;
+
+use oak_compiler::diagnostic;
+
+use std::env;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The lints triggered by the generated code, which is not written by the user, they are allowed on the generated module.
+const ALLOWED_LINTS: &[&str] = &[
+  "unused_parens", "unused_mut", "unused_variables", "unused_imports", "unused_braces",
+  "dead_code", "non_snake_case", "non_camel_case_types", "unreachable_code", "clippy::all"
+];
+
+/// Compiles the grammar file `path` into `OUT_DIR` with the default configuration, see `Config::compile`.
+pub fn compile<P: AsRef<Path>>(path: P) -> Result<PathBuf, Error> {
+  Config::new().compile(path)
+}
+
+pub struct Config
+{
+  out_dir: Option<PathBuf>,
+  rustfmt: bool
+}
+
+impl Config
+{
+  pub fn new() -> Self {
+    Config { out_dir: None, rustfmt: true }
+  }
+
+  /// The directory of the generated module, `OUT_DIR` by default.
+  pub fn out_dir<P: Into<PathBuf>>(mut self, out_dir: P) -> Self {
+    self.out_dir = Some(out_dir.into());
+    self
+  }
+
+  /// Formats the generated module with `rustfmt` (the default), it is left unformatted if `rustfmt` is not available.
+  pub fn rustfmt(mut self, rustfmt: bool) -> Self {
+    self.rustfmt = rustfmt;
+    self
+  }
+
+  /// Compiles the grammar file `path` into the file `<out_dir>/<name>.rs` declaring the module `name`, where `name` is the name of the grammar file without its extension.
+  /// The module has the same functions as the code generated by `oak!`: `parse_<rule>` and `recognize_<rule>` for each rule.
+  /// Returns the path of the module, or an error if the grammar has an error.
+  pub fn compile<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf, Error> {
+    let path = path.as_ref();
+    println!("cargo:rerun-if-changed={}", path.display());
+    let source = fs::read_to_string(path)?;
+    let code = generate(&source, &path.display().to_string())?;
+    let out_dir = match self.out_dir {
+      Some(ref out_dir) => out_dir.clone(),
+      None => env::var_os("OUT_DIR").map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound,
+          "`OUT_DIR` is not set, `oak_build` must be called from a build script or with `Config::out_dir`."))?
+    };
+    let name = module_name(path)?;
+    let module = out_dir.join(&name).with_extension("rs");
+    fs::write(&module, format!("// Generated by oak_build from `{}`, do not edit.\n\n#[allow({})]\npub mod {} {{\n{}\n}}\n",
+      path.display(), ALLOWED_LINTS.join(", "), name, code))?;
+    if self.rustfmt {
+      format_module(&module);
+    }
+    Ok(module)
+  }
+}
+
+impl Default for Config
+{
+  fn default() -> Self {
+    Config::new()
+  }
+}
+
+/// The name of the generated module is the name of the grammar file without its extension, it must be a Rust identifier.
+fn module_name(path: &Path) -> Result<String, Error> {
+  let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("");
+  let mut chars = name.chars();
+  let is_ident = match chars.next() {
+    Some(c) => (c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_'),
+    None => false
+  };
+  if is_ident {
+    Ok(name.to_string())
+  }
+  else {
+    Err(Error::Io(io::Error::new(io::ErrorKind::InvalidInput,
+      format!("the name of the file `{}` must be a Rust identifier, it is the name of the generated module.", path.display()))))
+  }
+}
+
+/// Compiles the grammar `source`, the diagnostics refer to the file `path`.
+fn generate(source: &str, path: &str) -> Result<proc_macro2::TokenStream, Error> {
+  let code = oak_compiler::parse(source).map(oak_compiler::compile);
+  let diagnostics = diagnostic::render_diagnostics(path);
+  for warning in &diagnostics.warnings {
+    eprintln!("{}\n", warning);
+    for line in warning.lines() {
+      println!("cargo:warning={}", line);
+    }
+  }
+  for error in &diagnostics.errors {
+    eprintln!("{}\n", error);
+  }
+  match code {
+    Some(code) if diagnostics.errors.is_empty() => Ok(code),
+    _ => Err(Error::Grammar(diagnostics.errors.join("\n")))
+  }
+}
+
+fn format_module(module: &Path) {
+  let rustfmt = env::var_os("RUSTFMT").unwrap_or("rustfmt".into());
+  // The module is still valid if it cannot be formatted.
+  let _ = Command::new(rustfmt).arg("--edition").arg("2018").arg(module).status();
+}
+
+#[derive(Debug)]
+pub enum Error
+{
+  Io(io::Error),
+  /// The diagnostics of the errors in the grammar, with their positions in the file.
+  Grammar(String)
+}
+
+impl fmt::Display for Error
+{
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      &Error::Io(ref err) => write!(f, "{}", err),
+      &Error::Grammar(ref diagnostics) => write!(f, "the grammar has errors:\n{}", diagnostics)
+    }
+  }
+}
+
+impl error::Error for Error {}
+
+impl From<io::Error> for Error
+{
+  fn from(err: io::Error) -> Self {
+    Error::Io(err)
+  }
+}
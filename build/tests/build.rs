@@ -0,0 +1,98 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate oak_build;
+
+use oak_build::{Config, Error};
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn out_dir(name: &str) -> PathBuf {
+  let dir = env::temp_dir().join("oak_build_tests").join(name);
+  fs::create_dir_all(&dir).unwrap();
+  dir
+}
+
+#[test]
+fn generates_the_parsing_functions() {
+  let module = Config::new()
+    .out_dir(out_dir("calc"))
+    .compile("tests/grammars/calc.oak")
+    .unwrap();
+  assert_eq!(module.file_name().unwrap(), "calc.rs");
+  let code = fs::read_to_string(module).unwrap();
+  for rule in &["expression", "sum", "product", "number"] {
+    assert!(code.contains(&format!("fn parse_{}", rule)), "`parse_{}` is not generated.", rule);
+    assert!(code.contains(&format!("fn recognize_{}", rule)), "`recognize_{}` is not generated.", rule);
+  }
+  assert!(code.contains("fn to_number"));
+}
+
+/// The `oak_runtime` library built as a dependency of the tests, in the directory of the test executable.
+fn runtime_library(deps: &Path) -> PathBuf {
+  fs::read_dir(deps).unwrap()
+    .map(|entry| entry.unwrap().path())
+    .filter(|path| {
+      let name = path.file_name().unwrap().to_string_lossy();
+      name.starts_with("liboak_runtime-") && name.ends_with(".rlib")
+    })
+    .max_by_key(|path| fs::metadata(path).unwrap().modified().unwrap())
+    .expect("`oak_runtime` is not built.")
+}
+
+#[test]
+fn compiles_the_generated_module() {
+  let dir = out_dir("compiled");
+  let module = Config::new()
+    .out_dir(&dir)
+    .compile("tests/grammars/calc.oak")
+    .unwrap();
+  let main = dir.join("main.rs");
+  fs::write(&main, format!(r#"
+    extern crate oak_runtime;
+    use oak_runtime::*;
+    include!({:?});
+    fn main() {{
+      assert_eq!(calc::parse_expression("1 +2*3".into_state()).into_full_result(), Ok(7));
+      assert!(calc::recognize_expression("1 +".into_state()).into_full_result().is_err());
+    }}
+  "#, module)).unwrap();
+  let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+  let rustc = env::var_os("RUSTC").unwrap_or("rustc".into());
+  let status = Command::new(rustc)
+    .arg(&main).arg("-o").arg(dir.join("calc"))
+    .arg("-D").arg("warnings")
+    .arg("-L").arg(format!("dependency={}", deps.display()))
+    .arg("--extern").arg(format!("oak_runtime={}", runtime_library(&deps).display()))
+    .status().unwrap();
+  assert!(status.success(), "the generated module does not compile without warnings.");
+  assert!(Command::new(dir.join("calc")).status().unwrap().success(), "the generated parser does not parse `1 +2*3`.");
+}
+
+#[test]
+fn reports_errors_with_file_positions() {
+  let result = Config::new()
+    .out_dir(out_dir("duplicate_rule"))
+    .compile("tests/grammars/duplicate_rule.oak");
+  match result {
+    Err(Error::Grammar(diagnostics)) => {
+      assert!(diagnostics.contains("tests/grammars/duplicate_rule.oak:4:1"),
+        "unexpected diagnostics:\n{}", diagnostics);
+    }
+    _ => panic!("the duplicate rule `sum` is not reported.")
+  }
+}
@@ -0,0 +1,189 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The command-line tool `oak` checks and inspects a grammar written in a `.oak` file, which contains what is usually written inside `oak! { ... }`.
+//! It runs the same passes as the macro, and prints the diagnostics with their positions in the file.
+//!
+//! ```text
+//! oak check grammars/calc.oak
+//! oak types grammars/calc.oak
+//! oak expand grammars/calc.oak
+//! oak graph grammars/calc.oak | dot -Tsvg > calc.svg
+//! ```
+//!
+//! The exit status is 1 if the grammar has errors and 2 if the command is invalid or the file cannot be read.
+
+extern crate oak_compiler;
+
+use oak_compiler::ast::{self, Expression, ItemIdent};
+use oak_compiler::{back, diagnostic};
+use oak_compiler::front::ast::FGrammar;
+use oak_compiler::middle::typing::ast::TGrammar;
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{self, Command, Stdio};
+
+static USAGE: &str = "usage: oak <command> <grammar.oak>
+
+commands:
+  check   analyses and types the grammar, and prints its diagnostics
+  types   prints the inferred type of each rule
+  expand  prints the generated Rust code
+  graph   prints the dependency graph of the rules in the Graphviz format";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Subcommand
+{
+  Check,
+  Types,
+  Expand,
+  Graph
+}
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  let subcommand = match args.get(0).map(|arg| arg.as_str()) {
+    Some("check") => Subcommand::Check,
+    Some("types") => Subcommand::Types,
+    Some("expand") => Subcommand::Expand,
+    Some("graph") => Subcommand::Graph,
+    Some("help") | Some("--help") | Some("-h") => {
+      println!("{}", USAGE);
+      return;
+    }
+    _ => usage_error()
+  };
+  if args.len() != 2 {
+    usage_error();
+  }
+  let path = &args[1];
+  let source = match fs::read_to_string(path) {
+    Ok(source) => source,
+    Err(err) => {
+      eprintln!("error: cannot read the grammar file `{}`: {}.", path, err);
+      process::exit(2);
+    }
+  };
+  process::exit(run(subcommand, &source, path));
+}
+
+fn usage_error() -> ! {
+  eprintln!("{}", USAGE);
+  process::exit(2);
+}
+
+/// Runs the subcommand on the grammar `source` of the file `path`, and returns the exit status.
+fn run(subcommand: Subcommand, source: &str, path: &str) -> i32 {
+  let mut output = String::new();
+  if let Some(ast) = oak_compiler::parse(source) {
+    output = match subcommand {
+      Subcommand::Graph => dependency_graph(&ast),
+      Subcommand::Types => types(ast),
+      Subcommand::Expand => format_code(oak_compiler::compile(ast).to_string()),
+      Subcommand::Check => { oak_compiler::typecheck(ast); String::new() }
+    };
+  }
+  let diagnostics = diagnostic::render_diagnostics(path);
+  for diagnostic in diagnostics.warnings.iter().chain(diagnostics.errors.iter()) {
+    eprintln!("{}\n", diagnostic);
+  }
+  let (errors, warnings) = (diagnostics.errors.len(), diagnostics.warnings.len());
+  if errors > 0 {
+    eprintln!("error: the grammar has {} error{} and {} warning{}.", errors, plural(errors), warnings, plural(warnings));
+    return 1;
+  }
+  if subcommand == Subcommand::Check {
+    eprintln!("the grammar is well-formed, with {} warning{}.", warnings, plural(warnings));
+  }
+  print!("{}", output);
+  0
+}
+
+/// The type of each rule in each configuration of the grammar.
+fn types(ast: FGrammar) -> String {
+  let mut output = String::new();
+  for (config, grammar) in oak_compiler::typecheck(ast) {
+    if let Some(cfg) = config.cfg_attribute() {
+      output.push_str(&format!("{}\n", ast::quote_string(&cfg)));
+    }
+    output.push_str(&rule_types(&grammar));
+  }
+  output
+}
+
+fn rule_types(grammar: &TGrammar) -> String {
+  grammar.rules.iter()
+    .map(|rule| format!("{}: {}\n", rule.ident(), ast::quote_string(&back::rust_type(grammar, rule.expr_idx))))
+    .collect()
+}
+
+/// Formats the code with `rustfmt`, it is printed unformatted if `rustfmt` is not available.
+fn format_code(code: String) -> String {
+  let rustfmt = env::var_os("RUSTFMT").unwrap_or("rustfmt".into());
+  let child = Command::new(rustfmt).arg("--edition").arg("2018")
+    .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null())
+    .spawn();
+  if let Ok(mut child) = child {
+    let written = child.stdin.take().map_or(false, |mut stdin| stdin.write_all(code.as_bytes()).is_ok());
+    if let Ok(formatted) = child.wait_with_output() {
+      if written && formatted.status.success() {
+        if let Ok(formatted) = String::from_utf8(formatted.stdout) {
+          return formatted;
+        }
+      }
+    }
+  }
+  format!("{}\n", code)
+}
+
+/// The rules called by each rule, in the format of Graphviz. The calls to external parsers are dashed.
+fn dependency_graph(grammar: &FGrammar) -> String {
+  let mut graph = format!("digraph grammar {{\n");
+  for rule in &grammar.rules {
+    graph.push_str(&format!("  {};\n", rule.ident()));
+    let mut calls = vec![];
+    calls_of(grammar, rule.expr_idx, &mut calls);
+    for call in calls {
+      graph.push_str(&format!("  {} -> {};\n", rule.ident(), call));
+    }
+  }
+  graph.push_str("}\n");
+  graph
+}
+
+fn calls_of(grammar: &FGrammar, expr_idx: usize, calls: &mut Vec<String>) {
+  let call = match &grammar.exprs[expr_idx] {
+    &Expression::NonTerminalSymbol(ref rule) => Some(format!("{}", rule)),
+    // The calls are resolved to the rules of the grammar during the analysis, see `resolve_non_terminal`.
+    &Expression::ExternalNonTerminalSymbol(ref path) => match path.get_ident() {
+      Some(ident) if grammar.rules.iter().any(|rule| rule.ident() == *ident) => Some(format!("{}", ident)),
+      _ => Some(format!("\"{}\" [style=dashed]", ast::quote_string(path)))
+    },
+    _ => None
+  };
+  match call {
+    Some(call) => if !calls.contains(&call) { calls.push(call); },
+    None => {
+      for child in grammar.exprs[expr_idx].children() {
+        calls_of(grammar, child, calls);
+      }
+    }
+  }
+}
+
+fn plural(n: usize) -> &'static str {
+  if n == 1 { "" } else { "s" }
+}
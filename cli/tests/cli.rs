@@ -0,0 +1,80 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::process::{Command, Output};
+
+fn oak(args: &[&str]) -> Output {
+  Command::new(env!("CARGO_BIN_EXE_oak")).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+  String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+  String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn check() {
+  let output = oak(&["check", "tests/grammars/calc.oak"]);
+  assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+  assert!(stderr(&output).contains("the grammar is well-formed"));
+}
+
+#[test]
+fn check_reports_errors_with_file_positions() {
+  let output = oak(&["check", "tests/grammars/type_error.oak"]);
+  assert_eq!(output.status.code(), Some(1));
+  let stderr = stderr(&output);
+  assert!(stderr.contains("the semantic action `add` takes 1 parameter"), "{}", stderr);
+  assert!(stderr.contains("tests/grammars/type_error.oak:3:30"), "{}", stderr);
+}
+
+#[test]
+fn types() {
+  let output = oak(&["types", "tests/grammars/calc.oak"]);
+  assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+  let stdout = stdout(&output);
+  assert!(stdout.contains("sum: u32\n"), "{}", stdout);
+  assert!(stdout.contains("spacing: ()\n"), "{}", stdout);
+}
+
+#[test]
+fn expand() {
+  let output = oak(&["expand", "tests/grammars/calc.oak"]);
+  assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+  let stdout = stdout(&output);
+  for rule in &["expression", "sum", "product", "number"] {
+    assert!(stdout.contains(&format!("fn parse_{}", rule)), "`parse_{}` is not generated.", rule);
+    assert!(stdout.contains(&format!("fn recognize_{}", rule)), "`recognize_{}` is not generated.", rule);
+  }
+}
+
+#[test]
+fn graph() {
+  let output = oak(&["graph", "tests/grammars/calc.oak"]);
+  assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+  let stdout = stdout(&output);
+  assert!(stdout.starts_with("digraph grammar {"));
+  assert!(stdout.contains("  sum -> product;\n"), "{}", stdout);
+  assert!(stdout.contains("  number -> spacing;\n"), "{}", stdout);
+}
+
+#[test]
+fn usage() {
+  assert_eq!(oak(&[]).status.code(), Some(2));
+  assert_eq!(oak(&["check"]).status.code(), Some(2));
+  assert_eq!(oak(&["check", "tests/grammars/missing.oak"]).status.code(), Some(2));
+}
@@ -0,0 +1,507 @@
+// Copyright 2015 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! AST of a PEG expression that is shared across all the compiling steps.
+
+pub use identifier::*;
+pub use diagnostic::SpanDiagnostic;
+pub use syn::spanned::Spanned;
+
+use std::fmt::{Formatter, Display, Error};
+
+pub use partial::Partial;
+
+pub use middle::typing::ast::IType;
+pub use middle::typing::ast::Type;
+
+use middle::analysis::ast::GrammarAttributes;
+
+use std::collections::HashMap;
+use std::default::Default;
+use std::ops::{Index, IndexMut};
+
+use syn::parse_quote;
+use quote::quote;
+
+pub trait ExprByIndex
+{
+  fn expr_by_index(&self, index: usize) -> Expression;
+}
+
+pub struct Grammar<ExprInfo>
+{
+  pub start_span: Span,
+  pub rules: Vec<Rule>,
+  pub exprs: Vec<Expression>,
+  pub exprs_info: Vec<ExprInfo>,
+  pub stream_alias: syn::ItemType,
+  pub rust_functions: HashMap<Ident, syn::ItemFn>,
+  pub rust_items: Vec<syn::Item>,
+  pub attributes: GrammarAttributes
+}
+
+impl<ExprInfo> Grammar<ExprInfo>
+{
+  pub fn new(start_span: Span, exprs: Vec<Expression>,
+    exprs_info: Vec<ExprInfo>) -> Grammar<ExprInfo>
+  {
+    Grammar {
+      start_span,
+      rules: vec![],
+      exprs,
+      exprs_info,
+      stream_alias: parse_quote!(pub type Stream<'a> = StrStream<'a>;),
+      rust_functions: HashMap::new(),
+      rust_items: vec![],
+      attributes: GrammarAttributes::default()
+    }
+  }
+
+  pub fn find_rule_by_ident(&self, id: &Ident) -> Rule {
+    self.rules.iter()
+      .find(|r| r.ident().to_string() == id.to_string())
+      .expect("Rule ident not registered in the known rules.")
+      .clone()
+  }
+
+  pub fn expr_index_of_rule(&self, id: &Ident) -> usize {
+    self.find_rule_by_ident(id).expr_idx
+  }
+
+  /// Rules without explicit visibility are public unless the grammar is annotated with `#![private_rules]`.
+  pub fn visibility_of(&self, rule: &Rule) -> syn::Visibility {
+    match rule.visibility {
+      Some(ref vis) => vis.clone(),
+      None if self.attributes.private_rules => syn::Visibility::Inherited,
+      None => parse_quote!(pub)
+    }
+  }
+
+  /// The recognizer and parser of an exposed rule are always generated, otherwise we only generate the functions called by other rules.
+  pub fn is_exposed(&self, rule: &Rule) -> bool {
+    match rule.visibility {
+      Some(_) => rule.is_public(),
+      None => !self.attributes.private_rules
+    }
+  }
+
+  pub fn stream_generics(&self) -> syn::Generics {
+    self.stream_alias.generics.clone()
+  }
+
+  /// Given `type Stream<'a, T, ..> where T: X = MyStream<'a, T, ...>`
+  /// We generate functions (similar to) the following one:
+  ///   fn parse<'a, T, ..>(stream: MyStream<'a, T, ...>) where T: X { ... }
+  /// This function creates the type `MyStream<'a, T, ...>` from the type alias.
+  /// The generics parameters are supposed to have the same name when we will generate the function.
+  /// We must transform the parameters of the type alias into arguments of the function argument's type.
+  pub fn stream_type(&self) -> syn::Type {
+    let name = self.stream_alias.ident.clone();
+    let (_, ty_generics, _) = self.stream_alias.generics.split_for_impl();
+    let stream_ty: syn::Type = parse_quote!(#name #ty_generics);
+    stream_ty
+  }
+
+  /// The span type of the underlying type is given by the trait's associated type `StreamSpan::Output`.
+  pub fn span_type(&self) -> syn::Type {
+    let range_ty: syn::Type = self.range_type();
+    parse_quote!(<#range_ty as StreamSpan>::Output)
+  }
+
+  pub fn range_type(&self) -> syn::Type {
+    let stream_ty = self.stream_type();
+    parse_quote!(Range<#stream_ty>)
+  }
+}
+
+/// Precedence levels of the PEG operators, used to print the minimal number of parenthesis.
+const CHOICE_PREC: u8 = 0;
+const ACTION_PREC: u8 = 1;
+const SPANNED_PREC: u8 = 2;
+const SEQUENCE_PREC: u8 = 3;
+const ASCRIPTION_PREC: u8 = 4;
+const PREFIX_PREC: u8 = 5;
+const SUFFIX_PREC: u8 = 6;
+const ATOM_PREC: u8 = 7;
+
+impl<ExprInfo> Grammar<ExprInfo>
+{
+  /// Prints the expression `expr_idx` in the PEG syntax of Oak, e.g. `"(" expr ")" > paren`.
+  pub fn display_expr(&self, expr_idx: usize) -> String {
+    self.display_expr_prec(expr_idx, CHOICE_PREC)
+  }
+
+  fn display_expr_prec(&self, expr_idx: usize, context_prec: u8) -> String {
+    use self::Expression::*;
+    let (prec, text) = match &self.exprs[expr_idx] {
+      &StrLiteral(ref lit) => (ATOM_PREC, format!("{}", lit)),
+      &AnySingleChar => (ATOM_PREC, format!(".")),
+      &CharacterClass(ref classes) => (ATOM_PREC, format!("{}", classes)),
+      &NonTerminalSymbol(ref id) => (ATOM_PREC, format!("{}", id)),
+      &ExternalNonTerminalSymbol(ref path) => (ATOM_PREC, quote!(#path).to_string().replace(" ", "")),
+      &Sequence(ref seq) => (SEQUENCE_PREC, self.display_exprs(seq, " ", SEQUENCE_PREC + 1)),
+      &Choice(ref choices) => (CHOICE_PREC, self.display_exprs(choices, " / ", ACTION_PREC)),
+      &ZeroOrMore(e) => (SUFFIX_PREC, format!("{}*", self.display_expr_prec(e, ATOM_PREC))),
+      &OneOrMore(e) => (SUFFIX_PREC, format!("{}+", self.display_expr_prec(e, ATOM_PREC))),
+      &ZeroOrOne(e) => (SUFFIX_PREC, format!("{}?", self.display_expr_prec(e, ATOM_PREC))),
+      &NotPredicate(e) => (PREFIX_PREC, format!("!{}", self.display_expr_prec(e, SUFFIX_PREC))),
+      &AndPredicate(e) => (PREFIX_PREC, format!("&{}", self.display_expr_prec(e, SUFFIX_PREC))),
+      &SemanticAction(e, boxed, ref action) => {
+        let boxed = if boxed { "box " } else { "" };
+        (ACTION_PREC, format!("{} > {}{}", self.display_expr_prec(e, SPANNED_PREC), boxed,
+          quote!(#action).to_string().replace(" ", "")))
+      }
+      &TypeAscription(e, ref ty) => {
+        let ty = match ty {
+          &IType::Invisible => format!("(^)"),
+          &IType::Regular(Type::Unit) => format!("()"),
+          &IType::Regular(Type::Rust(ref ty)) => format!("({})", quote!(#ty)),
+          _ => return self.display_expr_prec(e, context_prec)
+        };
+        (ASCRIPTION_PREC, format!("{}:{}", self.display_expr_prec(e, PREFIX_PREC), ty))
+      }
+      &SpannedExpr(e) => (SPANNED_PREC, format!(".. {}", self.display_expr_prec(e, SEQUENCE_PREC))),
+      // A range expression `... e` is represented as `... (e:())`.
+      &RangeExpr(e) => {
+        let e = match &self.exprs[e] {
+          &TypeAscription(e, _) => e,
+          _ => e
+        };
+        (SPANNED_PREC, format!("... {}", self.display_expr_prec(e, SEQUENCE_PREC)))
+      }
+    };
+    if prec < context_prec { format!("({})", text) }
+    else { text }
+  }
+
+  fn display_exprs(&self, exprs: &Vec<usize>, sep: &str, prec: u8) -> String {
+    exprs.iter()
+      .map(|&e| self.display_expr_prec(e, prec))
+      .collect::<Vec<_>>()
+      .join(sep)
+  }
+}
+
+impl<ExprInfo> Index<usize> for Grammar<ExprInfo>
+{
+  type Output = ExprInfo;
+
+  fn index<'a>(&'a self, index: usize) -> &'a Self::Output {
+    &self.exprs_info[index]
+  }
+}
+
+impl<ExprInfo> IndexMut<usize> for Grammar<ExprInfo>
+{
+  fn index_mut<'a>(&'a mut self, index: usize) -> &'a mut Self::Output {
+    &mut self.exprs_info[index]
+  }
+}
+
+impl<ExprInfo> ExprByIndex for Grammar<ExprInfo>
+{
+  fn expr_by_index(&self, index: usize) -> Expression {
+    self.exprs[index].clone()
+  }
+}
+
+#[derive(Clone)]
+pub struct Rule
+{
+  pub name: Ident,
+  pub expr_idx: usize,
+  /// `None` if the visibility is not explicitly given, e.g. `r = e` instead of `pub r = e`.
+  pub visibility: Option<syn::Visibility>,
+  /// Outer attributes of the rule, such as the doc comments `/// ...`.
+  pub attributes: Vec<syn::Attribute>
+}
+
+impl Rule
+{
+  pub fn new(name: Ident, expr_idx: usize, visibility: Option<syn::Visibility>,
+    attributes: Vec<syn::Attribute>) -> Rule
+  {
+    Rule { name, expr_idx, visibility, attributes }
+  }
+
+  pub fn doc_attributes(&self) -> Vec<syn::Attribute> {
+    self.attributes.iter()
+      .filter(|attr| attr.path.is_ident("doc"))
+      .cloned()
+      .collect()
+  }
+
+  /// `true` if the rule has an explicit visibility other than `pub(self)`, which is the same as no visibility.
+  pub fn is_public(&self) -> bool {
+    match self.visibility {
+      None | Some(syn::Visibility::Inherited) => false,
+      Some(syn::Visibility::Restricted(ref restricted)) => !restricted.path.is_ident("self"),
+      Some(_) => true
+    }
+  }
+
+  /// `true` if the rule is annotated with `#[allow(lint)]`, e.g. `#[allow(unused)]`.
+  pub fn is_allowed(&self, lint: &str) -> bool {
+    self.attributes.iter()
+      .filter(|attr| attr.path.is_ident("allow"))
+      .filter_map(|attr| attr.parse_args_with(
+        syn::punctuated::Punctuated::<Ident, syn::Token![,]>::parse_terminated).ok())
+      .any(|lints| lints.iter().any(|l| l == lint))
+  }
+
+  /// `true` if the rule is annotated with `#[explain_type]`.
+  pub fn explain_type(&self) -> bool {
+    self.attributes.iter().any(|attr| attr.path.is_ident("explain_type"))
+  }
+
+  /// `true` if the rule is annotated with `#[trivia]`, its text is spacing or comments for the printer generated by `#![cst]`.
+  pub fn is_trivia(&self) -> bool {
+    self.attributes.iter().any(|attr| attr.path.is_ident("trivia"))
+  }
+
+  /// The text of the doc comments of the rule, one line per doc attribute.
+  pub fn doc(&self) -> String {
+    let mut lines = vec![];
+    for attr in self.doc_attributes() {
+      if let Ok(syn::Meta::NameValue(meta)) = attr.parse_meta() {
+        if let syn::Lit::Str(text) = meta.lit {
+          let text = text.value();
+          lines.push(text.strip_prefix(' ').unwrap_or(&text).to_string());
+        }
+      }
+    }
+    lines.join("\n")
+  }
+}
+
+impl ItemIdent for Rule
+{
+  fn ident(&self) -> Ident {
+    self.name.clone()
+  }
+}
+
+impl Spanned for Rule
+{
+  fn span(&self) -> Span {
+    self.name.span().clone()
+  }
+}
+
+#[derive(Clone, Debug)]
+pub enum Expression
+{
+  StrLiteral(StrLiteralExpr), // "match me"
+  AnySingleChar, // .
+  CharacterClass(CharacterClassExpr), // [0-9]
+  NonTerminalSymbol(Ident), // a_rule
+  ExternalNonTerminalSymbol(syn::Path), // RustItem
+  Sequence(Vec<usize>), // a_rule next_rule
+  Choice(Vec<usize>), // try_this / or_try_this_one
+  ZeroOrMore(usize), // expr*
+  OneOrMore(usize), // expr+
+  ZeroOrOne(usize), // expr?
+  NotPredicate(usize), // !expr
+  AndPredicate(usize), // &expr
+  SemanticAction(usize, bool, syn::Expr), // expr > function, the boolean is true if boxed.
+  TypeAscription(usize, IType), // expr:() or expr:(^) or expr:<rust-ty>
+  SpannedExpr(usize), // .. expr
+  RangeExpr(usize), // ... expr
+}
+
+impl Expression
+{
+  /// The indexes of the direct sub-expressions.
+  pub fn children(&self) -> Vec<usize> {
+    use self::Expression::*;
+    match self {
+      &Sequence(ref exprs) | &Choice(ref exprs) => exprs.clone(),
+      &ZeroOrMore(e) | &OneOrMore(e) | &ZeroOrOne(e) | &NotPredicate(e) | &AndPredicate(e)
+    | &SemanticAction(e, _, _) | &TypeAscription(e, _) | &SpannedExpr(e) | &RangeExpr(e) => vec![e],
+      &StrLiteral(_) | &AnySingleChar | &CharacterClass(_)
+    | &NonTerminalSymbol(_) | &ExternalNonTerminalSymbol(_) => vec![]
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct StrLiteralExpr
+{
+  pub value: String,
+  /// `true` for `"select"i`, matching the literal up to the case of its characters.
+  pub ignore_case: bool
+}
+
+impl StrLiteralExpr
+{
+  pub fn new(value: String, ignore_case: bool) -> StrLiteralExpr {
+    StrLiteralExpr {
+      value: value,
+      ignore_case: ignore_case
+    }
+  }
+}
+
+impl Display for StrLiteralExpr
+{
+  fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error> {
+    formatter.write_fmt(format_args!("{:?}{}", self.value, if self.ignore_case { "i" } else { "" }))
+  }
+}
+
+/// The Unicode sets that can be used in character classes with `\p{..}`, they are generated in `oak_runtime::unicode`.
+pub static UNICODE_SETS: [&'static str; 35] = [
+  "L", "Lu", "Ll", "Lt", "Lm", "Lo",
+  "M", "Mn", "Mc", "Me",
+  "N", "Nd", "Nl", "No",
+  "P", "Pc", "Pd", "Ps", "Pe", "Pi", "Pf", "Po",
+  "S", "Sm", "Sc", "Sk", "So",
+  "Z", "Zs", "Zl", "Zp",
+  "Cc", "Cf",
+  "XID_Start", "XID_Continue"
+];
+
+#[derive(Clone, Debug)]
+pub struct CharacterClassExpr
+{
+  pub intervals: Vec<CharacterInterval>,
+  /// Names of the Unicode sets (`\p{L}`), see `UNICODE_SETS`.
+  pub sets: Vec<String>,
+  /// `true` for `[^"..."]`, matching the characters not in the class.
+  pub negated: bool
+}
+
+impl CharacterClassExpr
+{
+  pub fn new(intervals: Vec<CharacterInterval>, sets: Vec<String>, negated: bool) -> CharacterClassExpr {
+    CharacterClassExpr {
+      intervals: intervals,
+      sets: sets,
+      negated: negated
+    }
+  }
+
+  /// The intervals of the characters matched by this class, or `None` if it uses Unicode sets.
+  pub fn matched_intervals(&self) -> Option<Vec<CharacterInterval>> {
+    if !self.sets.is_empty() {
+      None
+    }
+    else if self.negated {
+      let mut complement = vec![];
+      let mut lo = Some('\0');
+      for interval in &self.intervals {
+        if let Some(l) = lo {
+          if l < interval.lo {
+            complement.push(CharacterInterval::new(l, prev_char(interval.lo)));
+          }
+        }
+        lo = next_char(interval.hi);
+      }
+      if let Some(l) = lo {
+        complement.push(CharacterInterval::new(l, std::char::MAX));
+      }
+      Some(complement)
+    }
+    else {
+      Some(self.intervals.clone())
+    }
+  }
+}
+
+/// The character following `c`, skipping the surrogate code points.
+pub fn next_char(c: char) -> Option<char> {
+  match c {
+    '\u{D7FF}' => Some('\u{E000}'),
+    c => std::char::from_u32(c as u32 + 1)
+  }
+}
+
+/// The character preceding `c`, which must not be `\0`, skipping the surrogate code points.
+pub fn prev_char(c: char) -> char {
+  match c {
+    '\u{E000}' => '\u{D7FF}',
+    c => std::char::from_u32(c as u32 - 1).unwrap()
+  }
+}
+
+impl Display for CharacterClassExpr
+{
+  fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error> {
+    formatter.write_str(if self.negated { "[^\"" } else { "[\"" })?;
+    // A single `-` is printed first so it is not read as an interval separator.
+    let (dash, intervals): (Vec<_>, Vec<_>) = self.intervals.iter()
+      .partition(|interval| interval.lo == '-' && interval.hi == '-');
+    for interval in dash.into_iter().chain(intervals) {
+      interval.fmt(formatter)?;
+    }
+    for set in &self.sets {
+      formatter.write_fmt(format_args!("\\p{{{}}}", set))?;
+    }
+    formatter.write_str("\"]")
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct CharacterInterval
+{
+  pub lo: char,
+  pub hi: char
+}
+
+impl CharacterInterval
+{
+  pub fn new(lo: char, hi: char) -> CharacterInterval {
+    CharacterInterval {
+      lo: lo,
+      hi: hi
+    }
+  }
+
+  pub fn escape_lo(&self) -> String {
+    self.lo.escape_default().collect()
+  }
+
+  pub fn escape_hi(&self) -> String {
+    self.hi.escape_default().collect()
+  }
+}
+
+impl Display for CharacterInterval
+{
+  fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error> {
+    if self.lo == self.hi {
+      formatter.write_str(self.escape_lo().as_str())
+    }
+    else {
+      formatter.write_fmt(format_args!("{}-{}", self.escape_lo(), self.escape_hi()))
+    }
+  }
+}
+
+pub fn display_path_cycle(path: &Vec<Ident>) -> String {
+  let mut path_desc = String::new();
+  for rule in path {
+    path_desc.extend(format!("{} -> ", rule).chars());
+  }
+  path_desc.extend(format!("{}", path[0]).chars());
+  path_desc
+}
+
+/// Prints the tokens without the spaces inserted by `quote` around the punctuation, e.g. `Vec<char>` instead of `Vec < char >`.
+pub fn quote_string<T: quote::ToTokens>(tokens: &T) -> String {
+  format!("{}", quote!(#tokens))
+    .replace(" < ", "<").replace("< ", "<").replace(" >", ">")
+    .replace(" ,", ",").replace("( ", "(").replace(" )", ")")
+    .replace(" :: ", "::").replace(":: ", "::").replace("& ", "&")
+}
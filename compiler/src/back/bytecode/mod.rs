@@ -0,0 +1,254 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The bytecode backend, selected with `#![bytecode]`, compiles the rules to the instructions of the virtual machine `oak_runtime::vm` instead of Rust code.
+//! The parsers are slower but the generated code is much smaller, which matters for large grammars.
+//! We generate in the grammar module:
+//!
+//! ```text
+//! static OAK_PROGRAM: [Instruction; N] = [..];
+//! static OAK_ACTIONS: [Action; M] = [oak_action_0, ..];
+//! fn oak_action_0(values: &mut Vec<Value>) { .. }
+//! fn oak_run<'a>(state: ParseState<Stream<'a>, ()>, entry: usize) -> ParseState<Stream<'a>, Value>
+//! ```
+//!
+//! The function `oak_action_i` pops the arguments of the i-th semantic action, converts them to their Rust types, and pushes its result.
+//! The recognizer and parser functions of the rules have the same signatures as with the default backend, they run the code of the rule with `oak_run` and convert its value.
+//! The Rust values are stored in `Value::Rust(Box<dyn Any>)`, which requires the types of the rules to be `'static` (see `middle::typing::bytecode_value`).
+
+mod program;
+mod value;
+
+use middle::typing::ast::*;
+use back::compiler::CompilerKind;
+use back::compiler::semantic_action::is_unit_variant;
+use back::context::Context;
+use back::expectation::*;
+use back::name_factory::*;
+use self::program::*;
+use self::value::*;
+
+use quote::format_ident;
+use syn::{parse_quote, parse_quote_spanned};
+
+pub struct BytecodeCompiler<'a>
+{
+  grammar: &'a TGrammar,
+  expectations: &'a ExpectationTable
+}
+
+impl<'a> BytecodeCompiler<'a>
+{
+  /// Compiles the rules to the items described above.
+  pub fn compile(grammar: &'a TGrammar, expectations: &'a ExpectationTable) -> Vec<syn::Item> {
+    let compiler = BytecodeCompiler { grammar, expectations };
+    let functions = compiler.rule_functions();
+    let program = ProgramCompiler::compile(grammar, expectations, functions.clone());
+    let mut items = vec![
+      compiler.compile_program(&program),
+      compiler.compile_action_table(&program)
+    ];
+    items.extend(program.actions.iter().enumerate()
+      .map(|(i, &action_idx)| compiler.compile_action(i, action_idx)));
+    items.push(compiler.compile_run(&program));
+    items.extend(functions.into_iter()
+      .map(|(rule, kind)| compiler.compile_rule_function(&program, rule, kind)));
+    items
+  }
+
+  /// The recognizer and parser functions of the exposed rules, and the parser functions of the start rules, in the order of the rules' declarations.
+  /// The parser of a rule of type unit calls its recognizer.
+  fn rule_functions(&self) -> Vec<(Ident, CompilerKind)> {
+    let mut functions = vec![];
+    for rule in &self.grammar.rules {
+      let id = rule.ident();
+      let exposed = self.grammar.is_exposed(rule);
+      let parser = exposed || self.grammar.attributes.starts.contains(&id);
+      let unit = self.grammar[rule.expr_idx].ty == Type::Unit;
+      if exposed || (parser && unit) {
+        functions.push((id.clone(), CompilerKind::Recognizer));
+      }
+      if parser {
+        functions.push((id, CompilerKind::Parser));
+      }
+    }
+    functions
+  }
+
+  fn compile_program(&self, program: &Program) -> syn::Item {
+    let instructions: Vec<syn::Expr> = program.code.iter()
+      .map(|instruction| self.compile_instruction(program, instruction))
+      .collect();
+    let len = instructions.len();
+    parse_quote!(
+      static OAK_PROGRAM: [oak_runtime::vm::Instruction; #len] = {
+        use oak_runtime::vm::Instruction::*;
+        [#(#instructions),*]
+      };
+    )
+  }
+
+  fn compile_instruction(&self, program: &Program, instruction: &Instruction) -> syn::Expr {
+    match instruction {
+      &Instruction::Literal(ref lit, expect) => {
+        let value = lit.value.as_str();
+        if lit.ignore_case { parse_quote!(LiteralIgnoreCase(#value, #expect)) }
+        else { parse_quote!(Literal(#value, #expect)) }
+      }
+      &Instruction::AnySingleChar(expect, push) => parse_quote!(AnySingleChar(#expect, #push)),
+      &Instruction::CharacterClass(ref classes, expect, push) => {
+        let class = compile_character_class(classes);
+        parse_quote!(CharacterClass(&#class, #expect, #push))
+      }
+      &Instruction::Choice(target) => parse_quote!(Choice(#target)),
+      &Instruction::Commit(target) => parse_quote!(Commit(#target)),
+      &Instruction::BackCommit(target) => parse_quote!(BackCommit(#target)),
+      &Instruction::FailTwice => parse_quote!(FailTwice),
+      &Instruction::Fail => parse_quote!(Fail),
+      &Instruction::Call(ref rule, kind) => {
+        let target = program.address(self.grammar, rule, kind);
+        parse_quote!(Call(#target))
+      }
+      &Instruction::Return => parse_quote!(Return),
+      &Instruction::External(external, push) => parse_quote!(External(#external, #push)),
+      &Instruction::Action(action) => parse_quote!(Action(#action)),
+      &Instruction::PushList => parse_quote!(PushList),
+      &Instruction::Append(n) => parse_quote!(Append(#n)),
+      &Instruction::PushNone => parse_quote!(PushNone),
+      &Instruction::PushSome(n) => parse_quote!(PushSome(#n)),
+      &Instruction::Mark => parse_quote!(Mark),
+      &Instruction::Span(n) => parse_quote!(Span(#n))
+    }
+  }
+
+  fn compile_action_table(&self, program: &Program) -> syn::Item {
+    let actions: Vec<Ident> = (0..program.actions.len()).map(action_name).collect();
+    let len = actions.len();
+    parse_quote!(
+      static OAK_ACTIONS: [oak_runtime::vm::Action; #len] = [#(#actions),*];
+    )
+  }
+
+  /// The arguments are converted from the values on the stack, and the call is located on the semantic action in the grammar as with the default backend.
+  fn compile_action(&self, i: usize, action_idx: usize) -> syn::Item {
+    let name = action_name(i);
+    let (expr_idx, boxed, action) = match self.grammar.expr_by_index(action_idx) {
+      SemanticAction(expr_idx, boxed, action) => (expr_idx, boxed, action),
+      _ => unreachable!("[bug] the `Action` instructions refer to semantic actions.")
+    };
+    let span = self.span_of(action_idx);
+    let arity = self.grammar[expr_idx].type_cardinality();
+    let arg_indexes = match self.grammar[expr_idx].ty {
+      Type::Tuple(ref indexes) => indexes.clone(),
+      _ => vec![expr_idx; arity]
+    };
+    let args: Vec<syn::Expr> = arg_indexes.into_iter()
+      .map(|idx| convert(self.grammar, idx, parse_quote!(args.next().unwrap())))
+      .collect();
+    let action_call: syn::Expr =
+      if is_unit_variant(&action, arity == 0) {
+        parse_quote_spanned!(span=> #action)
+      }
+      else {
+        parse_quote_spanned!(span=> #action(#(#args),*))
+      };
+    let action_call: syn::Expr =
+      if boxed { parse_quote_spanned!(span=> Box::new(#action_call)) }
+      else { action_call };
+    let pop_args: Option<syn::Stmt> =
+      if arity > 0 { Some(parse_quote!(let mut args = oak_runtime::vm::pop_values(values, #arity);)) }
+      else { None };
+    parse_quote!(
+      fn #name(values: &mut Vec<oak_runtime::vm::Value>) {
+        #pop_args
+        let result = #action_call;
+        values.push(oak_runtime::vm::Value::rust(result));
+      }
+    )
+  }
+
+  /// The external parsers depend on the type of the stream, they are given to the machine with the span function, if needed.
+  fn compile_run(&self, program: &Program) -> syn::Item {
+    let stream_ty = self.grammar.stream_type();
+    let generics = self.grammar.stream_generics();
+    let table = table_name();
+    let externals: Vec<syn::Expr> = program.externals.iter()
+      .map(|&idx| {
+        let path = match self.grammar.expr_by_index(idx) {
+          ExternalNonTerminalSymbol(path) => path,
+          _ => unreachable!("[bug] the `External` instructions refer to external parsers.")
+        };
+        let parser_fn = parser_name(path);
+        let span = self.span_of(idx);
+        parse_quote_spanned!(span=> |state| oak_runtime::vm::external(#parser_fn(state)))
+      })
+      .collect();
+    let len = externals.len();
+    let with_span: Option<proc_macro2::TokenStream> =
+      if program.spans { Some(quote::quote!(.with_span(|range| oak_runtime::vm::Value::rust(range.stream_span())))) }
+      else { None };
+    parse_quote!(
+      #[allow(dead_code)]
+      fn oak_run #generics (state: oak_runtime::ParseState<#stream_ty, ()>, entry: usize)
+        -> oak_runtime::ParseState<#stream_ty, oak_runtime::vm::Value>
+      {
+        let externals: [oak_runtime::vm::External<#stream_ty>; #len] = [#(#externals),*];
+        oak_runtime::vm::Machine::new(&OAK_PROGRAM, &#table, &OAK_ACTIONS, &externals)
+          #with_span
+          .run(entry, state)
+      }
+    )
+  }
+
+  fn compile_rule_function(&self, program: &Program, rule: Ident, kind: CompilerKind) -> syn::Item {
+    let rule = self.grammar.find_rule_by_ident(&rule);
+    let context = Context::new(self.grammar, self.expectations);
+    let entry = program.address(self.grammar, &rule.ident(), kind);
+    match kind {
+      CompilerKind::Recognizer => {
+        let body = parse_quote!(oak_runtime::vm::map(oak_run(state, #entry), |_| ()));
+        context.into_rule_function(kind, false, body, rule)
+      }
+      CompilerKind::Parser if self.grammar[rule.expr_idx].ty == Type::Unit => context.into_parser_alias(rule),
+      CompilerKind::Parser => {
+        let value = convert(self.grammar, rule.expr_idx, parse_quote!(value));
+        let body = parse_quote!(oak_runtime::vm::map(oak_run(state, #entry), |value| #value));
+        context.into_rule_function(kind, false, body, rule)
+      }
+    }
+  }
+
+  fn span_of(&self, expr_idx: usize) -> Span {
+    Span::call_site().located_at(self.grammar[expr_idx].span())
+  }
+}
+
+fn action_name(i: usize) -> Ident {
+  format_ident!("oak_action_{}", i)
+}
+
+/// The class `classes` as a constant `oak_runtime::vm::CharacterClass`, it is also used by the printer generated with `#![cst]`.
+pub fn compile_character_class(classes: &CharacterClassExpr) -> syn::Expr {
+  let intervals = classes.intervals.iter()
+    .map(|interval| { let (lo, hi) = (interval.lo, interval.hi); quote::quote!((#lo, #hi)) });
+  let sets = classes.sets.iter()
+    .map(|set| format_ident!("{}", set.to_uppercase()));
+  let negated = classes.negated;
+  parse_quote!(
+    oak_runtime::vm::CharacterClass {
+      intervals: &[#(#intervals),*],
+      sets: &[#(&oak_runtime::unicode::#sets),*],
+      negated: #negated
+    })
+}
@@ -0,0 +1,277 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compiles the parsing expressions to the instructions of `oak_runtime::vm`.
+//! As with the recognizer and parser functions, each rule has a recognizer code which does not push values, and a parser code.
+//! An expression of type unit is always compiled as a recognizer.
+
+use middle::typing::ast::*;
+use back::compiler::CompilerKind;
+use back::expectation::*;
+
+use std::collections::HashMap;
+
+pub enum Instruction
+{
+  Literal(StrLiteralExpr, usize),
+  AnySingleChar(usize, bool),
+  CharacterClass(CharacterClassExpr, usize, bool),
+  Choice(usize),
+  Commit(usize),
+  BackCommit(usize),
+  FailTwice,
+  Fail,
+  /// The address is known when all the rules are compiled.
+  Call(Ident, CompilerKind),
+  Return,
+  External(usize, bool),
+  Action(usize),
+  PushList,
+  Append(usize),
+  PushNone,
+  PushSome(usize),
+  Mark,
+  Span(usize)
+}
+
+pub struct Program
+{
+  pub code: Vec<Instruction>,
+  /// The address of the recognizer and parser code of the rules compiled.
+  pub entries: HashMap<(Ident, CompilerKind), usize>,
+  /// The semantic actions called by the `Action` instructions, they are given by the index of their expression.
+  pub actions: Vec<usize>,
+  /// The calls to external parsers of the `External` instructions, given by the index of their expression.
+  pub externals: Vec<usize>,
+  /// `true` if there is a `Span` instruction.
+  pub spans: bool
+}
+
+impl Program
+{
+  pub fn address(&self, grammar: &TGrammar, rule: &Ident, kind: CompilerKind) -> usize {
+    self.entries[&(rule.clone(), code_kind(grammar, rule, kind))]
+  }
+}
+
+/// The parser of a rule of type unit is its recognizer.
+fn code_kind(grammar: &TGrammar, rule: &Ident, kind: CompilerKind) -> CompilerKind {
+  if grammar[grammar.expr_index_of_rule(rule)].ty == Type::Unit { CompilerKind::Recognizer }
+  else { kind }
+}
+
+pub struct ProgramCompiler<'a>
+{
+  grammar: &'a TGrammar,
+  expectations: &'a ExpectationTable,
+  program: Program,
+  worklist: Vec<(Ident, CompilerKind)>
+}
+
+impl<'a> ProgramCompiler<'a>
+{
+  /// Compiles the code of the rules `roots`, and of the rules they call.
+  pub fn compile(grammar: &'a TGrammar, expectations: &'a ExpectationTable,
+    roots: Vec<(Ident, CompilerKind)>) -> Program
+  {
+    let mut compiler = ProgramCompiler {
+      grammar, expectations,
+      program: Program { code: vec![], entries: HashMap::new(), actions: vec![], externals: vec![], spans: false },
+      worklist: vec![]
+    };
+    for (rule, kind) in roots {
+      compiler.use_rule(rule, kind);
+    }
+    while let Some((rule, kind)) = compiler.worklist.pop() {
+      let entry = compiler.program.code.len();
+      compiler.program.entries.insert((rule.clone(), kind), entry);
+      compiler.compile_expr(grammar.expr_index_of_rule(&rule), kind);
+      compiler.emit(Instruction::Return);
+    }
+    compiler.program
+  }
+
+  /// Registers the code of `rule` to compile, and returns its kind.
+  fn use_rule(&mut self, rule: Ident, kind: CompilerKind) -> CompilerKind {
+    let kind = code_kind(self.grammar, &rule, kind);
+    let key = (rule, kind);
+    if !self.program.entries.contains_key(&key) && !self.worklist.contains(&key) {
+      self.worklist.push(key);
+    }
+    kind
+  }
+
+  fn emit(&mut self, instruction: Instruction) -> usize {
+    self.program.code.push(instruction);
+    self.program.code.len() - 1
+  }
+
+  /// Sets the target of the jump at `address` to the next instruction.
+  fn patch(&mut self, address: usize) {
+    let next = self.program.code.len();
+    match self.program.code[address] {
+      Instruction::Choice(ref mut target)
+    | Instruction::Commit(ref mut target)
+    | Instruction::BackCommit(ref mut target) => *target = next,
+      _ => unreachable!("[bug] only the jumps can be patched.")
+    }
+  }
+
+  fn register(items: &mut Vec<usize>, expr_idx: usize) -> usize {
+    match items.iter().position(|&idx| idx == expr_idx) {
+      Some(pos) => pos,
+      None => {
+        items.push(expr_idx);
+        items.len() - 1
+      }
+    }
+  }
+
+  fn cardinality(&self, expr_idx: usize) -> usize {
+    self.grammar[expr_idx].type_cardinality()
+  }
+
+  fn compile_expr(&mut self, idx: usize, kind: CompilerKind) {
+    let kind =
+      if self.grammar[idx].ty == Type::Unit { CompilerKind::Recognizer }
+      else { kind };
+    let parser = kind == CompilerKind::Parser;
+    match self.grammar.expr_by_index(idx) {
+      StrLiteral(lit) => {
+        let expect = self.expectations.id_of(&str_literal_item(&lit));
+        self.emit(Instruction::Literal(lit, expect));
+      }
+      AnySingleChar => {
+        let expect = self.expectations.id_of(&any_single_char_item());
+        self.emit(Instruction::AnySingleChar(expect, parser));
+      }
+      CharacterClass(classes) => {
+        let expect = self.expectations.id_of(&char_class_item(&classes));
+        self.emit(Instruction::CharacterClass(classes, expect, parser));
+      }
+      Sequence(seq) => {
+        for child in seq {
+          self.compile_expr(child, kind);
+        }
+      }
+      Choice(choices) => self.compile_choice(choices, kind),
+      ZeroOrMore(child) => {
+        if parser { self.emit(Instruction::PushList); }
+        self.compile_repeat(child, kind);
+      }
+      OneOrMore(child) => {
+        if parser { self.emit(Instruction::PushList); }
+        self.compile_element(child, kind);
+        self.compile_repeat(child, kind);
+      }
+      ZeroOrOne(child) => self.compile_optional(child, kind),
+      NotPredicate(child) => {
+        let choice = self.emit(Instruction::Choice(0));
+        self.compile_expr(child, CompilerKind::Recognizer);
+        self.emit(Instruction::FailTwice);
+        self.patch(choice);
+      }
+      AndPredicate(child) => {
+        let choice = self.emit(Instruction::Choice(0));
+        self.compile_expr(child, CompilerKind::Recognizer);
+        let commit = self.emit(Instruction::BackCommit(0));
+        self.patch(choice);
+        self.emit(Instruction::Fail);
+        self.patch(commit);
+      }
+      NonTerminalSymbol(rule) => {
+        let kind = self.use_rule(rule.clone(), kind);
+        self.emit(Instruction::Call(rule, kind));
+      }
+      ExternalNonTerminalSymbol(_) => {
+        let external = Self::register(&mut self.program.externals, idx);
+        self.emit(Instruction::External(external, parser));
+      }
+      SemanticAction(child, _, _) => {
+        self.compile_expr(child, kind);
+        if parser {
+          let action = Self::register(&mut self.program.actions, idx);
+          self.emit(Instruction::Action(action));
+        }
+      }
+      TypeAscription(child, _) => self.compile_expr(child, kind),
+      SpannedExpr(child) => {
+        if parser { self.emit(Instruction::Mark); }
+        self.compile_expr(child, kind);
+        if parser {
+          self.program.spans = true;
+          let n = self.cardinality(child);
+          self.emit(Instruction::Span(n));
+        }
+      }
+      RangeExpr(_) => unreachable!("[bug] the range expressions are rejected with `#![bytecode]`.")
+    }
+  }
+
+  /// `e1 / e2 / e3` is compiled to `Choice(l1) e1 Commit(end) l1: Choice(l2) e2 Commit(end) l2: e3 end:`.
+  fn compile_choice(&mut self, choices: Vec<usize>, kind: CompilerKind) {
+    let last = choices.len() - 1;
+    let mut commits = vec![];
+    for (i, child) in choices.into_iter().enumerate() {
+      if i < last {
+        let choice = self.emit(Instruction::Choice(0));
+        self.compile_expr(child, kind);
+        commits.push(self.emit(Instruction::Commit(0)));
+        self.patch(choice);
+      }
+      else {
+        self.compile_expr(child, kind);
+      }
+    }
+    for commit in commits {
+      self.patch(commit);
+    }
+  }
+
+  /// An element of a list is appended to the list once it is parsed.
+  fn compile_element(&mut self, child: usize, kind: CompilerKind) {
+    self.compile_expr(child, kind);
+    if kind == CompilerKind::Parser {
+      let n = self.cardinality(child);
+      self.emit(Instruction::Append(n));
+    }
+  }
+
+  /// `e*` is compiled to `loop: Choice(end) e Commit(loop) end:`, thus `e+` is `e e*`.
+  fn compile_repeat(&mut self, child: usize, kind: CompilerKind) {
+    let choice = self.emit(Instruction::Choice(0));
+    self.compile_element(child, kind);
+    self.emit(Instruction::Commit(choice));
+    self.patch(choice);
+  }
+
+  /// `e?` is compiled to `Choice(none) e PushSome Commit(end) none: PushNone end:`.
+  fn compile_optional(&mut self, child: usize, kind: CompilerKind) {
+    let choice = self.emit(Instruction::Choice(0));
+    self.compile_expr(child, kind);
+    if kind == CompilerKind::Parser {
+      let n = self.cardinality(child);
+      self.emit(Instruction::PushSome(n));
+      let commit = self.emit(Instruction::Commit(0));
+      self.patch(choice);
+      self.emit(Instruction::PushNone);
+      self.patch(commit);
+    }
+    else {
+      let commit = self.emit(Instruction::Commit(0));
+      self.patch(choice);
+      self.patch(commit);
+    }
+  }
+}
@@ -0,0 +1,55 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Converts a `oak_runtime::vm::Value` to the Rust type of an expression, for instance a value of type `Vec<(char, T)>` is converted with:
+//!
+//! ```text
+//! value.into_list(|value| {
+//!   let mut fields = value.into_fields();
+//!   (fields.next().unwrap().into_char(), fields.next().unwrap().into_rust::<T>())
+//! })
+//! ```
+
+use middle::typing::ast::*;
+use middle::typing::ast::Type::*;
+use syn::parse_quote;
+
+/// The Rust value of the expression `expr_idx` from `value`.
+pub fn convert(grammar: &TGrammar, expr_idx: usize, value: syn::Expr) -> syn::Expr {
+  match grammar[expr_idx].ty.clone() {
+    Unit => parse_quote!(()),
+    Atom => parse_quote!(#value.into_char()),
+    // The span type refers to the stream type, which is not available in the semantic actions, it is inferred instead.
+    Rust(ty) =>
+      if ty == grammar.span_type() { parse_quote!(#value.into_rust()) }
+      else { parse_quote!(#value.into_rust::<#ty>()) },
+    List(child) => {
+      let element = convert(grammar, child, parse_quote!(value));
+      parse_quote!(#value.into_list(|value| #element))
+    }
+    Optional(child) => {
+      let element = convert(grammar, child, parse_quote!(value));
+      parse_quote!(#value.into_optional(|value| #element))
+    }
+    Tuple(indexes) => {
+      let fields: Vec<syn::Expr> = indexes.into_iter()
+        .map(|idx| convert(grammar, idx, parse_quote!(fields.next().unwrap())))
+        .collect();
+      parse_quote!({
+        let mut fields = #value.into_fields();
+        (#(#fields),*)
+      })
+    }
+  }
+}
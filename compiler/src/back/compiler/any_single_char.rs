@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use back::compiler::*;
+use back::expectation::any_single_char_item;
 
 type MatchPatternFn = for <'a> fn(&mut Context<'a>) -> syn::Pat;
 
@@ -51,6 +52,7 @@ impl CompileExpr for AnySingleCharCompiler
     continuation: Continuation) -> syn::Expr
   {
     let pattern = (self.matched_pattern)(context);
+    let error = context.compile_error(any_single_char_item().as_str());
     continuation
       .map_success(|success, failure| parse_quote!(
         match state.next() {
@@ -58,7 +60,7 @@ impl CompileExpr for AnySingleCharCompiler
             #success
           }
           None => {
-            state.error("<character>");
+            #error;
             #failure
           }
         }
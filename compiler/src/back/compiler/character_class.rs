@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use back::compiler::*;
+use back::expectation::char_class_item;
 use quote::format_ident;
 
 type VarInPatternFn = for <'a> fn(&mut Context<'a>) -> Ident;
@@ -58,16 +59,25 @@ impl CharacterClassCompiler
     }
   }
 
+  fn compile_set(&self, set: &String, x: Ident) -> syn::Expr {
+    let table = format_ident!("{}", set.to_uppercase());
+    parse_quote!(oak_runtime::unicode::contains(oak_runtime::unicode::#table, #x))
+  }
+
   fn compile_condition(&self, x: Ident) -> syn::Expr {
-    let mut intervals = self.classes.intervals.iter().cloned();
-    let first_interval = intervals.next()
-      .expect("Empty character intervals should be forbidden at the parsing stage.");
-    intervals
+    let mut conditions = self.classes.intervals.iter().cloned()
       .map(|char_interval| self.compile_interval(char_interval, x.clone()))
-      .fold(
-        self.compile_interval(first_interval, x.clone()),
-        |accu, interval| parse_quote!(#accu || #interval)
-      )
+      .chain(self.classes.sets.iter().map(|set| self.compile_set(set, x.clone())));
+    let first_condition = conditions.next()
+      .expect("Empty character classes should be forbidden at the parsing stage.");
+    let condition = conditions.fold(first_condition,
+      |accu, condition| parse_quote!(#accu || #condition));
+    if self.classes.negated {
+      parse_quote!(!(#condition))
+    }
+    else {
+      condition
+    }
   }
 }
 
@@ -76,9 +86,7 @@ impl CompileExpr for CharacterClassCompiler
   fn compile_expr<'a>(&self, context: &mut Context<'a>,
     continuation: Continuation) -> syn::Expr
   {
-    let classes_desc = format!("{}", self.classes);
-    let classes_desc_str = classes_desc.as_str();
-
+    let error = context.compile_error(char_class_item(&self.classes).as_str());
     let var = (self.bounded_var)(context);
     let condition = self.compile_condition(var.clone());
     let mark = context.next_mark_name();
@@ -91,7 +99,7 @@ impl CompileExpr for CharacterClassCompiler
           }
           _ => {
             state = state.restore(#mark);
-            state.error(#classes_desc_str);
+            #error;
             #failure
           }
         }
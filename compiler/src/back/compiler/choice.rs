@@ -47,7 +47,8 @@ impl CompileExpr for ChoiceCompiler
 
     let mark = context.next_mark_name();
     let branch_failed = context.next_branch_failed_name();
-    context.push_mut_ref_fv(branch_failed.clone(), parse_quote!(bool));
+    // `branch_failed` is a `Cell` because the success continuation can be shared in a closure where it is passed by reference.
+    context.push_ref_fv(branch_failed.clone(), parse_quote!(std::cell::Cell<bool>));
 
     // Each branch of the choice must be compiled in the same variable names environment (they share names of the variables they are building) and with a fresh success continuation size (each branch might create independent success continuation).
     let scope = context.save_scope();
@@ -58,14 +59,14 @@ impl CompileExpr for ChoiceCompiler
       .map(|idx| {
         context.restore_scope(scope.clone());
         let b = branch_failed.clone();
-        let success: syn::Stmt = parse_quote!(#b = false;);
+        let success: syn::Stmt = parse_quote!(#b.set(false););
         continuation.compile_and_wrap(context, self.compiler, idx, success)
       })
       .collect();
 
     // The last branch does not need to assign `false` to the variable `branch_failed`.
     context.restore_scope(scope.clone());
-    context.pop_mut_ref_fv();
+    context.pop_ref_fv();
     let (success, failure) = continuation.unwrap();
     branches.push(context.compile(self.compiler, last, success, failure));
 
@@ -76,7 +77,7 @@ impl CompileExpr for ChoiceCompiler
       .rev()
       .fold(parse_quote!(state), |accu: syn::Expr, branch|
         parse_quote!({
-          if #branch_failed {
+          if #branch_failed.get() {
             let mut state = state.restore_from_failure(#mark.clone());
             let state = #branch;
             #accu
@@ -86,7 +87,7 @@ impl CompileExpr for ChoiceCompiler
 
     parse_quote!({
       let #mark = state.mark();
-      let mut #branch_failed = true;
+      let #branch_failed = std::cell::Cell::new(true);
       let state = #first;
       #choice
     })
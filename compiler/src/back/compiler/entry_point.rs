@@ -0,0 +1,173 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generates the entry points of the rules declared with `#![start(r1, ..., rN)]`.
+//! For a single start rule `r`, we generate in the grammar module:
+//!
+//! ```text
+//! pub fn parse<'a, OakInput>(input: OakInput) -> Result<T, ParseError> where
+//!   OakInput: oak_runtime::stream::Stream<Output=Stream<'a>>
+//! pub fn parse_prefix<'a, OakInput>(input: OakInput) -> Result<(T, Stream<'a>), ParseError> where ...
+//! ```
+//!
+//! where `T` is the type of `r`. With `#![cst]`, `parse_cst` also returns the concrete syntax tree of the input:
+//!
+//! ```text
+//! pub fn parse_cst<'a, OakInput>(input: OakInput) -> Result<(T, oak_runtime::cst::SyntaxNode), ParseError> where ...
+//! pub fn reparse_cst<'oak_text, 'a>(tree: &SyntaxNode, edit: &TextEdit, text: &'oak_text str) -> Result<Reparse, ParseError> where
+//!   Stream<'a>: oak_runtime::incremental::TextStream<'oak_text>, ...
+//! ```
+//!
+//! where `reparse_cst` can only be called when the stream implements `TextStream`, such as `StrStream`.
+//! The constant `OAK_LOOKAHEAD` is also generated in the grammar module to reparse the other rules with `oak_runtime::incremental::reparse`.
+//!
+//! With several start rules, these functions are generated in a module named after each rule, e.g. `r1::parse`.
+
+use back::compiler::*;
+use back::compiler::rtype::*;
+use back::name_factory::*;
+
+use quote::format_ident;
+
+pub struct EntryPointCompiler<'a>
+{
+  grammar: &'a TGrammar
+}
+
+impl<'a> EntryPointCompiler<'a>
+{
+  pub fn compile(grammar: &'a TGrammar) -> Vec<syn::Item> {
+    let compiler = EntryPointCompiler { grammar };
+    let starts = grammar.attributes.starts.clone();
+    let mut items =
+      if starts.len() == 1 {
+        compiler.compile_entry_points(&starts[0])
+      }
+      else {
+        starts.iter()
+          .map(|rule| compiler.compile_entry_points_mod(rule))
+          .collect()
+      };
+    if grammar.attributes.cst {
+      items.push(compiler.compile_lookahead());
+    }
+    items
+  }
+
+  fn compile_entry_points_mod(&self, rule: &Ident) -> syn::Item {
+    let entry_points = self.compile_entry_points(rule);
+    let doc = format!("Entry points of the start rule `{}`.", rule);
+    parse_quote!(
+      #[doc = #doc]
+      pub mod #rule {
+        #[allow(unused_imports)]
+        use super::*;
+        #(#entry_points)*
+      }
+    )
+  }
+
+  fn compile_entry_points(&self, rule: &Ident) -> Vec<syn::Item> {
+    let parser_fn = parser_id(rule.clone());
+    let ty = TypeCompiler::compile(self.grammar, self.grammar.expr_index_of_rule(rule));
+    let stream_ty = self.grammar.stream_type();
+    let input_ty = format_ident!("OakInput");
+    let mut generics = self.grammar.stream_generics();
+    generics.params.push(parse_quote!(#input_ty));
+    generics.make_where_clause().predicates.push(
+      parse_quote!(#input_ty: oak_runtime::stream::Stream<Output=#stream_ty>));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let state: syn::Expr = parse_quote!(
+      oak_runtime::ParseState::new(oak_runtime::stream::Stream::stream(input)));
+    let parse_doc = format!("Parses the full input with the rule `{}`.", rule);
+    let parse_prefix_doc = format!("Parses a prefix of the input with the rule `{}` and returns the stream positioned after the data read.", rule);
+    let mut entry_points = vec![
+      parse_quote!(
+        #[doc = #parse_doc]
+        pub fn parse #impl_generics (input: #input_ty) -> Result<#ty, oak_runtime::ParseError> #where_clause
+        {
+          #parser_fn(#state).into_full_result()
+        }
+      ),
+      parse_quote!(
+        #[doc = #parse_prefix_doc]
+        pub fn parse_prefix #impl_generics (input: #input_ty) -> Result<(#ty, #stream_ty), oak_runtime::ParseError> #where_clause
+        {
+          #parser_fn(#state).into_prefix_result()
+        }
+      )
+    ];
+    if self.grammar.attributes.cst {
+      let kind = rule.to_string();
+      let parse_cst_doc = format!("Parses the full input with the rule `{}` and builds its concrete syntax tree.", rule);
+      entry_points.push(parse_quote!(
+        #[doc = #parse_cst_doc]
+        pub fn parse_cst #impl_generics (input: #input_ty)
+          -> Result<(#ty, oak_runtime::cst::SyntaxNode), oak_runtime::ParseError> #where_clause
+        {
+          let start = oak_runtime::stream::Stream::stream(input);
+          let state = #parser_fn(oak_runtime::ParseState::new(start.clone()).with_cst());
+          let tree = state.syntax_tree(#kind, start);
+          state.into_full_result().map(|data| (data, tree))
+        }
+      ));
+    }
+    if self.grammar.attributes.cst {
+      entry_points.push(self.compile_reparse_cst(rule));
+    }
+    entry_points
+  }
+
+  /// The stream type is an alias which cannot be resolved, thus the traits needed to move the nodes in the edited text are required in the `where` clause.
+  fn compile_reparse_cst(&self, rule: &Ident) -> syn::Item {
+    let recognizer_fn = recognizer_id(rule.clone());
+    let stream_ty = self.grammar.stream_type();
+    let mut generics = self.grammar.stream_generics();
+    generics.params.insert(0, parse_quote!('oak_text));
+    let where_clause = generics.make_where_clause();
+    where_clause.predicates.push(parse_quote!(#stream_ty: oak_runtime::incremental::TextStream<'oak_text>));
+    where_clause.predicates.push(parse_quote!(std::ops::Range<#stream_ty>:
+      oak_runtime::stream::StreamText + oak_runtime::stream::StreamSpan<Output=oak_runtime::Span>));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let reparse_cst_doc = format!("Parses `text`, the input of `tree` modified by `edit`, with the rule `{}` and reuses the nodes of `tree` not affected by the edit.", rule);
+    parse_quote!(
+      #[doc = #reparse_cst_doc]
+      pub fn reparse_cst #impl_generics (tree: &oak_runtime::cst::SyntaxNode, edit: &oak_runtime::incremental::TextEdit,
+        text: &'oak_text str) -> Result<oak_runtime::incremental::Reparse, oak_runtime::ParseError> #where_clause
+      {
+        oak_runtime::incremental::reparse(tree, edit, text, OAK_LOOKAHEAD, #recognizer_fn)
+      }
+    )
+  }
+
+  fn compile_lookahead(&self) -> syn::Item {
+    let lookahead = self.lookahead();
+    parse_quote!(
+      /// The number of bytes the parser can examine after the farthest position it reached, see `oak_runtime::incremental::reparse`.
+      pub const OAK_LOOKAHEAD: usize = #lookahead;
+    )
+  }
+
+  /// The number of bytes examined after the farthest position reached: a character, or a string literal which does not match.
+  /// A character of a literal compared without case can match a character encoded with more bytes.
+  fn lookahead(&self) -> usize {
+    self.grammar.exprs.iter()
+      .filter_map(|expr| match expr {
+        &Expression::StrLiteral(ref lit) if lit.ignore_case => Some(lit.value.chars().count() * 4),
+        &Expression::StrLiteral(ref lit) => Some(lit.value.len()),
+        _ => None
+      })
+      .fold(4, usize::max)
+  }
+}
@@ -0,0 +1,126 @@
+// Copyright 2016 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub use middle::typing::ast::*;
+use back::compiler::rule::*;
+use back::compiler::entry_point::*;
+use back::compiler::reference::*;
+use back::compiler::visitor::*;
+use back::compiler::printer::*;
+use back::compiler::CompilerKind;
+use back::expectation::ExpectationTable;
+use back::bytecode::BytecodeCompiler;
+
+use quote::quote;
+use std::collections::HashMap;
+
+pub struct GrammarCompiler
+{
+  grammar: TGrammar,
+  /// Attached to every generated item when the grammar is compiled for one configuration of its `#[cfg(..)]` attributes.
+  cfg: Option<syn::Attribute>
+}
+
+impl GrammarCompiler
+{
+  pub fn compile(grammar: TGrammar, cfg: Option<syn::Attribute>) -> proc_macro2::TokenStream {
+    let compiler = GrammarCompiler::new(grammar, cfg);
+    let mod_content = compiler.compile_mod_content();
+    let result = compiler.compile_grammar_module(mod_content);
+    result
+  }
+
+  fn new(grammar: TGrammar, cfg: Option<syn::Attribute>) -> GrammarCompiler {
+    GrammarCompiler {
+      grammar: grammar,
+      cfg: cfg
+    }
+  }
+
+  fn compile_grammar_module(&self, module_content: Vec<syn::Item>) -> proc_macro2::TokenStream {
+    let cfg = &self.cfg;
+    let cfgs = std::iter::repeat(cfg).take(module_content.len());
+    quote!(
+      // #![allow(unused_mut)]
+      #cfg
+      #[allow(unused_imports)]
+      use oak_runtime::stream::*;
+      #cfg
+      #[allow(unused_imports)]
+      use oak_runtime::str_stream::StrStream;
+      #cfg
+      #[allow(unused_imports)]
+      use std::ops::Range;
+
+      #(#cfgs #module_content)*
+    )
+  }
+
+  fn compile_mod_content(&self) -> Vec<syn::Item> {
+    let expectations = ExpectationTable::new(&self.grammar);
+    let mut mod_content = self.grammar.rust_items.clone();
+    mod_content.push(expectations.compile());
+    if self.grammar.attributes.bytecode {
+      mod_content.extend(BytecodeCompiler::compile(&self.grammar, &expectations).into_iter());
+    }
+    else {
+      mod_content.extend(self.compile_rules(&expectations).into_iter());
+    }
+    mod_content.extend(EntryPointCompiler::compile(&self.grammar).into_iter());
+    mod_content.extend(GrammarReferenceCompiler::compile(&self.grammar).into_iter());
+    mod_content.extend(VisitorCompiler::compile(&self.grammar).into_iter());
+    mod_content.extend(PrinterCompiler::compile(&self.grammar).into_iter());
+    mod_content.extend(self.grammar.rust_functions.values().cloned()
+      .map(syn::Item::Fn));
+    mod_content
+  }
+
+  /// Compiles the recognizer and parser functions of the exposed rules, and of the private rules only if they are called from another compiled function or an entry point.
+  /// The functions are generated in the order of the rules' declarations.
+  fn compile_rules(&self, expectations: &ExpectationTable) -> Vec<syn::Item> {
+    let mut worklist = vec![];
+    for rule in &self.grammar.rules {
+      if self.grammar.is_exposed(rule) {
+        worklist.push((rule.ident(), CompilerKind::Recognizer));
+        worklist.push((rule.ident(), CompilerKind::Parser));
+      }
+    }
+    for start in &self.grammar.attributes.starts {
+      worklist.push((start.clone(), CompilerKind::Parser));
+      // `reparse_cst` runs the recognizer of the start rule.
+      if self.grammar.attributes.cst {
+        worklist.push((start.clone(), CompilerKind::Recognizer));
+      }
+    }
+    let mut compiled = HashMap::new();
+    while let Some((id, kind)) = worklist.pop() {
+      if compiled.contains_key(&(id.clone(), kind)) {
+        continue;
+      }
+      let rule = self.grammar.find_rule_by_ident(&id);
+      let (item, used_rules) = RuleCompiler::compile(&self.grammar, expectations, rule, kind);
+      compiled.insert((id, kind), item);
+      worklist.extend(used_rules.into_iter());
+    }
+    let mut items = vec![];
+    for rule in &self.grammar.rules {
+      for kind in &[CompilerKind::Recognizer, CompilerKind::Parser] {
+        if let Some(item) = compiled.remove(&(rule.ident(), *kind)) {
+          items.push(item);
+        }
+      }
+    }
+    items
+  }
+}
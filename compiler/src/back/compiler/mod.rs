@@ -18,6 +18,10 @@ pub mod rtype;
 pub mod value;
 mod grammar;
 mod rule;
+mod entry_point;
+mod reference;
+mod visitor;
+mod printer;
 mod str_literal;
 mod sequence;
 mod choice;
@@ -27,11 +31,11 @@ mod optional;
 mod syntactic_predicate;
 mod character_class;
 mod non_terminal;
-mod semantic_action;
+pub mod semantic_action;
 mod spanned_expr;
 
 pub use quote::quote;
-pub use syn::parse_quote;
+pub use syn::{parse_quote, parse_quote_spanned};
 
 pub use back::compiler::grammar::*;
 pub use back::context::*;
@@ -47,6 +51,7 @@ use back::compiler::non_terminal::*;
 use back::compiler::semantic_action::*;
 use back::compiler::spanned_expr::*;
 
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CompilerKind
 {
   Recognizer,
@@ -99,8 +104,8 @@ pub fn recognizer_compiler(grammar: &TGrammar, idx: usize) -> Box<dyn CompileExp
     OneOrMore(expr_idx) => Box::new(RepeatCompiler::recognizer(expr_idx, 1)),
     NotPredicate(expr_idx) => Box::new(SyntacticPredicateCompiler::recognizer(expr_idx, Kind::Not)),
     AndPredicate(expr_idx) => Box::new(SyntacticPredicateCompiler::recognizer(expr_idx, Kind::And)),
-    NonTerminalSymbol(id) => Box::new(NonTerminalCompiler::recognizer(id)),
-    ExternalNonTerminalSymbol(path) => Box::new(NonTerminalCompiler::external_recognizer(path)),
+    NonTerminalSymbol(id) => Box::new(NonTerminalCompiler::recognizer(id, idx)),
+    ExternalNonTerminalSymbol(path) => Box::new(NonTerminalCompiler::external_recognizer(path, idx)),
       SemanticAction(expr_idx, _, _)
     | TypeAscription(expr_idx, _)
     | SpannedExpr(expr_idx)
@@ -20,43 +20,54 @@ pub struct NonTerminalCompiler;
 
 impl NonTerminalCompiler
 {
-  pub fn recognizer(id: Ident) -> NonTerminalRecognizerCompiler {
+  pub fn recognizer(id: Ident, this_idx: usize) -> NonTerminalRecognizerCompiler {
     NonTerminalRecognizerCompiler {
-      path: parse_quote!(#id)
+      path: parse_quote!(#id),
+      rule: Some(id),
+      this_idx
     }
   }
 
   pub fn parser(id: Ident, this_idx: usize) -> NonTerminalParserCompiler {
     NonTerminalParserCompiler {
       path: parse_quote!(#id),
+      rule: Some(id),
       this_idx
     }
   }
 
-  pub fn external_recognizer(path: syn::Path) -> NonTerminalRecognizerCompiler {
-    NonTerminalRecognizerCompiler { path }
+  pub fn external_recognizer(path: syn::Path, this_idx: usize) -> NonTerminalRecognizerCompiler {
+    NonTerminalRecognizerCompiler { path, rule: None, this_idx }
   }
 
   pub fn external_parser(path: syn::Path, this_idx: usize) -> NonTerminalParserCompiler {
-    NonTerminalParserCompiler { path, this_idx}
+    NonTerminalParserCompiler { path, rule: None, this_idx }
   }
 }
 
 pub struct NonTerminalRecognizerCompiler
 {
-  path: syn::Path
+  path: syn::Path,
+  /// `None` if the rule is external to the grammar.
+  rule: Option<Ident>,
+  this_idx: usize
 }
 
 impl CompileExpr for NonTerminalRecognizerCompiler
 {
-  fn compile_expr<'a>(&self, _context: &mut Context<'a>,
+  fn compile_expr<'a>(&self, context: &mut Context<'a>,
     continuation: Continuation) -> syn::Expr
   {
+    if let Some(ref rule) = self.rule {
+      context.use_rule(rule.clone(), CompilerKind::Recognizer);
+    }
     let recognizer_fn = recognizer_name(self.path.clone());
+    let span = context.span_of(self.this_idx);
+    let recognizer_call: syn::Expr = parse_quote_spanned!(span=> #recognizer_fn(state));
     continuation
       .map_success(|success, failure| parse_quote!(
         {
-          state = #recognizer_fn(state);
+          state = #recognizer_call;
           if state.is_successful() {
             state.discard_data();
             #success
@@ -73,6 +84,7 @@ impl CompileExpr for NonTerminalRecognizerCompiler
 pub struct NonTerminalParserCompiler
 {
   path: syn::Path,
+  rule: Option<Ident>,
   this_idx: usize
 }
 
@@ -81,7 +93,12 @@ impl CompileExpr for NonTerminalParserCompiler
   fn compile_expr<'a>(&self, context: &mut Context<'a>,
     continuation: Continuation) -> syn::Expr
   {
+    if let Some(ref rule) = self.rule {
+      context.use_rule(rule.clone(), CompilerKind::Parser);
+    }
     let parser_fn = parser_name(self.path.clone());
+    let span = context.span_of(self.this_idx);
+    let parser_call: syn::Expr = parse_quote_spanned!(span=> #parser_fn(state));
     let cardinality = context.expr_cardinality(self.this_idx);
     let mut vars_names: Vec<_> = (0..cardinality)
       .map(|_| context.next_free_var())
@@ -93,7 +110,7 @@ impl CompileExpr for NonTerminalParserCompiler
     continuation
       .map_success(|success, failure| parse_quote!(
         {
-          let stateful = #parser_fn(state);
+          let stateful = #parser_call;
           if stateful.is_successful() {
             let (stateless, #vars) = stateful.extract_data();
             state = stateless;
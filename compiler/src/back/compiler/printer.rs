@@ -0,0 +1,314 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generates the trait `Printer` printing the concrete syntax tree when the grammar is annotated with `#![cst]` (see `oak_runtime::printer`).
+//! The layout of each rule is computed from its expression:
+//!
+//!  * a rule annotated with `#[trivia]` is trivia, for instance `#[trivia] spacing = [" \n"]*:(^)`,
+//!  * a rule is a token if it only calls trivia rules, for instance `number = ["0-9"]+ spacing`, its text is formatted by the method `format_<rule>` of the printer,
+//!  * otherwise its expression is compiled to a static `oak_runtime::printer::Pattern`, matched against the children of its nodes to find its literals.
+//!
+//! A rule called in a syntactic predicate does not record its node, thus such a predicate cannot be checked by the printer and is compiled to a pattern always satisfied.
+
+use back::compiler::*;
+use back::bytecode::compile_character_class;
+use quote::format_ident;
+use proc_macro2::TokenStream;
+
+pub struct PrinterCompiler<'a>
+{
+  grammar: &'a TGrammar
+}
+
+impl<'a> PrinterCompiler<'a>
+{
+  pub fn compile(grammar: &'a TGrammar) -> Vec<syn::Item> {
+    if grammar.attributes.cst {
+      let compiler = PrinterCompiler { grammar };
+      vec![compiler.printer()]
+    }
+    else {
+      vec![]
+    }
+  }
+
+  fn printer(&self) -> syn::Item {
+    let mut layouts = vec![];
+    let mut patterns = vec![];
+    let mut token_kinds = vec![];
+    let mut methods = vec![];
+    let mut docs = vec![];
+    for (idx, rule) in self.grammar.rules.iter().enumerate() {
+      let kind = rule.ident().to_string();
+      let layout = match self.layout(rule) {
+        Layout::Trivia => quote!(RuleLayout::Trivia),
+        Layout::Token => {
+          token_kinds.push(kind.clone());
+          methods.push(format_ident!("format_{}", rule.ident()));
+          docs.push(format!(" Formats the text of the tokens of `{} = {}`.",
+            rule.ident(), self.grammar.display_expr(rule.expr_idx)));
+          quote!(RuleLayout::Token)
+        }
+        Layout::Structure => {
+          let name = format_ident!("OAK_PATTERN_{}", idx);
+          let pattern = PatternCompiler { grammar: self.grammar }.visit_expr(rule.expr_idx);
+          patterns.push(quote!(static #name: Pattern = #pattern;));
+          quote!(RuleLayout::Structure(&#name))
+        }
+      };
+      layouts.push(quote!(#kind => #layout));
+    }
+    let methods2 = methods.clone();
+    parse_quote!(
+      /// Printing of the concrete syntax trees built by `parse_cst`.
+      pub mod printer {
+        use oak_runtime::cst::SyntaxNode;
+        use oak_runtime::printer::*;
+
+        /// Prints a concrete syntax tree with a method per token rule and a method for the spacing.
+        /// By default, the tokens and the trivia are printed as they are in the tree.
+        pub trait Printer {
+          #(
+            #[doc = #docs]
+            fn #methods(&mut self, text: &str) -> String {
+              text.to_string()
+            }
+          )*
+
+          /// The text printed between the tokens `previous` and `next`, where `trivia` is the text of the trivia rules found between them.
+          /// `previous` is empty before the first token and `next` is empty after the last one.
+          fn spacing(&mut self, _previous: &str, trivia: &str, _next: &str) -> String {
+            trivia.to_string()
+          }
+
+          fn print(&mut self, tree: &SyntaxNode) -> String {
+            print(&mut Rules(self), tree)
+          }
+        }
+
+        #(#patterns)*
+
+        struct Rules<'a, P: Printer + ?Sized + 'a>(&'a mut P);
+
+        impl<'a, P: Printer + ?Sized> PrintRules for Rules<'a, P> {
+          fn layout(&self, kind: &str) -> RuleLayout {
+            match kind {
+              #(#layouts,)*
+              _ => RuleLayout::Structure(&Pattern::Sequence(&[]))
+            }
+          }
+
+          fn format_token(&mut self, kind: &str, text: &str) -> String {
+            match kind {
+              #(#token_kinds => self.0.#methods2(text),)*
+              _ => text.to_string()
+            }
+          }
+
+          fn spacing(&mut self, previous: &str, trivia: &str, next: &str) -> String {
+            self.0.spacing(previous, trivia, next)
+          }
+        }
+      }
+    )
+  }
+
+  fn layout(&self, rule: &Rule) -> Layout {
+    if rule.is_trivia() {
+      Layout::Trivia
+    }
+    else if RuleCalls::of(self.grammar, rule.expr_idx).iter()
+      .all(|call| self.grammar.find_rule_by_ident(call).is_trivia())
+    {
+      Layout::Token
+    }
+    else {
+      Layout::Structure
+    }
+  }
+}
+
+enum Layout
+{
+  Trivia,
+  Token,
+  Structure
+}
+
+/// Compiles an expression to a constant `oak_runtime::printer::Pattern`.
+struct PatternCompiler<'a>
+{
+  grammar: &'a TGrammar
+}
+
+impl<'a> PatternCompiler<'a>
+{
+  fn compile_children(&mut self, children: Vec<usize>) -> Vec<TokenStream> {
+    children.into_iter().map(|child| self.visit_expr(child)).collect()
+  }
+
+  /// The predicates calling a rule are always satisfied, see the module documentation.
+  fn compile_predicate(&mut self, child: usize, predicate: TokenStream) -> TokenStream {
+    if RuleCalls::of(self.grammar, child).is_empty() {
+      let pattern = self.visit_expr(child);
+      quote!(Pattern::#predicate(&#pattern))
+    }
+    else {
+      quote!(Pattern::Sequence(&[]))
+    }
+  }
+}
+
+impl<'a> ExprByIndex for PatternCompiler<'a>
+{
+  fn expr_by_index(&self, index: usize) -> Expression {
+    self.grammar.expr_by_index(index).clone()
+  }
+}
+
+impl<'a> Visitor<TokenStream> for PatternCompiler<'a>
+{
+  fn visit_str_literal(&mut self, _this: usize, lit: StrLiteralExpr) -> TokenStream {
+    let (text, ignore_case) = (lit.value, lit.ignore_case);
+    quote!(Pattern::Literal(Literal { text: #text, ignore_case: #ignore_case }))
+  }
+
+  fn visit_any_single_char(&mut self, _this: usize) -> TokenStream {
+    quote!(Pattern::AnySingleChar)
+  }
+
+  fn visit_character_class(&mut self, _this: usize, char_class: CharacterClassExpr) -> TokenStream {
+    let class = compile_character_class(&char_class);
+    quote!(Pattern::CharacterClass(&#class))
+  }
+
+  fn visit_non_terminal_symbol(&mut self, _this: usize, rule: &Ident) -> TokenStream {
+    let kind = rule.to_string();
+    let nullable = MatchEmpty { grammar: self.grammar }.visit_expr(self.grammar.expr_index_of_rule(rule));
+    quote!(Pattern::Rule(#kind, #nullable))
+  }
+
+  fn visit_external_non_terminal_symbol(&mut self, _this: usize, _rule: &syn::Path) -> TokenStream {
+    quote!(Pattern::External)
+  }
+
+  fn visit_sequence(&mut self, _this: usize, children: Vec<usize>) -> TokenStream {
+    let children = self.compile_children(children);
+    quote!(Pattern::Sequence(&[#(#children),*]))
+  }
+
+  fn visit_choice(&mut self, _this: usize, children: Vec<usize>) -> TokenStream {
+    let children = self.compile_children(children);
+    quote!(Pattern::Choice(&[#(#children),*]))
+  }
+
+  fn visit_zero_or_more(&mut self, _this: usize, child: usize) -> TokenStream {
+    let child = self.visit_expr(child);
+    quote!(Pattern::ZeroOrMore(&#child))
+  }
+
+  fn visit_one_or_more(&mut self, _this: usize, child: usize) -> TokenStream {
+    let child = self.visit_expr(child);
+    quote!(Pattern::OneOrMore(&#child))
+  }
+
+  fn visit_optional(&mut self, _this: usize, child: usize) -> TokenStream {
+    let child = self.visit_expr(child);
+    quote!(Pattern::ZeroOrOne(&#child))
+  }
+
+  fn visit_not_predicate(&mut self, _this: usize, child: usize) -> TokenStream {
+    self.compile_predicate(child, quote!(NotPredicate))
+  }
+
+  fn visit_and_predicate(&mut self, _this: usize, child: usize) -> TokenStream {
+    self.compile_predicate(child, quote!(AndPredicate))
+  }
+}
+
+/// The rules called by an expression, without looking into the called rules.
+struct RuleCalls<'a>
+{
+  grammar: &'a TGrammar,
+  calls: Vec<Ident>
+}
+
+impl<'a> RuleCalls<'a>
+{
+  fn of(grammar: &'a TGrammar, expr_idx: usize) -> Vec<Ident> {
+    let mut rule_calls = RuleCalls { grammar, calls: vec![] };
+    rule_calls.visit_expr(expr_idx);
+    rule_calls.calls
+  }
+}
+
+impl<'a> ExprByIndex for RuleCalls<'a>
+{
+  fn expr_by_index(&self, index: usize) -> Expression {
+    self.grammar.expr_by_index(index).clone()
+  }
+}
+
+impl<'a> Visitor<()> for RuleCalls<'a>
+{
+  unit_visitor_impl!(sequence);
+  unit_visitor_impl!(choice);
+
+  fn visit_non_terminal_symbol(&mut self, _this: usize, rule: &Ident) {
+    self.calls.push(rule.clone());
+  }
+}
+/// Whether an expression can succeed without consuming input, the left recursions are rejected by the well-formedness analysis.
+struct MatchEmpty<'a>
+{
+  grammar: &'a TGrammar
+}
+
+impl<'a> ExprByIndex for MatchEmpty<'a>
+{
+  fn expr_by_index(&self, index: usize) -> Expression {
+    self.grammar.expr_by_index(index).clone()
+  }
+}
+
+impl<'a> Visitor<bool> for MatchEmpty<'a>
+{
+  fn visit_str_literal(&mut self, _this: usize, lit: StrLiteralExpr) -> bool {
+    lit.value.is_empty()
+  }
+
+  fn visit_non_terminal_symbol(&mut self, _this: usize, rule: &Ident) -> bool {
+    self.visit_expr(self.grammar.expr_index_of_rule(rule))
+  }
+
+  fn visit_sequence(&mut self, _this: usize, children: Vec<usize>) -> bool {
+    children.into_iter().all(|child| self.visit_expr(child))
+  }
+
+  fn visit_choice(&mut self, _this: usize, children: Vec<usize>) -> bool {
+    children.into_iter().any(|child| self.visit_expr(child))
+  }
+
+  fn visit_zero_or_more(&mut self, _this: usize, _child: usize) -> bool {
+    true
+  }
+
+  fn visit_optional(&mut self, _this: usize, _child: usize) -> bool {
+    true
+  }
+
+  fn visit_syntactic_predicate(&mut self, _this: usize, _child: usize) -> bool {
+    true
+  }
+}
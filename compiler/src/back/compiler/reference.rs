@@ -0,0 +1,63 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Generates the constant `GRAMMAR_REFERENCE` when the grammar is annotated with `#![grammar_reference]`.
+//! It is a Markdown document listing, for each rule, its doc comments, its PEG definition and the Rust type of its parser.
+
+use back::compiler::*;
+use back::compiler::rtype::*;
+
+pub struct GrammarReferenceCompiler<'a>
+{
+  grammar: &'a TGrammar
+}
+
+impl<'a> GrammarReferenceCompiler<'a>
+{
+  pub fn compile(grammar: &'a TGrammar) -> Option<syn::Item> {
+    if grammar.attributes.grammar_reference {
+      let compiler = GrammarReferenceCompiler { grammar };
+      let reference = compiler.reference();
+      Some(parse_quote!(
+        /// Markdown reference of the rules of the grammar.
+        pub const GRAMMAR_REFERENCE: &str = #reference;
+      ))
+    }
+    else {
+      None
+    }
+  }
+
+  fn reference(&self) -> String {
+    let mut reference = format!("# Grammar reference\n");
+    for rule in &self.grammar.rules {
+      reference.push_str(&self.rule_reference(rule));
+    }
+    reference
+  }
+
+  fn rule_reference(&self, rule: &Rule) -> String {
+    let mut reference = format!("\n## `{}`\n\n", rule.ident());
+    let doc = rule.doc();
+    if !doc.is_empty() {
+      reference.push_str(&doc);
+      reference.push_str("\n\n");
+    }
+    let ty = TypeCompiler::compile(self.grammar, rule.expr_idx);
+    reference.push_str(&format!("```text\n{} = {}\n```\n\nType: `{}`\n",
+      rule.ident(), self.grammar.display_expr(rule.expr_idx), quote_string(&ty)));
+    reference
+  }
+}
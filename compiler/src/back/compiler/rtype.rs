@@ -16,7 +16,8 @@
 
 use middle::typing::ast::*;
 use middle::typing::ast::Type::*;
-use syn::parse_quote;
+use syn::parse_quote_spanned;
+use proc_macro2::Span;
 
 pub struct TypeCompiler<'a>
 {
@@ -34,39 +35,41 @@ impl<'a> TypeCompiler<'a>
     TypeCompiler { grammar }
   }
 
+  /// The types are spanned with the expression they come from, so a mismatch with the type expected by a semantic action is reported on the grammar.
   fn compile_type(&self, expr_idx: usize) -> syn::Type {
+    let span = Span::call_site().located_at(self.grammar[expr_idx].span());
     match self.grammar[expr_idx].ty.clone() {
-      Unit => Self::unit_type(),
-      Atom => self.atom_type(),
-      List(expr_idx) => self.list_type(expr_idx),
-      Optional(expr_idx) => self.optional_type(expr_idx),
+      Unit => Self::unit_type(span),
+      Atom => self.atom_type(span),
+      List(expr_idx) => self.list_type(span, expr_idx),
+      Optional(expr_idx) => self.optional_type(span, expr_idx),
       Rust(rust_ty) => rust_ty,
-      Tuple(indexes) => self.tuple_type(indexes),
+      Tuple(indexes) => self.tuple_type(span, indexes),
     }
   }
 
-  pub fn unit_type() -> syn::Type {
-    parse_quote!(())
+  pub fn unit_type(span: Span) -> syn::Type {
+    parse_quote_spanned!(span=> ())
   }
 
-  fn tuple_type(&self, indexes: Vec<usize>) -> syn::Type {
+  fn tuple_type(&self, span: Span, indexes: Vec<usize>) -> syn::Type {
     let tys: Vec<_> = indexes.into_iter()
       .map(|idx| self.compile_type(idx))
       .collect();
-    parse_quote!((#(#tys),*))
+    parse_quote_spanned!(span=> (#(#tys),*))
   }
 
-  fn atom_type(&self) -> syn::Type {
-    parse_quote!(char)
+  fn atom_type(&self, span: Span) -> syn::Type {
+    parse_quote_spanned!(span=> char)
   }
 
-  fn list_type(&self, expr_idx: usize) -> syn::Type {
+  fn list_type(&self, span: Span, expr_idx: usize) -> syn::Type {
     let ty = self.compile_type(expr_idx);
-    parse_quote!(Vec<#ty>)
+    parse_quote_spanned!(span=> Vec<#ty>)
   }
 
-  fn optional_type(&self, expr_idx: usize) -> syn::Type {
+  fn optional_type(&self, span: Span, expr_idx: usize) -> syn::Type {
     let ty = self.compile_type(expr_idx);
-    parse_quote!(Option<#ty>)
+    parse_quote_spanned!(span=> Option<#ty>)
   }
 }
@@ -14,45 +14,53 @@
 
 use back::compiler::*;
 use back::compiler::value::*;
+use back::expectation::ExpectationTable;
 
 pub struct RuleCompiler<'a>
 {
   grammar: &'a TGrammar,
+  expectations: &'a ExpectationTable,
   rule: Rule
 }
 
 impl<'a> RuleCompiler<'a>
 {
-  pub fn compile(grammar: &'a TGrammar, rule: Rule) -> Vec<syn::Item> {
-    let compiler = RuleCompiler::new(grammar, rule);
-    vec![
-      compiler.compile_recognizer(),
-      compiler.compile_parser()
-    ]
+  /// Compiles the recognizer or the parser function of `rule`, and returns the rule functions it calls.
+  pub fn compile(grammar: &'a TGrammar, expectations: &'a ExpectationTable,
+    rule: Rule, kind: CompilerKind) -> (syn::Item, Vec<(Ident, CompilerKind)>)
+  {
+    let compiler = RuleCompiler::new(grammar, expectations, rule);
+    match kind {
+      CompilerKind::Recognizer => compiler.compile_recognizer(),
+      CompilerKind::Parser => compiler.compile_parser()
+    }
   }
 
-  fn new(grammar: &'a TGrammar, rule: Rule) -> Self {
+  fn new(grammar: &'a TGrammar, expectations: &'a ExpectationTable, rule: Rule) -> Self {
     RuleCompiler {
       grammar: grammar,
+      expectations: expectations,
       rule: rule
     }
   }
 
-  fn compile_recognizer(&self) -> syn::Item {
-    let mut context = Context::new(self.grammar);
+  fn compile_recognizer(&self) -> (syn::Item, Vec<(Ident, CompilerKind)>) {
+    let mut context = Context::new(self.grammar, self.expectations);
     let success = parse_quote!(state.success(()));
     let failure = parse_quote!(state.failure());
 
     let body = context.compile(recognizer_compiler,
       self.expr(), success, failure);
 
-    context.into_recognizer_function(body, self.rule.clone())
+    let used_rules = context.used_rules();
+    (context.into_recognizer_function(body, self.rule.clone()), used_rules)
   }
 
-  fn compile_parser(&self) -> syn::Item {
-    let mut context = Context::new(self.grammar);
+  fn compile_parser(&self) -> (syn::Item, Vec<(Ident, CompilerKind)>) {
+    let mut context = Context::new(self.grammar, self.expectations);
     if self.parser_equals_recognizer() {
-      context.into_parser_alias(self.rule.clone())
+      let id = self.rule.ident();
+      (context.into_parser_alias(self.rule.clone()), vec![(id, CompilerKind::Recognizer)])
     }
     else {
       let scope = context.open_scope(self.expr());
@@ -64,7 +72,8 @@ impl<'a> RuleCompiler<'a>
         self.expr(), success, failure);
 
       context.close_scope(scope);
-      context.into_parser_function(body, self.rule.clone())
+      let used_rules = context.used_rules();
+      (context.into_parser_function(body, self.rule.clone()), used_rules)
     }
   }
 
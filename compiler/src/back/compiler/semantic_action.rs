@@ -18,14 +18,15 @@ pub struct SemanticActionCompiler
 {
   expr_idx: usize,
   boxed: bool,
-  action: syn::Expr
+  action: syn::Expr,
+  this_idx: usize
 }
 
 impl SemanticActionCompiler
 {
-  pub fn parser(expr_idx: usize, boxed: bool, action: syn::Expr, _this_idx: usize) -> SemanticActionCompiler {
+  pub fn parser(expr_idx: usize, boxed: bool, action: syn::Expr, this_idx: usize) -> SemanticActionCompiler {
     SemanticActionCompiler {
-      expr_idx, boxed, action
+      expr_idx, boxed, action, this_idx
     }
   }
 }
@@ -37,31 +38,23 @@ impl CompileExpr for SemanticActionCompiler
   {
     let result = context.next_free_var();
     let scope = context.open_scope(self.expr_idx);
-    let args: Vec<syn::Expr> = context.free_variables().into_iter()
+    let args: Vec<syn::Expr> = context.located_free_variables(self.expr_idx).into_iter()
       .map(|var| parse_quote!(#var))
       .collect();
     let action = self.action.clone();
-    let is_unit_variant =
-      match self.action {
-        syn::Expr::Path(ref expr_path) =>
-          if let Some(x) = expr_path.path.segments.last() {
-            x.ident.to_string().chars().next().expect("non empty identifier").is_uppercase() &&
-            context.has_unit_type(self.expr_idx)
-          }
-          else { false }
-        _ => false
-      };
+    let span = context.span_of(self.this_idx);
+    let is_unit_variant = is_unit_variant(&self.action, context.has_unit_type(self.expr_idx));
     let expr = continuation
       .map_success(|success, _| {
         let action_call: syn::Expr =
           if is_unit_variant {
-            parse_quote!(#action)
+            parse_quote_spanned!(span=> #action)
           }
           else {
-            parse_quote!(#action(#(#args),*))
+            parse_quote_spanned!(span=> #action(#(#args),*))
           };
         let boxed_action_call: syn::Expr =
-          if self.boxed { parse_quote!(Box::new(#action_call)) }
+          if self.boxed { parse_quote_spanned!(span=> Box::new(#action_call)) }
           else { action_call };
         parse_quote!({
           let #result = #boxed_action_call;
@@ -73,3 +66,16 @@ impl CompileExpr for SemanticActionCompiler
     expr
   }
 }
+
+/// `true` if the action is a variant of an enumeration (such as `Value::Null`) applied to an expression of type unit, it is not called.
+pub fn is_unit_variant(action: &syn::Expr, unit_expr: bool) -> bool {
+  match action {
+    &syn::Expr::Path(ref expr_path) =>
+      if let Some(x) = expr_path.path.segments.last() {
+        x.ident.to_string().chars().next().expect("non empty identifier").is_uppercase() &&
+        unit_expr
+      }
+      else { false }
+    _ => false
+  }
+}
@@ -13,38 +13,47 @@
 // limitations under the License.
 
 use back::compiler::*;
+use back::expectation::str_literal_item;
 
 pub struct StrLiteralCompiler
 {
-  literal: String
+  literal: StrLiteralExpr
 }
 
 impl StrLiteralCompiler
 {
-  pub fn recognizer(literal: String) -> StrLiteralCompiler {
+  pub fn recognizer(literal: StrLiteralExpr) -> StrLiteralCompiler {
     StrLiteralCompiler {
       literal: literal
     }
   }
 
-  pub fn parser(literal: String) -> StrLiteralCompiler {
+  pub fn parser(literal: StrLiteralExpr) -> StrLiteralCompiler {
     StrLiteralCompiler::recognizer(literal)
   }
 }
 
 impl CompileExpr for StrLiteralCompiler
 {
-  fn compile_expr<'a>(&self, _context: &mut Context<'a>,
+  fn compile_expr<'a>(&self, context: &mut Context<'a>,
     continuation: Continuation) -> syn::Expr
   {
-    let lit = self.literal.as_str();
+    let lit = self.literal.value.as_str();
+    let error = context.compile_error(str_literal_item(&self.literal).as_str());
+    let consume_prefix: syn::Expr =
+      if self.literal.ignore_case {
+        parse_quote!(state.consume_prefix_ignore_case(#lit))
+      }
+      else {
+        parse_quote!(state.consume_prefix(#lit))
+      };
     continuation
       .map_success(|success, failure| parse_quote!(
-        if state.consume_prefix(#lit) {
+        if #consume_prefix {
           #success
         }
         else {
-          state.error(#lit);
+          #error;
           #failure
         }
       ))
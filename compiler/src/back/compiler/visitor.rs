@@ -0,0 +1,198 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generates the traits `Visitor`, `VisitorMut` and `Fold` over the concrete syntax tree when the grammar is annotated with `#![cst]`.
+//! As in `oak_compiler::visitor`, each trait has one method per rule, called on the nodes of this rule, and a method per tree element (`visit_node` and `visit_token`) dispatching to them.
+//! By default, the methods of the rules walk the children of the node, thus an implementation only overrides the methods of the rules it is interested in.
+//!
+//!  * `Visitor` traverses a `SyntaxNode`.
+//!  * `VisitorMut` modifies a `GreenNode` in place, the nodes shared with another tree are copied on write.
+//!  * `Fold` rebuilds a `GreenNode` bottom-up, for instance to replace a node by a node of another kind.
+
+use back::compiler::*;
+use quote::format_ident;
+use proc_macro2::TokenStream;
+
+pub struct VisitorCompiler<'a>
+{
+  grammar: &'a TGrammar
+}
+
+impl<'a> VisitorCompiler<'a>
+{
+  pub fn compile(grammar: &'a TGrammar) -> Vec<syn::Item> {
+    if grammar.attributes.cst {
+      let compiler = VisitorCompiler { grammar };
+      let items = vec![compiler.visitor(), compiler.visitor_mut(), compiler.fold()];
+      vec![parse_quote!(
+        /// Traversals of the concrete syntax trees built by `parse_cst`.
+        pub mod visitor {
+          use oak_runtime::cst::*;
+          use std::rc::Rc;
+          #(#items)*
+        }
+      )]
+    }
+    else {
+      vec![]
+    }
+  }
+
+  /// The kinds of the nodes, in the order of the rules' declarations.
+  fn kinds(&self) -> Vec<String> {
+    self.grammar.rules.iter()
+      .map(|rule| rule.ident().to_string())
+      .collect()
+  }
+
+  fn methods(&self, prefix: &str, suffix: &str) -> Vec<Ident> {
+    self.grammar.rules.iter()
+      .map(|rule| format_ident!("{}{}{}", prefix, rule.ident(), suffix))
+      .collect()
+  }
+
+  fn docs(&self) -> Vec<String> {
+    self.grammar.rules.iter()
+      .map(|rule| format!(" Called on the nodes of the rule `{}`.", rule.ident()))
+      .collect()
+  }
+
+  fn visitor(&self) -> TokenStream {
+    let kinds = self.kinds();
+    let methods = self.methods("visit_", "");
+    let methods2 = methods.clone();
+    let docs = self.docs();
+    quote!(
+      /// Traverses a concrete syntax tree with one method per rule.
+      pub trait Visitor {
+        fn visit_node(&mut self, node: &SyntaxNode) {
+          walk_node(self, node)
+        }
+
+        fn visit_token(&mut self, _token: &SyntaxToken) {}
+
+        #(
+          #[doc = #docs]
+          fn #methods(&mut self, node: &SyntaxNode) {
+            walk_children(self, node)
+          }
+        )*
+      }
+
+      /// Calls the method of `visitor` for the rule of `node`.
+      pub fn walk_node<V: Visitor + ?Sized>(visitor: &mut V, node: &SyntaxNode) {
+        match node.kind() {
+          #(#kinds => visitor.#methods2(node),)*
+          _ => walk_children(visitor, node)
+        }
+      }
+
+      pub fn walk_children<V: Visitor + ?Sized>(visitor: &mut V, node: &SyntaxNode) {
+        for child in node.children_with_tokens() {
+          match child {
+            SyntaxElement::Node(child) => visitor.visit_node(&child),
+            SyntaxElement::Token(token) => visitor.visit_token(&token)
+          }
+        }
+      }
+    )
+  }
+
+  fn visitor_mut(&self) -> TokenStream {
+    let kinds = self.kinds();
+    let methods = self.methods("visit_", "_mut");
+    let methods2 = methods.clone();
+    let docs = self.docs();
+    quote!(
+      /// Modifies a concrete syntax tree in place with one method per rule.
+      pub trait VisitorMut {
+        fn visit_node_mut(&mut self, node: &mut GreenNode) {
+          walk_node_mut(self, node)
+        }
+
+        fn visit_token_mut(&mut self, _token: &mut Rc<str>) {}
+
+        #(
+          #[doc = #docs]
+          fn #methods(&mut self, node: &mut GreenNode) {
+            walk_children_mut(self, node)
+          }
+        )*
+      }
+
+      /// Calls the method of `visitor` for the rule of `node`.
+      pub fn walk_node_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut GreenNode) {
+        match node.kind() {
+          #(#kinds => visitor.#methods2(node),)*
+          _ => walk_children_mut(visitor, node)
+        }
+      }
+
+      pub fn walk_children_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut GreenNode) {
+        node.update_children(|children| {
+          for child in children.iter_mut() {
+            match *child {
+              GreenElement::Node(ref mut child) => visitor.visit_node_mut(Rc::make_mut(child)),
+              GreenElement::Token(ref mut token) => visitor.visit_token_mut(token)
+            }
+          }
+        })
+      }
+    )
+  }
+
+  fn fold(&self) -> TokenStream {
+    let kinds = self.kinds();
+    let methods = self.methods("fold_", "");
+    let methods2 = methods.clone();
+    let docs = self.docs();
+    quote!(
+      /// Rebuilds a concrete syntax tree bottom-up with one method per rule.
+      pub trait Fold {
+        fn fold_node(&mut self, node: Rc<GreenNode>) -> Rc<GreenNode> {
+          walk_fold(self, node)
+        }
+
+        fn fold_token(&mut self, token: Rc<str>) -> Rc<str> {
+          token
+        }
+
+        #(
+          #[doc = #docs]
+          fn #methods(&mut self, node: Rc<GreenNode>) -> Rc<GreenNode> {
+            fold_children(self, node)
+          }
+        )*
+      }
+
+      /// Calls the method of `folder` for the rule of `node`.
+      pub fn walk_fold<F: Fold + ?Sized>(folder: &mut F, node: Rc<GreenNode>) -> Rc<GreenNode> {
+        match node.kind() {
+          #(#kinds => folder.#methods2(node),)*
+          _ => fold_children(folder, node)
+        }
+      }
+
+      pub fn fold_children<F: Fold + ?Sized>(folder: &mut F, node: Rc<GreenNode>) -> Rc<GreenNode> {
+        let children = node.children().iter().cloned()
+          .map(|child| match child {
+            GreenElement::Node(child) => GreenElement::Node(folder.fold_node(child)),
+            GreenElement::Token(token) => GreenElement::Token(folder.fold_token(token))
+          })
+          .collect();
+        Rc::new(GreenNode::new(node.kind(), children))
+      }
+    )
+  }
+}
@@ -15,7 +15,8 @@
 
 pub use back::continuation::*;
 use back::name_factory::*;
-use back::compiler::ExprCompilerFn;
+use back::expectation::*;
+use back::compiler::{ExprCompilerFn, CompilerKind};
 use back::compiler::rtype::*;
 use back::compiler::{recognizer_compiler, parser_compiler};
 use back::compiler::value::*;
@@ -26,57 +27,117 @@ use syn::parse_quote;
 pub struct Context<'a>
 {
   grammar: &'a TGrammar,
+  expectations: &'a ExpectationTable,
   closures: Vec<syn::Stmt>,
   name_factory: NameFactory,
   free_variables: Vec<Ident>,
   mark_variables: Vec<Ident>,
   mut_ref_free_variables: Vec<(Ident, syn::Type)>,
-  num_combinators_compiled: usize
+  ref_free_variables: Vec<(Ident, syn::Type)>,
+  num_combinators_compiled: usize,
+  used_rules: Vec<(Ident, CompilerKind)>
 }
 
 impl<'a> Context<'a>
 {
-  pub fn new(grammar: &'a TGrammar) -> Self
+  pub fn new(grammar: &'a TGrammar, expectations: &'a ExpectationTable) -> Self
   {
     Context {
       grammar: grammar,
+      expectations: expectations,
       closures: vec![],
       name_factory: NameFactory::new(),
       free_variables: vec![],
       mark_variables: vec![],
       mut_ref_free_variables: vec![],
-      num_combinators_compiled: 0
+      ref_free_variables: vec![],
+      num_combinators_compiled: 0,
+      used_rules: vec![]
     }
   }
 
   pub fn into_recognizer_function(self, body: syn::Expr, rule: Rule) -> syn::Item {
-    let recognizer_fn = recognizer_id(rule.ident());
-    self.function(recognizer_fn, true, body, parse_quote!(()))
+    self.into_rule_function(CompilerKind::Recognizer, true, body, rule)
   }
 
-  pub fn into_parser_alias(self, rule: Rule) -> syn::Item {
+  pub fn into_parser_alias(mut self, rule: Rule) -> syn::Item {
     let id = rule.ident();
     let recognizer_fn = recognizer_name(parse_quote!(#id));
-    let parser_fn = parser_id(id);
-    self.function(parser_fn, false,
+    let parser_fn = parser_id(id.clone());
+    self.use_rule(id, CompilerKind::Recognizer);
+    let doc = format!("Parses the rule `{}`, which does not produce a value.", rule.ident());
+    self.function(parser_fn, &rule, doc, false,
       parse_quote!(#recognizer_fn(state)),
       parse_quote!(()))
   }
 
   pub fn into_parser_function(self, body: syn::Expr, rule: Rule) -> syn::Item {
-    let parser_fn = parser_id(rule.ident());
-    let ty = TypeCompiler::compile(self.grammar, rule.expr_idx);
-    self.function(parser_fn, true, body, ty)
+    self.into_rule_function(CompilerKind::Parser, true, body, rule)
   }
 
-  fn function(self, name: Ident, state_mut: bool, body: syn::Expr, ty: syn::Type) -> syn::Item {
+  /// The recognizer or parser function of `rule`, `state_mut` is `false` if `body` does not modify the state in place.
+  pub fn into_rule_function(self, kind: CompilerKind, state_mut: bool, body: syn::Expr, rule: Rule) -> syn::Item {
+    let body = self.record_node(body, &rule, kind);
+    match kind {
+      CompilerKind::Recognizer => {
+        let recognizer_fn = recognizer_id(rule.ident());
+        let doc = format!("Recognizes the rule `{}` without building its value.", rule.ident());
+        self.function(recognizer_fn, &rule, doc, state_mut, body, parse_quote!(()))
+      }
+      CompilerKind::Parser => {
+        let parser_fn = parser_id(rule.ident());
+        let ty = TypeCompiler::compile(self.grammar, rule.expr_idx);
+        let doc = format!("Parses the rule `{}` and builds its value.", rule.ident());
+        self.function(parser_fn, &rule, doc, state_mut, body, ty)
+      }
+    }
+  }
+
+  /// With `#![cst]`, the rule records its node in the state once it is parsed, see `oak_runtime::cst`.
+  /// The recognizer reuses the node of a previous tree if possible, see `oak_runtime::incremental`.
+  fn record_node(&self, body: syn::Expr, rule: &Rule, kind: CompilerKind) -> syn::Expr {
+    if self.grammar.attributes.cst {
+      let node_kind = rule.ident().to_string();
+      let reuse: Option<syn::Stmt> =
+        if kind == CompilerKind::Recognizer {
+          Some(parse_quote!(
+            if let Some(index) = state.reusable_node(#node_kind) {
+              return state.reuse_node(index);
+            }
+          ))
+        }
+        else { None };
+      parse_quote!({
+        #reuse
+        let oak_cst_start = state.current.clone();
+        let state = #body;
+        state.record_node(#node_kind, oak_cst_start)
+      })
+    }
+    else {
+      body
+    }
+  }
+
+  fn function(self, name: Ident, rule: &Rule, doc: String, state_mut: bool,
+    body: syn::Expr, ty: syn::Type) -> syn::Item
+  {
     let state_param = self.state_param(state_mut);
     let stream_ty = self.grammar.stream_type();
     let generics = self.grammar.stream_generics();
+    let vis = self.grammar.visibility_of(rule);
+    let mut doc_attrs = rule.doc_attributes();
+    if vis != syn::Visibility::Inherited {
+      if !doc_attrs.is_empty() {
+        doc_attrs.push(parse_quote!(#[doc = ""]));
+      }
+      doc_attrs.push(parse_quote!(#[doc = #doc]));
+    }
     let closures = self.closures;
     parse_quote!(
+      #(#doc_attrs)*
       #[inline]
-      pub fn #name #generics (#state_param) -> oak_runtime::ParseState<#stream_ty, #ty>
+      #vis fn #name #generics (#state_param) -> oak_runtime::ParseState<#stream_ty, #ty>
       {
         #(#closures)*
         #body
@@ -84,6 +145,15 @@ impl<'a> Context<'a>
     )
   }
 
+  /// Registers that the recognizer or parser function of `rule` is called by the function being compiled.
+  pub fn use_rule(&mut self, rule: Ident, kind: CompilerKind) {
+    self.used_rules.push((rule, kind));
+  }
+
+  pub fn used_rules(&self) -> Vec<(Ident, CompilerKind)> {
+    self.used_rules.clone()
+  }
+
   fn state_param(&self, state_mut: bool) -> syn::FnArg {
     let mut_kw = if state_mut {
       Some(quote!(mut))
@@ -171,6 +241,9 @@ impl<'a> Context<'a>
       .chain(self.mut_ref_free_variables
         .iter().cloned()
         .map(|(var, ty)| parse_quote!(#var: &mut #ty)))
+      .chain(self.ref_free_variables
+        .iter().cloned()
+        .map(|(var, ty)| parse_quote!(#var: &#ty)))
       .chain(self.free_variables
         .iter()
         .map(|var| parse_quote!(#var:_)))
@@ -186,6 +259,9 @@ impl<'a> Context<'a>
       .chain(self.mut_ref_free_variables
         .iter().cloned()
         .map(|(var, _)| parse_quote!(&mut #var)))
+      .chain(self.ref_free_variables
+        .iter().cloned()
+        .map(|(var, _)| parse_quote!(&#var)))
       .chain(self.free_variables
         .iter()
         .map(|var| parse_quote!(#var)))
@@ -195,6 +271,13 @@ impl<'a> Context<'a>
       .collect()
   }
 
+  /// Reports that `item` was expected at the current position of the state.
+  pub fn compile_error(&self, item: &str) -> syn::Expr {
+    let table = table_name();
+    let id = self.expectations.id_of(item);
+    parse_quote!(state.error(&#table, #id))
+  }
+
   pub fn next_mark_name(&mut self) -> Ident {
     self.name_factory.next_mark_name()
   }
@@ -235,15 +318,42 @@ impl<'a> Context<'a>
     self.mut_ref_free_variables.push((mut_ref_var,mut_ref_ty));
   }
 
-  pub fn pop_mut_ref_fv(&mut self) {
-    self.mut_ref_free_variables.pop()
-      .expect("There is no mut ref free variables.");
+  /// Same as `push_mut_ref_fv` but the variable is passed by shared reference, e.g. a `Cell`.
+  pub fn push_ref_fv(&mut self, ref_var: Ident, ref_ty: syn::Type) {
+    self.ref_free_variables.push((ref_var, ref_ty));
+  }
+
+  pub fn pop_ref_fv(&mut self) {
+    self.ref_free_variables.pop()
+      .expect("There is no ref free variables.");
   }
 
   pub fn expr_cardinality(&self, expr_idx: usize) -> usize {
     self.grammar[expr_idx].type_cardinality()
   }
 
+  /// The location of the expression in the grammar, used with `quote_spanned!` so rustc reports errors of the generated code on the grammar.
+  /// Names are still resolved at the call site, as with `quote!`.
+  pub fn span_of(&self, expr_idx: usize) -> Span {
+    Span::call_site().located_at(self.grammar[expr_idx].span())
+  }
+
+  /// The free variables of the current scope, located at the expression they are bound to.
+  /// It is used to report a type mismatch on the argument of a semantic action on its expression in the grammar.
+  pub fn located_free_variables(&self, expr_idx: usize) -> Vec<Ident> {
+    let spans: Vec<Span> = match self.grammar[expr_idx].ty {
+      Type::Tuple(ref indexes) => indexes.iter().map(|idx| self.span_of(*idx)).collect(),
+      _ => vec![self.span_of(expr_idx)]
+    };
+    self.free_variables.iter().zip(spans.into_iter())
+      .map(|(var, span)| {
+        let mut var = var.clone();
+        var.set_span(var.span().located_at(span));
+        var
+      })
+      .collect()
+  }
+
   pub fn has_unit_type(&self, expr_idx: usize) -> bool {
     self.grammar[expr_idx].ty == crate::middle::typing::ast::Type::Unit
   }
@@ -252,6 +362,7 @@ impl<'a> Context<'a>
     let scope = self.save_scope();
     self.num_combinators_compiled = 0;
     self.mut_ref_free_variables = vec![];
+    self.ref_free_variables = vec![];
     let cardinality = self.expr_cardinality(expr_idx);
     let free_vars = self.name_factory.fresh_vars(cardinality);
     self.free_variables = free_vars;
@@ -268,13 +379,15 @@ impl<'a> Context<'a>
     Scope::new(
       self.num_combinators_compiled,
       self.free_variables.clone(),
-      self.mut_ref_free_variables.clone()
+      self.mut_ref_free_variables.clone(),
+      self.ref_free_variables.clone()
     )
   }
 
   pub fn restore_scope(&mut self, scope: Scope) {
     self.num_combinators_compiled = scope.num_combinators_compiled;
     self.mut_ref_free_variables = scope.mut_ref_free_variables;
+    self.ref_free_variables = scope.ref_free_variables;
     self.free_variables = scope.free_variables;
   }
 }
@@ -283,15 +396,17 @@ impl<'a> Context<'a>
 pub struct Scope {
   num_combinators_compiled: usize,
   free_variables: Vec<Ident>,
-  mut_ref_free_variables: Vec<(Ident, syn::Type)>
+  mut_ref_free_variables: Vec<(Ident, syn::Type)>,
+  ref_free_variables: Vec<(Ident, syn::Type)>
 }
 
 impl Scope {
-  fn new(n: usize, fv: Vec<Ident>, mfv: Vec<(Ident, syn::Type)>) -> Self {
+  fn new(n: usize, fv: Vec<Ident>, mfv: Vec<(Ident, syn::Type)>, rfv: Vec<(Ident, syn::Type)>) -> Self {
     Scope {
       num_combinators_compiled: n,
       free_variables: fv,
-      mut_ref_free_variables: mfv
+      mut_ref_free_variables: mfv,
+      ref_free_variables: rfv
     }
   }
 }
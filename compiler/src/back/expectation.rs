@@ -0,0 +1,82 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interns every item that can be expected by the grammar (literals, character classes and `.`) into a table generated once in the grammar module.
+//! The generated code reports an error with the index of the item in this table (`state.error(&OAK_EXPECTATIONS, 2)`), so the runtime only manipulates integers and looks up the names when the error is displayed.
+
+use middle::typing::ast::*;
+
+use quote::format_ident;
+use syn::parse_quote;
+
+pub fn table_name() -> Ident {
+  format_ident!("OAK_EXPECTATIONS")
+}
+
+pub fn any_single_char_item() -> String {
+  String::from("<character>")
+}
+
+/// The case-insensitive literals are displayed with their suffix (`"select"i`) to distinguish them from the others.
+pub fn str_literal_item(lit: &StrLiteralExpr) -> String {
+  if lit.ignore_case { format!("{}", lit) }
+  else { lit.value.clone() }
+}
+
+pub fn char_class_item(classes: &CharacterClassExpr) -> String {
+  format!("{}", classes)
+}
+
+pub struct ExpectationTable
+{
+  items: Vec<String>
+}
+
+impl ExpectationTable
+{
+  pub fn new(grammar: &TGrammar) -> ExpectationTable {
+    let mut table = ExpectationTable { items: vec![] };
+    for expr in &grammar.exprs {
+      match expr {
+        &StrLiteral(ref lit) => table.intern(str_literal_item(lit)),
+        &CharacterClass(ref classes) => table.intern(char_class_item(classes)),
+        &AnySingleChar => table.intern(any_single_char_item()),
+        _ => ()
+      }
+    }
+    table
+  }
+
+  fn intern(&mut self, item: String) {
+    if !self.items.contains(&item) {
+      self.items.push(item);
+    }
+  }
+
+  pub fn id_of(&self, item: &str) -> usize {
+    self.items.iter()
+      .position(|i| i == item)
+      .expect("[bug] expected item not registered in the expectation table (please report this issue).")
+  }
+
+  pub fn compile(&self) -> syn::Item {
+    let name = table_name();
+    let len = self.items.len();
+    let items = &self.items;
+    parse_quote!(
+      #[allow(dead_code)]
+      static #name: [&'static str; #len] = [#(#items),*];
+    )
+  }
+}
@@ -32,7 +32,7 @@ We write `[e](s,f)` the compilation of the expression `e` with the success conti
     s = state.success((v1,v2,...,vN))
     f = state.failure()
 
-  ["a"](s,f) = if state.consume_prefix("a") { s } else { state.error("a"); f }
+  ["a"](s,f) = if state.consume_prefix("a") { s } else { state.error(&OAK_EXPECTATIONS, i); f }
 
   [e1 e2](s,f) = [e1]([e2](s,f), f)
 
@@ -48,6 +48,8 @@ We write `[e](s,f)` the compilation of the expression `e` with the success conti
 
 ```
 
+The index `i` refers to `"a"` in the table of expected items, generated once per grammar (see `expectation::ExpectationTable`).
+
 A first problem is the name collision.
 Indeed, we declare `result` and `mark1` when compiling `e?` but maybe the same names are already used in the code outside of this expression.
 We avoid this problem by generating fresh variables, thanks to the structure `name_factory::NameFactory`, which is kept in the `context` structure.
@@ -112,11 +114,20 @@ When building the value `(T2, T3)`, we only care about `w` and `z`, hence if a c
 mod context;
 mod continuation;
 mod name_factory;
+pub mod expectation;
 mod compiler;
+mod bytecode;
 
 use middle::typing::ast::*;
 
-pub fn compile(grammar: TGrammar) -> proc_macro2::TokenStream
+/// `cfg` is the attribute enabling this grammar's configuration, see `middle::configuration`.
+pub fn compile(grammar: TGrammar, cfg: Option<syn::Attribute>) -> proc_macro2::TokenStream
+{
+  compiler::GrammarCompiler::compile(grammar, cfg)
+}
+
+/// The Rust type of the expression `expr_idx` in the generated code.
+pub fn rust_type(grammar: &TGrammar, expr_idx: usize) -> syn::Type
 {
-  compiler::GrammarCompiler::compile(grammar)
+  compiler::rtype::TypeCompiler::compile(grammar, expr_idx)
 }
@@ -0,0 +1,266 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Diagnostics of the analyses, built with `span.error(..)`, `span.warning(..)` or `span.note(..)` and emitted with `emit()`.
+//! With the `nightly` feature, they are emitted with `proc_macro::Diagnostic` when the grammar is compiled by a procedural macro.
+//! Otherwise, they are collected and expanded along the generated code by `expand_diagnostics`: errors become `compile_error!` invocations, and warnings and notes become the use of a deprecated item whose deprecation note is the message.
+//! Outside of a procedural macro, the tools of Oak retrieve them with `render_diagnostics`, which prints them with their positions in the grammar file.
+//! Similarly, `oak_file!` relocates them on its invocation with their positions in the grammar file written in the message.
+//!
+//! The grammar is analysed once per `#[cfg]` configuration, therefore identical diagnostics are only emitted once.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote_spanned;
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Level {
+  Error,
+  Warning,
+  Note,
+  Help
+}
+
+impl Level {
+  fn name(self) -> &'static str {
+    match self {
+      Level::Error => "error",
+      Level::Warning => "warning",
+      Level::Note => "note",
+      Level::Help => "help"
+    }
+  }
+}
+
+thread_local! {
+  /// The diagnostics already emitted during the current expansion.
+  static EMITTED: RefCell<BTreeSet<String>> = const { RefCell::new(BTreeSet::new()) };
+  /// The diagnostics not yet expanded when `proc_macro::Diagnostic` is not available.
+  static PENDING: RefCell<Vec<Diagnostic>> = const { RefCell::new(Vec::new()) };
+  /// `true` while the diagnostics are collected by `collect_diagnostics` instead of being emitted.
+  static COLLECTING: Cell<bool> = const { Cell::new(false) };
+}
+
+pub trait SpanDiagnostic
+{
+  fn error<T: Into<String>>(self, message: T) -> Diagnostic;
+  fn warning<T: Into<String>>(self, message: T) -> Diagnostic;
+  fn note<T: Into<String>>(self, message: T) -> Diagnostic;
+}
+
+impl SpanDiagnostic for Span
+{
+  fn error<T: Into<String>>(self, message: T) -> Diagnostic {
+    Diagnostic::new(Level::Error, self, message.into())
+  }
+
+  fn warning<T: Into<String>>(self, message: T) -> Diagnostic {
+    Diagnostic::new(Level::Warning, self, message.into())
+  }
+
+  fn note<T: Into<String>>(self, message: T) -> Diagnostic {
+    Diagnostic::new(Level::Note, self, message.into())
+  }
+}
+
+pub struct Diagnostic
+{
+  level: Level,
+  span: Span,
+  message: String,
+  children: Vec<(Level, Option<Span>, String)>
+}
+
+impl Diagnostic
+{
+  fn new(level: Level, span: Span, message: String) -> Self {
+    Diagnostic { level, span, message, children: vec![] }
+  }
+
+  fn child<T: Into<String>>(mut self, level: Level, span: Option<Span>, message: T) -> Self {
+    self.children.push((level, span, message.into()));
+    self
+  }
+
+  pub fn note<T: Into<String>>(self, message: T) -> Self {
+    self.child(Level::Note, None, message)
+  }
+
+  pub fn help<T: Into<String>>(self, message: T) -> Self {
+    self.child(Level::Help, None, message)
+  }
+
+  pub fn span_note<T: Into<String>>(self, span: Span, message: T) -> Self {
+    self.child(Level::Note, Some(span), message)
+  }
+
+  pub fn span_help<T: Into<String>>(self, span: Span, message: T) -> Self {
+    self.child(Level::Help, Some(span), message)
+  }
+
+  pub fn emit(self) {
+    let key = format!("{} {:?} {}", self.level.name(), self.span, self.full_message());
+    if EMITTED.with(|emitted| emitted.borrow_mut().insert(key)) {
+      self.emit_unique();
+    }
+  }
+
+  /// The message followed by the messages of the children, one per line.
+  fn full_message(&self) -> String {
+    let mut message = self.message.clone();
+    for &(level, _, ref child) in &self.children {
+      message.push_str(&format!("\n{}: {}", level.name(), child));
+    }
+    message
+  }
+
+  fn emit_unique(self) {
+    if COLLECTING.with(|collecting| collecting.get()) {
+      PENDING.with(|pending| pending.borrow_mut().push(self));
+    }
+    else {
+      self.emit_to_compiler();
+    }
+  }
+
+  #[cfg(feature = "nightly")]
+  fn emit_to_compiler(self) {
+    if !proc_macro::is_available() {
+      PENDING.with(|pending| pending.borrow_mut().push(self));
+      return;
+    }
+    let level = match self.level {
+      Level::Error => proc_macro::Level::Error,
+      Level::Warning => proc_macro::Level::Warning,
+      Level::Note => proc_macro::Level::Note,
+      Level::Help => proc_macro::Level::Help
+    };
+    let mut diagnostic = proc_macro::Diagnostic::spanned(self.span.unwrap(), level, self.message);
+    for (level, span, message) in self.children {
+      diagnostic = match (level, span) {
+        (Level::Help, Some(span)) => diagnostic.span_help(span.unwrap(), message),
+        (Level::Help, None) => diagnostic.help(message),
+        (_, Some(span)) => diagnostic.span_note(span.unwrap(), message),
+        (_, None) => diagnostic.note(message)
+      };
+    }
+    diagnostic.emit();
+  }
+
+  #[cfg(not(feature = "nightly"))]
+  fn emit_to_compiler(self) {
+    PENDING.with(|pending| pending.borrow_mut().push(self));
+  }
+
+  fn expand(&self) -> TokenStream {
+    let span = self.span;
+    let message = self.full_message();
+    match self.level {
+      Level::Error => quote_spanned!(span=> compile_error!{#message}),
+      _ => {
+        let message = if self.level == Level::Warning { message } else { format!("{}: {}", self.level.name(), message) };
+        quote_spanned!(span=>
+          const _: () = {
+            #[deprecated(note = #message)]
+            #[allow(non_camel_case_types)]
+            struct oak_warning;
+            let _ = oak_warning;
+          };
+        )
+      }
+    }
+  }
+
+  pub fn is_error(&self) -> bool {
+    self.level == Level::Error
+  }
+
+  /// Prints the diagnostic with its position in the grammar file `path`, in the format of rustc.
+  pub fn render(&self, path: &str) -> String {
+    format!("{}: {}", self.level.name(), self.located_message(path))
+  }
+
+  /// The message followed by the positions in the grammar file `path` and by the children.
+  fn located_message(&self, path: &str) -> String {
+    let location = |span: Span| format!("  --> {}:{}:{}", path, span.start().line, span.start().column + 1);
+    let mut lines = vec![self.message.clone(), location(self.span)];
+    for &(level, span, ref message) in &self.children {
+      lines.push(format!("  = {}: {}", level.name(), message));
+      if let Some(span) = span {
+        lines.push(location(span));
+      }
+    }
+    lines.join("\n")
+  }
+
+  /// The same diagnostic on `span`, the positions in the grammar file `path` are written in the message.
+  /// The diagnostics of a grammar loaded from a file have spans without meaning for the compiler.
+  pub fn relocate(&self, span: Span, path: &str) -> Diagnostic {
+    Diagnostic::new(self.level, span, self.located_message(path))
+  }
+}
+
+/// The diagnostics emitted since the last call, which also starts a new deduplication.
+pub fn take_diagnostics() -> Vec<Diagnostic> {
+  EMITTED.with(|emitted| emitted.borrow_mut().clear());
+  PENDING.with(|pending| std::mem::take(&mut *pending.borrow_mut()))
+}
+
+/// Runs `f` and returns the diagnostics emitted meanwhile instead of emitting them.
+pub fn collect_diagnostics<R, F: FnOnce() -> R>(f: F) -> (R, Vec<Diagnostic>) {
+  let pending = take_diagnostics();
+  COLLECTING.with(|collecting| collecting.set(true));
+  let result = f();
+  COLLECTING.with(|collecting| collecting.set(false));
+  let collected = take_diagnostics();
+  PENDING.with(|p| *p.borrow_mut() = pending);
+  (result, collected)
+}
+
+/// The diagnostics to expand along the generated code, it is empty with the `nightly` feature since they are already emitted.
+/// It must be called at the end of the expansion of the macro.
+pub fn expand_diagnostics() -> TokenStream {
+  take_diagnostics().iter().map(Diagnostic::expand).collect()
+}
+
+/// The diagnostics emitted since the last call of `take_diagnostics`, printed with their positions in the grammar file `path`.
+pub fn render_diagnostics(path: &str) -> RenderedDiagnostics {
+  RenderedDiagnostics::new(take_diagnostics(), path)
+}
+
+/// The diagnostics printed by `render_diagnostics`, the notes are counted as warnings.
+pub struct RenderedDiagnostics
+{
+  pub errors: Vec<String>,
+  pub warnings: Vec<String>
+}
+
+impl RenderedDiagnostics
+{
+  /// Prints the `diagnostics` with their positions in the grammar file `path`.
+  pub fn new(diagnostics: Vec<Diagnostic>, path: &str) -> Self {
+    let mut rendered = RenderedDiagnostics { errors: vec![], warnings: vec![] };
+    for diagnostic in diagnostics {
+      if diagnostic.is_error() {
+        rendered.errors.push(diagnostic.render(path));
+      }
+      else {
+        rendered.warnings.push(diagnostic.render(path));
+      }
+    }
+    rendered
+  }
+}
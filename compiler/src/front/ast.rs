@@ -14,6 +14,9 @@
 
 pub use ast::*;
 
+use std::collections::HashMap;
+
+#[derive(Clone)]
 pub struct FGrammar
 {
   pub start_span: Span,
@@ -21,7 +24,9 @@ pub struct FGrammar
   pub exprs: Vec<Expression>,
   pub exprs_info: Vec<FExpressionInfo>,
   pub rust_items: Vec<syn::Item>,
-  pub attributes: Vec<syn::Attribute>
+  pub attributes: Vec<syn::Attribute>,
+  /// `#[cfg(..)]` attributes of the alternatives of choices, indexed by the expression of the alternative.
+  pub branch_attributes: HashMap<usize, Vec<syn::Attribute>>
 }
 
 impl FGrammar
@@ -33,7 +38,8 @@ impl FGrammar
       exprs: vec![],
       exprs_info: vec![],
       rust_items: vec![],
-      attributes: vec![]
+      attributes: vec![],
+      branch_attributes: HashMap::new()
     }
   }
 
@@ -44,8 +50,10 @@ impl FGrammar
     expr_idx
   }
 
-  pub fn push_rule(&mut self, name: Ident, def: usize) {
-    self.rules.push(Rule::new(name, def));
+  pub fn push_rule(&mut self, name: Ident, def: usize, visibility: Option<syn::Visibility>,
+    attributes: Vec<syn::Attribute>)
+  {
+    self.rules.push(Rule::new(name, def, visibility, attributes));
   }
 
   pub fn push_attrs(&mut self, attrs: Vec<syn::Attribute>) {
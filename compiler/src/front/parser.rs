@@ -34,6 +34,11 @@ impl FGrammar {
     (&self.exprs_info[index] as &FExpressionInfo).span()
   }
 
+  /// Spans can only be joined on a nightly compiler, otherwise the span starts and ends at `lo`.
+  fn join_spans(lo: Span, hi: Span) -> Span {
+    lo.join(hi).unwrap_or(lo)
+  }
+
   fn parse_blocks(&mut self, ps: ParseStream) -> Result<()> {
     while !ps.is_empty() {
       self.push_attrs(ps.call(Attribute::parse_inner)?);
@@ -50,8 +55,21 @@ impl FGrammar {
   // A rule can have two shapes:
   //   1. rule1 = ...     (untyped)
   //   2. rule2:ty = ...  (typed)
+  // Both can be preceded by outer attributes such as doc comments, and a visibility such as `pub` or `pub(crate)`.
   fn peek_rule_lhs(&mut self, ps: ParseStream) -> bool {
-    if ps.peek(Ident) {
+    if ps.peek(Token![#]) {
+      let ps2 = ps.fork();
+      match ps2.call(Attribute::parse_outer) {
+        Ok(_) => self.peek_rule_lhs(&ps2),
+        Err(_) => false
+      }
+    }
+    else if ps.peek(Token![pub]) {
+      let ps2 = ps.fork();
+      let _: Result<syn::Visibility> = ps2.parse();
+      self.peek_rule_lhs(&ps2)
+    }
+    else if ps.peek(Ident) {
       if ps.peek2(Token![=]) {
         true
       }
@@ -70,6 +88,10 @@ impl FGrammar {
   }
 
   fn parse_rule(&mut self, ps: ParseStream) -> Result<()> {
+    let attributes = ps.call(Attribute::parse_outer)?;
+    let visibility =
+      if ps.peek(Token![pub]) { Some(ps.parse::<syn::Visibility>()?) }
+      else { None };
     let name: Ident = ps.parse()?;
     let (span, ty) = Self::parse_type(ps)?;
     let _: Token![=] = ps.parse()?;
@@ -78,15 +100,22 @@ impl FGrammar {
     if ty != IType::Infer {
       body = self.alloc_expr(span, TypeAscription(body, ty))
     }
-    self.push_rule(name, body);
+    self.push_rule(name, body, visibility, attributes);
     Ok(())
   }
 
   fn parse_rule_choice(&mut self, ps: ParseStream, rule_name: &str) -> Result<usize> {
     let mut choices = Vec::new();
+    let mut conditional = false;
     loop {
+      let attributes = Self::parse_branch_attributes(ps, rule_name)?;
       let spanned_expr = self.parse_spanned_expr(ps, rule_name)?;
-      choices.push(self.parse_semantic_action(ps, spanned_expr)?);
+      let branch = self.parse_semantic_action(ps, spanned_expr)?;
+      if !attributes.is_empty() {
+        conditional = true;
+        self.branch_attributes.insert(branch, attributes);
+      }
+      choices.push(branch);
       if ps.peek(Token![/]) {
         let _: Token![/] = ps.parse()?;
       }
@@ -95,16 +124,29 @@ impl FGrammar {
       }
     }
     let res =
-      if choices.len() == 1 {
+      // A single conditional branch is kept in a choice so it can be removed during configuration.
+      if choices.len() == 1 && !conditional {
         choices.pop().unwrap()
       } else {
         let lo = self.span_of(choices[0]);
         let hi = self.span_of(choices[choices.len() - 1]);
-        self.alloc_expr(lo.join(hi).unwrap(), Choice(choices))
+        self.alloc_expr(Self::join_spans(lo, hi), Choice(choices))
       };
     Ok(res)
   }
 
+  // Alternatives can only be annotated with `#[cfg(..)]`, e.g. `r = #[cfg(feature = "x")] e1 / e2`.
+  fn parse_branch_attributes(ps: ParseStream, rule_name: &str) -> Result<Vec<Attribute>> {
+    let attributes = ps.call(Attribute::parse_outer)?;
+    for attr in &attributes {
+      if !attr.path.is_ident("cfg") {
+        return Err(Error::new(attr.span(), format!(
+          "only `#[cfg(..)]` attributes are allowed on alternatives (in rule `{}`).", rule_name).as_str()));
+      }
+    }
+    Ok(attributes)
+  }
+
   fn peek_unit_type(ps: ParseStream, invisible: bool) -> bool {
     let ps2 = ps.fork();
     let try = || {
@@ -206,7 +248,7 @@ impl FGrammar {
       return Ok(seq[0]);
     }
     let hi = self.span_of(seq[seq.len() - 1]);
-    Ok(self.alloc_expr(lo.join(hi).unwrap(), Sequence(seq)))
+    Ok(self.alloc_expr(Self::join_spans(lo, hi), Sequence(seq)))
   }
 
   fn parse_typed_expr(&mut self, ps: ParseStream, rule_name: &str) -> Result<Option<usize>> {
@@ -244,7 +286,7 @@ impl FGrammar {
   {
     match self.parse_suffixed_expr(ps, rule_name)? {
       Some(expr) => {
-        let span = lo.join(self.span_of(expr)).unwrap();
+        let span = Self::join_spans(lo, self.span_of(expr));
         Ok(self.alloc_expr(span, make_prefix(expr)))
       }
       None => {
@@ -264,7 +306,7 @@ impl FGrammar {
       Some(expr) => expr,
       None => return Ok(None),
     };
-    let span = lo.join(ps.span()).unwrap();
+    let span = Self::join_spans(lo, ps.span());
     let res =
       if ps.peek(Token![*]) {
         let _: Token![*] = ps.parse()?;
@@ -320,7 +362,15 @@ impl FGrammar {
       // String literal "let", "fn", ...
       if ps.peek(LitStr) {
         let lit_str: LitStr = ps.parse()?;
-        Some(self.alloc_expr(span, StrLiteral(lit_str.value())))
+        let ignore_case = match lit_str.suffix() {
+          "" => false,
+          "i" => true,
+          suffix => return Err(Error::new(span, format!(
+            "Unknown suffix `{}` on the string literal (in rule {}). \
+            Only the suffix `i` is allowed, as in `\"select\"i`, to match the literal case-insensitively.",
+            suffix, rule_name).as_str()))
+        };
+        Some(self.alloc_expr(span, StrLiteral(StrLiteralExpr::new(lit_str.value(), ignore_case))))
       }
       // Any character `.`
       else if ps.peek(Token![.]) {
@@ -349,8 +399,8 @@ impl FGrammar {
       // Character class `["0-9"]`
       else if Self::peek_bracket(ps) {
         let sub_ps;
-        let _ = bracketed!(sub_ps in ps);
-        Some(self.parse_char_class(&sub_ps, span, rule_name)?)
+        let bracket = bracketed!(sub_ps in ps);
+        Some(self.parse_char_class(&sub_ps, bracket.span, rule_name)?)
       }
       else if ps.peek(Token![..]) {
         return Err(Error::new(span,
@@ -365,6 +415,7 @@ impl FGrammar {
   }
 
   fn parse_char_class(&mut self, ps: ParseStream, span: Span, rule_name: &str) -> Result<usize> {
+    let negated = ps.parse::<Option<Token![^]>>()?.is_some();
     if ps.peek(LitStr) {
       let lit_str: LitStr = ps.parse()?;
       if lit_str.value().is_empty() {
@@ -372,71 +423,148 @@ impl FGrammar {
           "Empty character classes are forbidden. For empty expression \
           you can use the empty string literal `\"\"`."))
       }
-      self.parse_set_of_char_range(span, lit_str.value(), rule_name)
+      let items = Self::tokenize_char_class(span, lit_str.value(), rule_name)?;
+      let (intervals, sets) = Self::parse_set_of_char_range(span, items, rule_name)?;
+      Ok(self.alloc_expr(span, CharacterClass(CharacterClassExpr::new(intervals, sets, negated))))
     }
     else {
       Err(Error::new(span,
         format!("Unexpected character in this character class (in rule {}). \
-            `[` must only be followed by a string literal (such as in `[\"a-z\"]`).", rule_name).as_str()))
+            `[` must only be followed by a string literal (such as in `[\"a-z\"]` or `[^\"a-z\"]`).", rule_name).as_str()))
     }
   }
 
-  fn parse_set_of_char_range(&mut self, span: Span, ranges: String, rule_name: &str) -> Result<usize> {
-    let mut ranges = ranges.chars().peekable();
-    let mut intervals = vec![];
-    match ranges.peek() {
-      Some(&sep) if sep == '-' => {
-        intervals.push(CharacterInterval::new('-', '-'));
-        ranges.next();
-      }
-      _ => ()
+  /// Splits the content of a character class into characters and named sets, and resolves the escape sequences.
+  fn tokenize_char_class(span: Span, class: String, rule_name: &str) -> Result<Vec<ClassItem>> {
+    let mut chars = class.chars().peekable();
+    let mut items = vec![];
+    while let Some(c) = chars.next() {
+      let item = match c {
+        '\\' => Self::parse_class_escape(span, &mut chars, rule_name)?,
+        '[' if chars.peek() == Some(&':') => {
+          chars.next();
+          let name: String = chars.by_ref().take_while(|&c| c != ':').collect();
+          if chars.next() != Some(']') {
+            return Err(Error::new(span, format!(
+              "Unterminated named set `[:{}` in character class, it must be of the form `[:alpha:]` (in rule {}).",
+              name, rule_name).as_str()));
+          }
+          ClassItem::Intervals(posix_class(&name).ok_or_else(|| Error::new(span, format!(
+            "Unknown named set `[:{}:]` in character class, the known sets are {} (in rule {}).",
+            name, POSIX_CLASSES.iter().map(|n| format!("`[:{}:]`", n)).collect::<Vec<_>>().join(", "),
+            rule_name).as_str()))?)
+        }
+        '-' => ClassItem::Separator,
+        c => ClassItem::Char(c)
+      };
+      items.push(item);
     }
-    loop {
-      let char_set = self.parse_char_range(span, &mut ranges, rule_name)?;
-      intervals.extend_from_slice(char_set.as_slice());
-      if char_set.is_empty() {
-          break;
+    Ok(items)
+  }
+
+  fn parse_class_escape<'b>(span: Span, chars: &mut Peekable<Chars<'b>>, rule_name: &str) -> Result<ClassItem> {
+    let escape_err = |msg: String| Error::new(span, format!("{} (in rule {}).", msg, rule_name).as_str());
+    match chars.next() {
+      Some('n') => Ok(ClassItem::Char('\n')),
+      Some('r') => Ok(ClassItem::Char('\r')),
+      Some('t') => Ok(ClassItem::Char('\t')),
+      Some('0') => Ok(ClassItem::Char('\0')),
+      Some(c @ '-') | Some(c @ ']') | Some(c @ '[') | Some(c @ '^') | Some(c @ '\\') => Ok(ClassItem::Char(c)),
+      Some('u') | Some('p') if chars.peek() != Some(&'{') =>
+        Err(escape_err(format!("Expected `{{` after `\\u` or `\\p` in character class"))),
+      Some('u') => {
+        chars.next();
+        let code: String = chars.by_ref().take_while(|&c| c != '}').collect();
+        u32::from_str_radix(&code, 16).ok()
+          .and_then(std::char::from_u32)
+          .map(ClassItem::Char)
+          .ok_or_else(|| escape_err(format!("Invalid unicode escape `\\u{{{}}}` in character class", code)))
       }
+      Some('p') => {
+        chars.next();
+        let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+        if UNICODE_SETS.contains(&name.as_str()) {
+          Ok(ClassItem::Unicode(name))
+        }
+        else {
+          Err(escape_err(format!("Unknown Unicode set `\\p{{{}}}` in character class, the known sets are {}",
+            name, UNICODE_SETS.iter().map(|n| format!("`{}`", n)).collect::<Vec<_>>().join(", "))))
+        }
+      }
+      Some(c) => Err(escape_err(format!("Unknown escape sequence `\\{}` in character class, \
+        only `\\-`, `\\]`, `\\[`, `\\^`, `\\\\`, `\\n`, `\\r`, `\\t`, `\\0`, `\\u{{..}}` and `\\p{{..}}` are allowed", c))),
+      // A trailing backslash has nothing to escape, it is the character itself as it was before the escape sequences, e.g. `["\\"]`.
+      None => Ok(ClassItem::Char('\\'))
     }
-    Ok(self.alloc_expr(span, CharacterClass(CharacterClassExpr::new(intervals))))
   }
 
-  fn parse_char_range<'b>(&mut self, span: Span, ranges: &mut Peekable<Chars<'b>>, rule_name: &str) -> Result<Vec<CharacterInterval>> {
-    let mut res = vec![];
+  fn parse_set_of_char_range(span: Span, items: Vec<ClassItem>, rule_name: &str)
+    -> Result<(Vec<CharacterInterval>, Vec<String>)>
+  {
     let separator_err = format!(
       "Unexpected separator `-`. Put it in the start or the end if you want \
-      to accept it as a character in the set. Otherwise, you should only use it for \
+      to accept it as a character in the set, or escape it with `\\-`. Otherwise, you should only use it for \
       character intervals as in `[\"a-z\"]` (in rule {}).",
       rule_name);
-    let lo = ranges.next();
-    // Twisted logic due to the fact that `peek` borrows the ranges...
-    let lo = {
-      let next = ranges.peek();
-      match (lo, next) {
-        (Some('-'), Some(_)) => {
-          return Err(Error::new(span, separator_err.as_str()));
-        }
-        (Some(lo), Some(&sep)) if sep == '-' => {
-          lo
+    let mut intervals = vec![];
+    let mut sets = vec![];
+    let last = items.len() - 1;
+    let mut i = 0;
+    while i <= last {
+      match (&items[i], items.get(i+1), items.get(i+2)) {
+        (&ClassItem::Char(lo), Some(&ClassItem::Separator), Some(&ClassItem::Char(hi))) => {
+          intervals.push(CharacterInterval::new(lo, hi));
+          i += 3;
+          continue;
         }
-        (Some(lo), _) => {
-          res.push(CharacterInterval::new(lo, lo)); // If lo == '-', it ends the class, allowed.
-          return Ok(res);
+        (&ClassItem::Char(_), Some(&ClassItem::Separator), Some(_)) => {
+          return Err(Error::new(span, separator_err.as_str()));
         }
-        (None, _) => return Ok(res),
+        (&ClassItem::Char(c), _, _) => intervals.push(CharacterInterval::new(c, c)),
+        (&ClassItem::Separator, _, _) if i == 0 || i == last => intervals.push(CharacterInterval::new('-', '-')),
+        (&ClassItem::Separator, _, _) => return Err(Error::new(span, separator_err.as_str())),
+        (&ClassItem::Intervals(ref posix), _, _) => intervals.extend(posix.iter().cloned()),
+        (&ClassItem::Unicode(ref name), _, _) => sets.push(name.clone())
       }
-    };
-    ranges.next();
-    match ranges.next() {
-      Some('-') => { return Err(Error::new(span, separator_err.as_str())); }
-      Some(hi) => {
-        res.push(CharacterInterval::new(lo, hi));
-      }
-      None => {
-        res.push(CharacterInterval::new(lo, lo));
-        res.push(CharacterInterval::new('-', '-'));
-      }
-    };
-    Ok(res)
+      i += 1;
+    }
+    Ok((intervals, sets))
   }
 }
+
+/// An element of a character class before the intervals are built.
+enum ClassItem {
+  Char(char),
+  /// An unescaped `-`.
+  Separator,
+  /// A POSIX named set such as `[:alpha:]`.
+  Intervals(Vec<CharacterInterval>),
+  /// A Unicode set such as `\p{L}`.
+  Unicode(String)
+}
+
+static POSIX_CLASSES: [&'static str; 13] = [
+  "alpha", "digit", "alnum", "upper", "lower", "space", "blank",
+  "punct", "xdigit", "cntrl", "print", "graph", "word"
+];
+
+/// The ASCII characters of the POSIX named sets.
+fn posix_class(name: &str) -> Option<Vec<CharacterInterval>> {
+  let ranges: &[(char, char)] = match name {
+    "alpha" => &[('a', 'z'), ('A', 'Z')],
+    "digit" => &[('0', '9')],
+    "alnum" => &[('a', 'z'), ('A', 'Z'), ('0', '9')],
+    "upper" => &[('A', 'Z')],
+    "lower" => &[('a', 'z')],
+    "space" => &[(' ', ' '), ('\t', '\r')],
+    "blank" => &[(' ', ' '), ('\t', '\t')],
+    "punct" => &[('!', '/'), (':', '@'), ('[', '`'), ('{', '~')],
+    "xdigit" => &[('0', '9'), ('A', 'F'), ('a', 'f')],
+    "cntrl" => &[('\0', '\x1F'), ('\x7F', '\x7F')],
+    "print" => &[(' ', '~')],
+    "graph" => &[('!', '~')],
+    "word" => &[('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')],
+    _ => return None
+  };
+  Some(ranges.iter().map(|&(lo, hi)| CharacterInterval::new(lo, hi)).collect())
+}
@@ -0,0 +1,87 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The passes of Oak compiling a grammar: the front-end parses it (`front`), the middle-end analyses and types it (`middle`) and the back-end generates its parser (`back`).
+//! They are shared by the procedural macro `oak!`, the build script helper `oak_build`, the command-line tool `oak` and the interpreter `oak_interpreter`.
+//! The diagnostics are emitted as described in `diagnostic`.
+
+#![cfg_attr(feature = "nightly", feature(proc_macro_diagnostic))]
+
+extern crate partial;
+extern crate syn;
+extern crate quote;
+#[cfg(feature = "nightly")]
+extern crate proc_macro;
+extern crate proc_macro2;
+
+pub mod ast;
+pub mod visitor;
+pub mod front;
+pub mod middle;
+pub mod back;
+pub mod identifier;
+pub mod diagnostic;
+
+use diagnostic::SpanDiagnostic;
+use front::ast::FGrammar;
+use middle::configuration::Configuration;
+use middle::typing::ast::TGrammar;
+
+use proc_macro2::TokenStream;
+
+/// Parses the grammar `source`, written as inside `oak! { ... }`, the lexing and parsing errors are emitted as diagnostics.
+pub fn parse(source: &str) -> Option<FGrammar> {
+  let tokens: TokenStream = match source.parse() {
+    Ok(tokens) => tokens,
+    Err(err) => {
+      let err: proc_macro2::LexError = err;
+      err.span().error(format!("{}", err)).emit();
+      return None;
+    }
+  };
+  match syn::parse2(tokens) {
+    Ok(ast) => Some(ast),
+    Err(err) => {
+      for err in err {
+        err.span().error(err.to_string()).emit();
+      }
+      None
+    }
+  }
+}
+
+/// Analyses and types the grammar in each of its configurations, it stops at the first configuration with errors.
+pub fn typecheck(ast: FGrammar) -> Vec<(Configuration, TGrammar)> {
+  let mut grammars = vec![];
+  for config in Configuration::all(&ast).unwrap_or_default() {
+    match middle::typecheck(ast.clone(), &config) {
+      Some(grammar) => grammars.push((config, grammar)),
+      None => break
+    }
+  }
+  grammars
+}
+
+/// Generates the code of the grammar for each of its configurations, the diagnostics are not expanded.
+pub fn compile(ast: FGrammar) -> TokenStream {
+  typecheck(ast).into_iter()
+    .map(|(config, grammar)| back::compile(grammar, config.cfg_attribute()))
+    .collect()
+}
+
+/// Generates the code of the grammar in the configuration `config` only, the code is not annotated with the `#[cfg]` attribute of the configuration.
+pub fn compile_configuration(ast: FGrammar, config: &Configuration) -> TokenStream {
+  middle::typecheck(ast, config)
+    .map_or_else(TokenStream::new, |grammar| back::compile(grammar, None))
+}
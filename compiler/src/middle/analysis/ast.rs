@@ -29,13 +29,34 @@ impl AGrammar
 
 pub struct GrammarAttributes
 {
-  pub print_typing: PrintLevel
+  pub print_typing: PrintLevel,
+  /// Rules without explicit visibility are private if `#![private_rules]` is present.
+  pub private_rules: bool,
+  /// Rules declared with `#![start(r1, r2)]`, entry points taking the full input are generated for them.
+  pub starts: Vec<Ident>,
+  /// `#![grammar_reference]` generates a constant `GRAMMAR_REFERENCE` documenting the rules in Markdown.
+  pub grammar_reference: bool,
+  /// `#![allow(unused)]` disables the warnings about unused rules.
+  pub allow_unused: bool,
+  /// `#![export_typing(path = "typing.json")]` writes the inferred types in a JSON document at this path.
+  pub export_typing: Option<String>,
+  /// `#![bytecode]` compiles the rules to instructions executed by the virtual machine of `oak_runtime`, see `back::bytecode`.
+  pub bytecode: bool,
+  /// `#![cst]` records the concrete syntax tree while parsing, see `oak_runtime::cst`.
+  pub cst: bool
 }
 
 impl Default for GrammarAttributes {
   fn default() -> Self {
     GrammarAttributes {
-      print_typing: PrintLevel::default()
+      print_typing: PrintLevel::default(),
+      private_rules: false,
+      starts: vec![],
+      grammar_reference: false,
+      allow_unused: false,
+      export_typing: None,
+      bytecode: false,
+      cst: false
     }
   }
 }
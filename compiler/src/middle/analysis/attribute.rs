@@ -0,0 +1,287 @@
+// Copyright 2014 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use middle::analysis::ast::*;
+
+use syn::Token;
+use syn::punctuated::Punctuated;
+
+pub fn decorate_with_attributes(mut grammar: AGrammar,
+  attributes: Vec<syn::Attribute>) -> Partial<AGrammar>
+{
+  check_rules_attributes(&grammar);
+  if merge_grammar_attributes(&mut grammar, attributes) {
+    Partial::Value(grammar)
+  }
+  else {
+    Partial::Fake(grammar)
+  }
+}
+
+fn warn_ignore_attr(span: Span) {
+    span.warning(format!(
+      "unknown attribute: it will be ignored."))
+    .emit();
+}
+
+/// The outer attributes allowed on rules are doc comments, which are copied on the generated functions, `#[allow(..)]` to silence the warnings of the analysis, `#[explain_type]` to trace the type inference of the rule and `#[trivia]` to print the rule as spacing (see `#![cst]`).
+fn check_rules_attributes(grammar: &AGrammar) {
+  for rule in &grammar.rules {
+    for attr in &rule.attributes {
+      if !attr.path.is_ident("doc") && !attr.path.is_ident("allow")
+       && !attr.path.is_ident("explain_type") && !attr.path.is_ident("trivia")
+      {
+        warn_ignore_attr(attr.span());
+      }
+    }
+  }
+}
+
+/// Returns `false` if an error occurred.
+fn merge_grammar_attributes(grammar: &mut AGrammar, attrs: Vec<syn::Attribute>) -> bool {
+  let mut no_error = true;
+  for attr in &attrs {
+    if let Some(ident) = attr.path.get_ident().cloned() {
+      no_error &= merge_grammar_attr(grammar, &ident, attr);
+    }
+    else {
+      warn_ignore_attr(attr.span());
+    }
+  }
+  if let Some(attr) = attrs.iter().find(|attr| attr.path.is_ident("cst")) {
+    no_error &= check_cst_attr(grammar, attr.span());
+  }
+  else {
+    warn_trivia_without_cst(grammar);
+  }
+  no_error
+}
+
+/// `#[trivia]` is only used by the printer generated by `#![cst]`.
+fn warn_trivia_without_cst(grammar: &AGrammar) {
+  for rule in &grammar.rules {
+    if let Some(attr) = rule.attributes.iter().find(|attr| attr.path.is_ident("trivia")) {
+      attr.span().warning(
+        "`#[trivia]` is only used by the printer generated by `#![cst]`: it will be ignored.")
+      .emit();
+    }
+  }
+}
+
+fn merge_grammar_attr(grammar: &mut AGrammar, ident: &Ident, attr: &syn::Attribute) -> bool {
+  match &*ident.to_string() {
+    "debug_typing" => {
+      grammar.merge_print_typing(PrintLevel::Debug);
+    },
+    "show_typing" => {
+      grammar.merge_print_typing(PrintLevel::Show);
+    },
+    "private_rules" => {
+      grammar.attributes.private_rules = true;
+    },
+    "grammar_reference" => {
+      grammar.attributes.grammar_reference = true;
+    },
+    "allow" => {
+      merge_allow_attr(grammar, attr);
+    },
+    "export_typing" => {
+      return merge_export_typing_attr(grammar, attr);
+    },
+    "start" => {
+      return merge_start_attr(grammar, attr);
+    },
+    "bytecode" => {
+      return merge_bytecode_attr(grammar);
+    },
+    "cst" => {
+      grammar.attributes.cst = true;
+    },
+    _ => {
+      warn_ignore_attr(ident.span());
+    }
+  }
+  true
+}
+
+/// `#![allow(unused)]` silences the warnings about unused rules in the whole grammar.
+fn merge_allow_attr(grammar: &mut AGrammar, attr: &syn::Attribute) {
+  match attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated) {
+    Ok(lints) => {
+      for lint in lints {
+        if lint == "unused" {
+          grammar.attributes.allow_unused = true;
+        }
+        else {
+          warn_ignore_attr(lint.span());
+        }
+      }
+    }
+    Err(_) => warn_ignore_attr(attr.span())
+  }
+}
+
+/// `#![export_typing(path = "...")]` exports the inferred types of the rules and expressions in a JSON document, the path is relative to the crate manifest.
+fn merge_export_typing_attr(grammar: &mut AGrammar, attr: &syn::Attribute) -> bool {
+  match attr.parse_meta() {
+    Ok(syn::Meta::List(ref list)) if list.nested.len() == 1 => {
+      if let syn::NestedMeta::Meta(syn::Meta::NameValue(ref meta)) = list.nested[0] {
+        if let syn::Lit::Str(ref path) = meta.lit {
+          if meta.path.is_ident("path") {
+            grammar.attributes.export_typing = Some(path.value());
+            return true;
+          }
+        }
+      }
+    }
+    _ => ()
+  }
+  attr.span().error(
+    "expected a path, for example `#![export_typing(path = \"target/typing.json\")]`.")
+  .emit();
+  false
+}
+
+/// `#![bytecode]` selects the bytecode backend. The values of its stack are `'static`, thus it cannot build the ranges of the stream (`... e`).
+fn merge_bytecode_attr(grammar: &mut AGrammar) -> bool {
+  grammar.attributes.bytecode = true;
+  let mut no_error = true;
+  for (idx, expr) in grammar.exprs.iter().enumerate() {
+    if let &Expression::RangeExpr(_) = expr {
+      grammar[idx].span().error(
+        "the range expression `...` is not supported by `#![bytecode]`, consider the span expression `..` instead.")
+      .emit();
+      no_error = false;
+    }
+  }
+  no_error
+}
+
+/// `#![cst]` is checked once all the attributes are merged: the virtual machine of `#![bytecode]` does not call the rule functions recording the nodes, and the entry point `parse_cst` is generated for the start rules.
+/// The methods `visit_node` and `visit_token` of the generated visitors dispatch to the methods of the rules, thus a rule cannot be named `node` or `token`.
+fn check_cst_attr(grammar: &AGrammar, span: Span) -> bool {
+  if grammar.attributes.bytecode {
+    span.error(
+      "`#![cst]` is not supported by `#![bytecode]`.")
+    .emit();
+    return false;
+  }
+  if grammar.attributes.starts.len() == 1 {
+    if let Some(rule) = grammar.rules.iter().find(|r| r.ident() == "cst") {
+      span.error(
+        "the entry point `parse_cst` generated by `#![cst]` clashes with the parser of the rule `cst`.")
+      .span_note(rule.span(), "rule `cst` declared here, consider renaming it.")
+      .emit();
+      return false;
+    }
+  }
+  if let Some(rule) = grammar.rules.iter().find(|r| r.ident() == "node" || r.ident() == "token") {
+    span.error(format!(
+      "the visitors generated by `#![cst]` already have a method `visit_{}`.", rule.ident()))
+    .span_note(rule.span(), format!("rule `{}` declared here, consider renaming it.", rule.ident()))
+    .emit();
+    return false;
+  }
+  let mut no_error = true;
+  for item in &grammar.rust_items {
+    for ident in type_namespace_idents(item) {
+      if ident == "visitor" || ident == "printer" {
+        span.error(format!(
+          "the module `{}` generated by `#![cst]` clashes with an item of the grammar.", ident))
+        .span_note(ident.span(), format!("`{}` declared here, consider renaming it.", ident))
+        .emit();
+        no_error = false;
+      }
+    }
+  }
+  no_error
+}
+
+/// The names declared by `item` in the namespace of the modules.
+fn type_namespace_idents(item: &syn::Item) -> Vec<Ident> {
+  fn use_idents(tree: &syn::UseTree, idents: &mut Vec<Ident>) {
+    match *tree {
+      syn::UseTree::Path(ref path) => use_idents(&path.tree, idents),
+      syn::UseTree::Name(ref name) => idents.push(name.ident.clone()),
+      syn::UseTree::Rename(ref rename) => idents.push(rename.rename.clone()),
+      syn::UseTree::Group(ref group) => {
+        for tree in &group.items {
+          use_idents(tree, idents);
+        }
+      }
+      syn::UseTree::Glob(_) => ()
+    }
+  }
+  match *item {
+    syn::Item::Mod(ref item) => vec![item.ident.clone()],
+    syn::Item::Struct(ref item) => vec![item.ident.clone()],
+    syn::Item::Enum(ref item) => vec![item.ident.clone()],
+    syn::Item::Union(ref item) => vec![item.ident.clone()],
+    syn::Item::Trait(ref item) => vec![item.ident.clone()],
+    syn::Item::Type(ref item) => vec![item.ident.clone()],
+    syn::Item::ExternCrate(ref item) =>
+      vec![item.rename.as_ref().map_or(item.ident.clone(), |rename| rename.1.clone())],
+    syn::Item::Use(ref item) => {
+      let mut idents = vec![];
+      use_idents(&item.tree, &mut idents);
+      idents
+    }
+    _ => vec![]
+  }
+}
+
+/// `#![start(r1, ..., rN)]` declares the rules for which the entry points `parse` and `parse_prefix` are generated.
+fn merge_start_attr(grammar: &mut AGrammar, attr: &syn::Attribute) -> bool {
+  let rules = match attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated) {
+    Ok(rules) => rules,
+    Err(_) => {
+      attr.span().error(
+        "expected a list of rules, for example `#![start(program)]`.")
+      .emit();
+      return false;
+    }
+  };
+  let mut no_error = true;
+  for rule in rules {
+    if !grammar.rules.iter().any(|r| r.ident() == rule) {
+      rule.span().error(format!(
+        "unknown rule `{}` declared as a start rule.", rule))
+      .emit();
+      no_error = false;
+    }
+    else if grammar.attributes.starts.contains(&rule) {
+      rule.span().warning(format!(
+        "rule `{}` is declared several times as a start rule.", rule))
+      .emit();
+    }
+    else {
+      grammar.attributes.starts.push(rule);
+    }
+  }
+  no_error && check_entry_points_names(grammar, attr.span())
+}
+
+/// The entry points are named `parse` and `parse_prefix` so they must not clash with the parser of a rule named `prefix`.
+fn check_entry_points_names(grammar: &AGrammar, span: Span) -> bool {
+  if grammar.attributes.starts.len() == 1 {
+    if let Some(rule) = grammar.rules.iter().find(|r| r.ident() == "prefix") {
+      span.error(
+        "the entry point `parse_prefix` generated by `#![start(..)]` clashes with the parser of the rule `prefix`.")
+      .span_note(rule.span(), "rule `prefix` declared here, consider renaming it.")
+      .emit();
+      return false;
+    }
+  }
+  true
+}
@@ -0,0 +1,101 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Normalizes the intervals of the character classes: they are sorted and the overlapping or adjacent intervals are merged, e.g. `["a-zA-Zb-d_"]` becomes `["A-Z_a-z"]`.
+//! A reversed interval such as `"z-a"` matches no character and is an error.
+//! We warn about the intervals already matched (`["a-zb"]`) or overlapping (`["a-mf-z"]`) with a previous interval of the class.
+
+use middle::analysis::ast::*;
+
+pub struct CharacterClassNormalization<'a>
+{
+  grammar: &'a AGrammar,
+  has_error: bool
+}
+
+impl<'a> CharacterClassNormalization<'a>
+{
+  pub fn analyse(mut grammar: AGrammar) -> Partial<AGrammar> {
+    let normalized = {
+      let mut analyser = CharacterClassNormalization { grammar: &grammar, has_error: false };
+      let normalized: Vec<(usize, CharacterClassExpr)> = grammar.exprs.iter()
+        .enumerate()
+        .filter_map(|(idx, expr)| match expr {
+          &Expression::CharacterClass(ref classes) => Some((idx, analyser.normalize(idx, classes))),
+          _ => None
+        })
+        .collect();
+      if analyser.has_error { return Partial::Nothing }
+      normalized
+    };
+    for (idx, classes) in normalized {
+      grammar.exprs[idx] = Expression::CharacterClass(classes);
+    }
+    Partial::Value(grammar)
+  }
+
+  fn normalize(&mut self, this: usize, classes: &CharacterClassExpr) -> CharacterClassExpr {
+    let mut intervals: Vec<CharacterInterval> = vec![];
+    for interval in &classes.intervals {
+      if interval.lo > interval.hi {
+        self.error_reversed_interval(this, interval);
+      }
+      else {
+        self.check_overlap(this, &intervals, interval);
+        intervals.push(interval.clone());
+      }
+    }
+    CharacterClassExpr::new(merge_intervals(intervals), classes.sets.clone(), classes.negated)
+  }
+
+  fn check_overlap(&self, this: usize, previous: &Vec<CharacterInterval>, interval: &CharacterInterval) {
+    let overlapping = previous.iter()
+      .find(|p| p.lo <= interval.hi && interval.lo <= p.hi);
+    if let Some(p) = overlapping {
+      let msg =
+        if p.lo <= interval.lo && interval.hi <= p.hi {
+          format!("`{}` is already matched by `{}` in this character class.", interval, p)
+        }
+        else {
+          format!("`{}` overlaps with `{}` in this character class.", interval, p)
+        };
+      self.grammar[this].span().warning(msg).emit();
+    }
+  }
+
+  fn error_reversed_interval(&mut self, this: usize, interval: &CharacterInterval) {
+    self.has_error = true;
+    let reversed = CharacterInterval::new(interval.hi, interval.lo);
+    self.grammar[this].span().error(format!(
+      "the interval `{}-{}` is reversed and matches no character.",
+      interval.escape_lo(), interval.escape_hi()))
+    .help(format!("write it `{}`.", reversed))
+    .emit();
+  }
+}
+
+/// Sorts the intervals and merges those that overlap or are adjacent.
+fn merge_intervals(mut intervals: Vec<CharacterInterval>) -> Vec<CharacterInterval> {
+  intervals.sort_by_key(|i| i.lo);
+  let mut merged: Vec<CharacterInterval> = vec![];
+  for interval in intervals {
+    match merged.last_mut() {
+      Some(ref mut last) if last.hi >= interval.lo || next_char(last.hi) == Some(interval.lo) => {
+        last.hi = last.hi.max(interval.hi);
+      }
+      _ => merged.push(interval)
+    }
+  }
+  merged
+}
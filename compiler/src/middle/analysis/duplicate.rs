@@ -12,8 +12,6 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-pub use std::collections::HashMap;
-
 use middle::analysis::ast::*;
 use partial::Partial::*;
 
@@ -86,9 +84,9 @@ impl<Item> DuplicateItem<Item> where
   }
 
   fn duplicate_items(&self, pre: &Item, current: Item) {
-    current.span().unstable()
+    current.span()
       .error(format!("duplicate definition of {} with name `{}`", self.what_is_duplicated, current.ident()))
-      .span_note(pre.span().unstable(), format!("previous definition of `{}` here", pre.ident()))
+      .span_note(pre.span(), format!("previous definition of `{}` here", pre.ident()))
       .emit();
   }
 
@@ -19,14 +19,18 @@ use middle::analysis::resolve_non_terminal::*;
 use middle::analysis::well_formedness::*;
 use middle::analysis::attribute::*;
 use middle::analysis::useless_chaining::*;
-// use middle::analysis::unreachable_rule::*;
+use middle::analysis::unreachable_rule::*;
+use middle::analysis::unused_rule::*;
+use middle::analysis::character_class::*;
 
 mod duplicate;
 mod resolve_non_terminal;
 mod well_formedness;
 mod attribute;
 mod useless_chaining;
-// mod unreachable_rule;
+mod unreachable_rule;
+mod unused_rule;
+mod character_class;
 pub mod ast;
 
 pub fn analyse(fgrammar: FGrammar) -> Partial<AGrammar> {
@@ -36,8 +40,10 @@ pub fn analyse(fgrammar: FGrammar) -> Partial<AGrammar> {
   rule_duplicate(grammar, fgrammar.rules)
   .and_then(|grammar| rust_functions_duplicate(grammar, frust_items))
   .and_then(|grammar| ResolveNonTerminal::resolve(grammar))
+  .and_then(|grammar| CharacterClassNormalization::analyse(grammar))
   .and_then(|grammar| WellFormedness::analyse(grammar))
   .and_then(|grammar| UselessChaining::analyse(grammar))
-  // .and_then(|grammar| UnreachableRule::analyse(grammar))   // This analysis must be reviewed and fixed.
+  .and_then(|grammar| UnreachableRule::analyse(grammar))
   .and_then(|grammar| decorate_with_attributes(grammar, fattributes))
+  .and_then(|grammar| UnusedRule::analyse(grammar))
 }
@@ -0,0 +1,334 @@
+// Copyright 2018 Chao Lin & William Sergeant (Sorbonne University)
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! This analysis warns about the alternatives of a choice that can never be reached because a previous alternative always succeeds on the inputs they match.
+//! For instance, in `"=" / "=="`, the second alternative is unreachable since `"="` succeeds on every input starting with `==`, and we suggest the reordering `"==" / "="`.
+//!
+//! An expression `e` is approximated by sets of words, where a word is a sequence of character sets (a literal `"ab"` is the word `[a][b]`, and a class `["a-z"]` the word `[a-z]`):
+//!  * `sure(e)`: `e` succeeds on every input starting with a word of this set.
+//!  * `exact(e)`: `e` succeeds on every input starting with a word of this set, by consuming exactly this word.
+//!  * `need(e)`: `e` only succeeds on inputs starting with a word of this set, `None` if we do not know.
+//!
+//! The alternative `e2` of `e1 / e2` is unreachable if every word of `need(e2)` starts with a word of `sure(e1)`.
+//! Sequences are approximated by concatenating the words of their sub-expressions, as long as we know exactly how much the prefix of the sequence consumes (repetitions are greedy, e.g. `"a"+ "a"` never succeeds).
+//! The sets are bounded in size, when the bound is exceeded `sure` and `exact` are truncated and `need` becomes unknown, which keeps the analysis sound.
+//!
+//! The warning can be silenced on a rule with `#[allow(unreachable_alternative)]`.
+
+use middle::analysis::ast::*;
+
+use std::collections::{HashMap, HashSet};
+
+/// A sequence of character sets, each set is represented by a list of intervals.
+type Word = Vec<Vec<(char, char)>>;
+
+const MAX_WORDS: usize = 32;
+
+#[derive(Clone, Default)]
+struct Prefixes
+{
+  sure: Vec<Word>,
+  exact: Vec<Word>,
+  need: Option<Vec<Word>>,
+  /// `true` if the expression always consumes exactly one word of `need` when it succeeds.
+  finite: bool
+}
+
+impl Prefixes
+{
+  fn word(word: Word) -> Prefixes {
+    Prefixes {
+      sure: vec![word.clone()],
+      exact: vec![word.clone()],
+      need: Some(vec![word]),
+      finite: true
+    }
+  }
+
+  /// The prefixes of an expression that always succeeds without requirement on the input, such as `e*`.
+  fn always() -> Prefixes {
+    Prefixes {
+      sure: vec![vec![]],
+      .. Prefixes::default()
+    }
+  }
+}
+
+fn concat(words1: &Vec<Word>, words2: &Vec<Word>) -> Vec<Word> {
+  let mut words = vec![];
+  for w1 in words1 {
+    for w2 in words2 {
+      let mut w = w1.clone();
+      w.extend(w2.iter().cloned());
+      words.push(w);
+    }
+  }
+  words
+}
+
+fn truncate(mut words: Vec<Word>) -> Vec<Word> {
+  words.truncate(MAX_WORDS);
+  words
+}
+
+fn bounded(words: Vec<Word>) -> Option<Vec<Word>> {
+  if words.len() > MAX_WORDS { None }
+  else { Some(words) }
+}
+
+fn set_contains(set: &Vec<(char, char)>, lo: char, hi: char) -> bool {
+  let mut lo = lo;
+  let mut intervals = set.clone();
+  intervals.sort();
+  for (l, h) in intervals {
+    if l <= lo && lo <= h {
+      if hi <= h { return true; }
+      match std::char::from_u32(h as u32 + 1) {
+        Some(next) => lo = next,
+        None => return false
+      }
+    }
+  }
+  false
+}
+
+fn is_subset(set1: &Vec<(char, char)>, set2: &Vec<(char, char)>) -> bool {
+  set1.iter().all(|&(lo, hi)| set_contains(set2, lo, hi))
+}
+
+fn intersects(set1: &Vec<(char, char)>, set2: &Vec<(char, char)>) -> bool {
+  set1.iter().any(|&(lo1, hi1)| set2.iter().any(|&(lo2, hi2)| lo1 <= hi2 && lo2 <= hi1))
+}
+
+/// `true` if every input starting with `word` also starts with `prefix`.
+fn starts_with(word: &Word, prefix: &Word) -> bool {
+  prefix.len() <= word.len()
+    && prefix.iter().zip(word.iter()).all(|(p, w)| is_subset(w, p))
+}
+
+/// `true` if an input can start with both `word1` and `word2`.
+fn compatible(word1: &Word, word2: &Word) -> bool {
+  word1.iter().zip(word2.iter()).all(|(c1, c2)| intersects(c1, c2))
+}
+
+pub struct UnreachableRule<'a>
+{
+  grammar: &'a AGrammar,
+  rules_prefixes: HashMap<Ident, Prefixes>,
+  /// Rules being visited, used to stop on recursive rules.
+  visiting: Vec<Ident>,
+  /// Choices already checked, since they can be visited again through rule calls.
+  checked_choices: HashSet<usize>
+}
+
+impl <'a> UnreachableRule<'a>
+{
+  pub fn analyse(grammar: AGrammar) -> Partial<AGrammar> {
+    UnreachableRule::check_unreachable_rule(&grammar);
+    Partial::Value(grammar)
+  }
+
+  fn check_unreachable_rule(grammar: &'a AGrammar) {
+    let mut analyser = UnreachableRule {
+      grammar,
+      rules_prefixes: HashMap::new(),
+      visiting: vec![],
+      checked_choices: HashSet::new()
+    };
+    for rule in &grammar.rules {
+      analyser.visit_non_terminal_symbol(rule.expr_idx, &rule.ident());
+    }
+  }
+
+  /// `true` if the rule being visited, which contains the choice being checked, allows its unreachable alternatives.
+  fn is_allowed(&self) -> bool {
+    self.visiting.last()
+      .map_or(false, |rule| self.grammar.find_rule_by_ident(rule).is_allowed("unreachable_alternative"))
+  }
+
+  fn check_choice(&self, this: usize, children: &Vec<usize>, prefixes: &Vec<Prefixes>) {
+    // The alternatives after an always-succeeding alternative are reported by the well-formedness analysis.
+    let last = prefixes.iter()
+      .position(|p| p.sure.contains(&vec![]))
+      .unwrap_or(children.len() - 1);
+    for j in 1..last+1 {
+      let need = match prefixes[j].need {
+        Some(ref need) if !need.is_empty() => need,
+        _ => continue
+      };
+      let shadowing = (0..j).find(|&i|
+        need.iter().all(|w| prefixes[i].sure.iter().any(|s| starts_with(w, s))));
+      if let Some(i) = shadowing {
+        self.warn_unreachable(this, children, prefixes, i, j);
+      }
+    }
+  }
+
+  fn warn_unreachable(&self, this: usize, children: &Vec<usize>, prefixes: &Vec<Prefixes>, i: usize, j: usize) {
+    let shadowing = self.grammar.display_expr(children[i]);
+    let equivalent = prefixes[j].sure.iter().any(|s|
+      prefixes[i].need.as_ref().map_or(false, |need| need.iter().all(|w| starts_with(w, s))));
+    let help =
+      if equivalent {
+        format!("these alternatives match the same inputs, consider removing one of them.")
+      }
+      else {
+        let mut reordered = children.clone();
+        let unreachable = reordered.remove(j);
+        reordered.insert(i, unreachable);
+        let reordered: Vec<String> = reordered.into_iter()
+          .map(|child| self.grammar.display_expr(child))
+          .collect();
+        format!("consider reordering the alternatives: `{}`.", reordered.join(" / "))
+      };
+    self.grammar[children[j]].span().warning(format!(
+      "this alternative is unreachable because `{}` succeeds on all the inputs it matches.", shadowing))
+    .span_note(self.grammar[children[i]].span(), "this alternative is tried before.")
+    .span_help(self.grammar[this].span(), help)
+    .emit();
+  }
+}
+
+impl<'a> ExprByIndex for UnreachableRule<'a>
+{
+  fn expr_by_index(&self, index: usize) -> Expression {
+    self.grammar.expr_by_index(index).clone()
+  }
+}
+
+impl<'a> Visitor<Prefixes> for UnreachableRule<'a>
+{
+  fn visit_str_literal(&mut self, _this: usize, lit: StrLiteralExpr) -> Prefixes {
+    let word: Word = lit.value.chars().map(|c| vec![(c, c)]).collect();
+    if lit.ignore_case {
+      // The literal matches at least its own characters, the other characters matched depend on the case folding and are unknown to the analysis.
+      Prefixes { sure: vec![word], .. Prefixes::default() }
+    }
+    else {
+      Prefixes::word(word)
+    }
+  }
+
+  fn visit_any_single_char(&mut self, _this: usize) -> Prefixes {
+    Prefixes::word(vec![vec![('\0', std::char::MAX)]])
+  }
+
+  fn visit_character_class(&mut self, _this: usize, char_class: CharacterClassExpr) -> Prefixes {
+    match char_class.matched_intervals() {
+      Some(intervals) => Prefixes::word(vec![intervals.iter().map(|i| (i.lo, i.hi)).collect()]),
+      // The characters of the Unicode sets are unknown to the analysis.
+      None => Prefixes::default()
+    }
+  }
+
+  fn visit_non_terminal_symbol(&mut self, _this: usize, rule: &Ident) -> Prefixes {
+    if let Some(prefixes) = self.rules_prefixes.get(rule) {
+      return prefixes.clone();
+    }
+    if self.visiting.contains(rule) {
+      return Prefixes::default();
+    }
+    self.visiting.push(rule.clone());
+    let prefixes = self.visit_expr(self.grammar.expr_index_of_rule(rule));
+    self.visiting.pop();
+    self.rules_prefixes.insert(rule.clone(), prefixes.clone());
+    prefixes
+  }
+
+  fn visit_sequence(&mut self, _this: usize, children: Vec<usize>) -> Prefixes {
+    let prefixes: Vec<Prefixes> = children.into_iter()
+      .map(|child| self.visit_expr(child))
+      .collect();
+    let (last, init) = prefixes.split_last().expect("sequence is not empty");
+    let mut exact = vec![vec![]];
+    for p in init {
+      exact = truncate(concat(&exact, &p.exact));
+    }
+    let sure = truncate(concat(&exact, &last.sure));
+    let exact = truncate(concat(&exact, &last.exact));
+    let mut need = Some(vec![vec![]]);
+    for p in &prefixes {
+      need = match (need, &p.need) {
+        (Some(need), &Some(ref words)) => bounded(concat(&need, words)),
+        _ => None
+      };
+      if !p.finite { break; }
+    }
+    Prefixes { sure, exact, need, finite: prefixes.iter().all(|p| p.finite) }
+  }
+
+  fn visit_choice(&mut self, this: usize, children: Vec<usize>) -> Prefixes {
+    let prefixes: Vec<Prefixes> = children.iter()
+      .map(|&child| self.visit_expr(child))
+      .collect();
+    if self.checked_choices.insert(this) && !self.is_allowed() {
+      self.check_choice(this, &children, &prefixes);
+    }
+    let mut choice = Prefixes { finite: true, need: Some(vec![]), .. Prefixes::default() };
+    for (i, p) in prefixes.iter().enumerate() {
+      choice.sure.extend(p.sure.iter().cloned());
+      // A word is consumed exactly by the choice only if the previous alternatives fail on it.
+      for w in &p.exact {
+        let previous_fail = prefixes[..i].iter().all(|prev|
+          prev.need.as_ref().map_or(false, |need| need.iter().all(|n| !compatible(n, w))));
+        if previous_fail {
+          choice.exact.push(w.clone());
+        }
+      }
+      choice.need = match (choice.need, &p.need) {
+        (Some(mut need), &Some(ref words)) => { need.extend(words.iter().cloned()); bounded(need) },
+        _ => None
+      };
+      choice.finite &= p.finite;
+    }
+    choice.sure = truncate(choice.sure);
+    choice.exact = truncate(choice.exact);
+    choice
+  }
+
+  fn visit_zero_or_more(&mut self, _this: usize, child: usize) -> Prefixes {
+    self.visit_expr(child);
+    Prefixes::always()
+  }
+
+  fn visit_optional(&mut self, _this: usize, child: usize) -> Prefixes {
+    self.visit_expr(child);
+    Prefixes::always()
+  }
+
+  fn visit_one_or_more(&mut self, _this: usize, child: usize) -> Prefixes {
+    let prefixes = self.visit_expr(child);
+    Prefixes {
+      sure: prefixes.sure,
+      need: prefixes.need,
+      .. Prefixes::default()
+    }
+  }
+
+  fn visit_not_predicate(&mut self, _this: usize, child: usize) -> Prefixes {
+    self.visit_expr(child);
+    Prefixes::default()
+  }
+
+  fn visit_and_predicate(&mut self, _this: usize, child: usize) -> Prefixes {
+    let prefixes = self.visit_expr(child);
+    Prefixes {
+      sure: prefixes.sure,
+      need: prefixes.need,
+      .. Prefixes::default()
+    }
+  }
+}
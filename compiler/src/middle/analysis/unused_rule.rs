@@ -0,0 +1,96 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Warns about the rules that are never used.
+//! We build the call graph of the rules and compute the rules reachable from the entry rules, which are the start rules (`#![start(..)]`) and the rules declared public (e.g. `pub r = ...`).
+//! The rules without visibility are not entry rules, even if they are public because `#![private_rules]` is not given, otherwise the warning would never be emitted on most grammars.
+//! If the grammar has no such rule, the first rule declared is considered to be the entry rule.
+//! The warning can be silenced with `#[allow(unused)]` on the rule, or for the whole grammar with `#![allow(unused)]`.
+
+use middle::analysis::ast::*;
+
+pub struct UnusedRule<'a>
+{
+  grammar: &'a AGrammar,
+  calls: Vec<Ident>
+}
+
+impl<'a> UnusedRule<'a>
+{
+  pub fn analyse(grammar: AGrammar) -> Partial<AGrammar> {
+    if !grammar.attributes.allow_unused {
+      UnusedRule::check_unused_rules(&grammar);
+    }
+    Partial::Value(grammar)
+  }
+
+  fn check_unused_rules(grammar: &'a AGrammar) {
+    let entry_rules = Self::entry_rules(grammar);
+    let mut analyser = UnusedRule { grammar, calls: vec![] };
+    let mut reachable = vec![];
+    let mut worklist = entry_rules.clone();
+    while let Some(rule) = worklist.pop() {
+      if !reachable.contains(&rule) {
+        worklist.extend(analyser.calls_of(&rule).into_iter());
+        reachable.push(rule);
+      }
+    }
+    let entry_rules: Vec<String> = entry_rules.iter().map(|r| format!("`{}`", r)).collect();
+    for rule in &grammar.rules {
+      if !reachable.contains(&rule.ident()) && !rule.is_allowed("unused") {
+        rule.span().warning(format!("rule `{}` is never used.", rule.ident()))
+          .help(format!("it is not reachable from the entry rules {}, \
+            use `#[allow(unused)]` on the rule to silence this warning.", entry_rules.join(", ")))
+          .emit();
+      }
+    }
+  }
+
+  fn entry_rules(grammar: &AGrammar) -> Vec<Ident> {
+    let mut entry_rules = grammar.attributes.starts.clone();
+    for rule in &grammar.rules {
+      if rule.is_public() && !entry_rules.contains(&rule.ident()) {
+        entry_rules.push(rule.ident());
+      }
+    }
+    if entry_rules.is_empty() {
+      entry_rules.push(grammar.rules[0].ident());
+    }
+    entry_rules
+  }
+
+  fn calls_of(&mut self, rule: &Ident) -> Vec<Ident> {
+    self.calls.clear();
+    self.visit_expr(self.grammar.expr_index_of_rule(rule));
+    self.calls.clone()
+  }
+}
+
+impl<'a> ExprByIndex for UnusedRule<'a>
+{
+  fn expr_by_index(&self, index: usize) -> Expression {
+    self.grammar.expr_by_index(index).clone()
+  }
+}
+
+impl<'a> Visitor<()> for UnusedRule<'a>
+{
+  unit_visitor_impl!(sequence);
+  unit_visitor_impl!(choice);
+
+  fn visit_non_terminal_symbol(&mut self, _this: usize, rule: &Ident) {
+    self.calls.push(rule.clone());
+  }
+}
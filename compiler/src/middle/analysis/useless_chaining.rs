@@ -73,7 +73,7 @@ impl <'a> UselessChaining<'a>
   }
 
   fn warn_useless_chaining(&self, span: Span, pattern_detected: &'static str, how_to_rewrite: &'static str) -> bool {
-    span.unstable().warning(format!(
+    span.warning(format!(
       "unnecessary chaining of predicates of the form `{}`\n\
        You can rewrite this expression to the equivalent one `{}`.\n\
        ({} ~~~> {})",
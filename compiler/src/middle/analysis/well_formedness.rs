@@ -12,6 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Computes the well-formedness attributes (`WFA`) of every expression: whether it can fail, can succeed, always consumes or never consumes input.
+//! Left recursions, infinite loops and rules that can never succeed are errors.
+//! Once the attributes reached a fixpoint, they are also used to lint the following expressions (the lint name is given in parenthesis, it can be silenced with `#[allow(..)]` on the rule):
+//!
+//!  * the alternatives of a choice after an always-succeeding alternative are dead (`dead_alternative`),
+//!  * `!e` never matches when `e` always succeeds (`never_matching_predicate`),
+//!  * `e?` is redundant when `e` cannot fail (`redundant_optional`).
+
 use middle::analysis::ast::*;
 use std::mem::swap;
 use std::collections::{HashMap, HashSet};
@@ -58,7 +66,9 @@ pub struct WellFormedness<'a>
   rules_wfa: HashMap<Ident, WFA>,
   reached_fixpoint: bool,
   well_formed: bool,
-  errors: HashSet<usize> // Whether we already spot an error on this rule (to avoid multi-reporting).
+  errors: HashSet<usize>, // Whether we already spot an error on this rule (to avoid multi-reporting).
+  lint: bool, // Lints are only reported once the fixpoint is reached.
+  warnings: HashSet<usize>
 }
 
 // Start with an empty set of the expression attributes.
@@ -76,6 +86,9 @@ impl<'a> WellFormedness<'a>
   fn is_well_formed(grammar: &'a AGrammar) -> bool {
     let mut analyser = WellFormedness::new(grammar);
     analyser.visit_rules();
+    if analyser.well_formed {
+      analyser.lint_rules();
+    }
     analyser.well_formed
   }
 
@@ -89,7 +102,9 @@ impl<'a> WellFormedness<'a>
         .collect(),
       reached_fixpoint: false,
       well_formed: true,
-      errors: HashSet::new()
+      errors: HashSet::new(),
+      lint: false,
+      warnings: HashSet::new()
     }
   }
 
@@ -103,6 +118,19 @@ impl<'a> WellFormedness<'a>
     }
   }
 
+  /// Visits the rules a last time with the attributes of the fixpoint to report the lints, and checks that every rule can succeed.
+  fn lint_rules(&mut self) {
+    self.lint = true;
+    for rule in self.grammar.rules.iter() {
+      self.visit_rule(&rule.ident());
+    }
+    for rule in self.grammar.rules.iter() {
+      if !self.rules_wfa[&rule.ident()].can_succeed {
+        self.error_rule_never_succeed(rule);
+      }
+    }
+  }
+
   fn visit_rule(&mut self, rule: &Ident) -> WFA {
     if self.is_rec(rule) {
       if !self.consume_input_since(rule) && !self.consumed_input {
@@ -175,7 +203,7 @@ impl<'a> WellFormedness<'a>
       rec_path.extend(self.rec_path_from(rule_id).into_iter()
         .map(|(r,_)| r)
         .rev());
-      rule.span().unstable().error(format!(
+      rule.span().error(format!(
         "Left-recursion is not supported in Oak; the following rule cycle \
         do not consume any input and would therefore loop forever\n\
         Detected cycle: {}\n\
@@ -187,19 +215,21 @@ impl<'a> WellFormedness<'a>
     }
   }
 
-  fn error_never_succeed(&mut self, expr_idx: usize) {
-    if self.register_error(expr_idx) {
+  fn error_rule_never_succeed(&mut self, rule: &Rule) {
+    if self.register_error(rule.expr_idx) {
       self.well_formed = false;
-      self.grammar[expr_idx].span().unstable().error(format!(
-        "Expression will never succeed.\n\
-        Solution: Remove this expression.")).emit();
+      rule.span().error(format!(
+        "rule `{}` can never succeed.", rule.ident()))
+      .span_note(self.grammar[rule.expr_idx].span(), format!(
+        "this expression fails on every input."))
+      .emit();
     }
   }
 
   fn error_always_succeed_without_consuming(&mut self, expr_idx: usize) {
     if self.register_error(expr_idx) {
       self.well_formed = false;
-      self.grammar[expr_idx].span().unstable().error(format!(
+      self.grammar[expr_idx].span().error(format!(
         "Expression will always succeed without consuming any input.\n\
         Solution: Remove this expression.")).emit();
     }
@@ -208,7 +238,7 @@ impl<'a> WellFormedness<'a>
   fn error_loop_repeat(&mut self, expr_idx: usize) {
     if self.register_error(expr_idx) {
       self.well_formed = false;
-      self.grammar[expr_idx].span().unstable().error(format!(
+      self.grammar[expr_idx].span().error(format!(
         "Infinite loop detected. A repeat operator (`e*` or `e+`) will \
         never stop because the sub-expression does not consume input.\n\
         Solution: Rewrite the expression such that it consumes at least \
@@ -216,17 +246,38 @@ impl<'a> WellFormedness<'a>
     }
   }
 
-  fn error_unreachable_branches(&mut self, choice: usize, always_succeed_branch: usize)
+  fn warn_dead_alternatives(&mut self, choice: usize, children: &[usize], always_succeed_branch: usize)
   {
-    if self.register_error(always_succeed_branch) {
-      self.well_formed = false;
-      self.grammar[choice].span().unstable().error(format!(
-        "Unreachable branch in a choice expression. We detected that \
-        some branches cannot be reached in this expression.\n\
-        Solution: Either remove (or rewrite) this branch or move it \
-        to the end of the choice expression."))
-      .span_note(self.grammar[always_succeed_branch].span().unstable(), format!(
-        "Branch always succeeding"))
+    if self.register_warning(choice, "dead_alternative") {
+      let dead = &children[always_succeed_branch+1..];
+      let plural = if dead.len() > 1 { "s are" } else { " is" };
+      let always_succeed = self.grammar.display_expr(children[always_succeed_branch]);
+      self.grammar[dead[0]].span().warning(format!(
+        "the following alternative{} never tried because `{}` always succeeds.",
+        plural, always_succeed))
+      .span_note(self.grammar[children[always_succeed_branch]].span(),
+        "this alternative cannot fail.")
+      .help("remove the dead alternatives or move the always-succeeding alternative to the end of the choice.")
+      .emit();
+    }
+  }
+
+  fn warn_never_matching_predicate(&mut self, this: usize, child: usize) {
+    if self.register_warning(this, "never_matching_predicate") {
+      self.grammar[this].span().warning(format!(
+        "`{}` never matches because `{}` always succeeds.",
+        self.grammar.display_expr(this), self.grammar.display_expr(child)))
+      .help("remove this expression or rewrite the predicate.")
+      .emit();
+    }
+  }
+
+  fn warn_redundant_optional(&mut self, this: usize, child: usize) {
+    if self.register_warning(this, "redundant_optional") {
+      let child_expr = self.grammar.display_expr(child);
+      self.grammar[this].span().warning(format!(
+        "the optional operator is redundant because `{}` cannot fail.", child_expr))
+      .help(format!("consider removing the `?`: `{}`.", child_expr))
       .emit();
     }
   }
@@ -234,6 +285,13 @@ impl<'a> WellFormedness<'a>
   fn register_error(&mut self, expr_idx: usize) -> bool {
     self.errors.insert(expr_idx)
   }
+
+  /// `true` if the lint must be reported on `expr_idx`: we are in the lint pass, the enclosing rule does not allow it and it was not reported yet.
+  fn register_warning(&mut self, expr_idx: usize, lint: &str) -> bool {
+    let allowed = self.recursion_path.last()
+      .map_or(false, |&(ref rule, _)| self.grammar.find_rule_by_ident(rule).is_allowed(lint));
+    self.lint && !allowed && self.warnings.insert(expr_idx)
+  }
 }
 
 impl<'a> ExprByIndex for WellFormedness<'a>
@@ -251,20 +309,16 @@ impl<'a> Visitor<WFA> for WellFormedness<'a>
       "Expression must either fails or succeeds.");
     assert!(!wfa.always_consume || !wfa.never_consume,
       "Expression cannot always and never consume at the same time.");
-    if wfa.can_fail && !wfa.can_succeed {
-      self.error_never_succeed(this);
-      wfa.can_succeed = true; // Error-recovery.
-    }
-    else if wfa.can_succeed && !wfa.can_fail && wfa.never_consume {
+    if wfa.can_succeed && !wfa.can_fail && wfa.never_consume {
       self.error_always_succeed_without_consuming(this);
       wfa.never_consume = false; // Error-recovery.
     }
     wfa
   }
 
-  fn visit_str_literal(&mut self, _this: usize, literal: String) -> WFA {
+  fn visit_str_literal(&mut self, _this: usize, literal: StrLiteralExpr) -> WFA {
     let mut wfa = WFA::default();
-    if literal.is_empty() {
+    if literal.value.is_empty() {
       wfa.can_fail = false;
       wfa.always_consume = false;
       // NOTE: the empty string is special, and it can be desirable that it always succeeds without consuming, for some patterns in combination with choice.
@@ -293,8 +347,16 @@ impl<'a> Visitor<WFA> for WellFormedness<'a>
     WFA::always_succeed(child_wfa.never_consume)
   }
 
-  fn visit_optional(&mut self, _this: usize, child: usize) -> WFA {
+  fn visit_optional(&mut self, this: usize, child: usize) -> WFA {
     let child_wfa = self.visit_expr(child);
+    // `e*?` and `e??` are reported by the useless chaining analysis.
+    let chained = match self.expr_by_index(child) {
+      Expression::ZeroOrMore(_) | Expression::ZeroOrOne(_) => true,
+      _ => false
+    };
+    if !child_wfa.can_fail && !child_wfa.never_consume && !chained {
+      self.warn_redundant_optional(this, child);
+    }
     WFA::always_succeed(child_wfa.never_consume)
   }
 
@@ -308,6 +370,9 @@ impl<'a> Visitor<WFA> for WellFormedness<'a>
 
   fn visit_not_predicate(&mut self, this: usize, child: usize) -> WFA {
     let mut wfa = self.visit_syntactic_predicate(this, child);
+    if !wfa.can_fail {
+      self.warn_never_matching_predicate(this, child);
+    }
     swap(&mut wfa.can_succeed, &mut wfa.can_fail);
     wfa
   }
@@ -329,7 +394,7 @@ impl<'a> Visitor<WFA> for WellFormedness<'a>
       wfa.always_consume &= child_wfa.always_consume;
       wfa.never_consume &= child_wfa.never_consume;
       if i != children.len() - 1 && !child_wfa.can_fail {
-        self.error_unreachable_branches(this, children[i]);
+        self.warn_dead_alternatives(this, &children, i);
         return wfa;
       }
     }
@@ -0,0 +1,357 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Rules and alternatives can be annotated with `#[cfg(..)]`.
+//! A procedural macro cannot evaluate these predicates, so the grammar has one configuration for each assignment of the atomic predicates (e.g. `feature = "x"`) appearing in the grammar, except the assignments no target can select.
+//! The code generated for a configuration is annotated with a `#[cfg(all(..))]` attribute enabling it only under this assignment, e.g. `#[cfg(all(feature = "x", not(unix)))]`.
+//! `oak!` goes further and puts this attribute on a nested invocation compiling the grammar in this configuration, therefore only the configuration selected by the compiler is analysed.
+//! In a configuration, the disabled rules and alternatives are removed from the grammar before the analysis, so the rest of the compilation ignores them.
+
+use front::ast::*;
+use visitor::*;
+
+use quote::quote;
+use syn::parse_quote;
+
+/// The number of configurations is exponential in the number of atomic predicates, so we limit it.
+const MAX_ATOMIC_PREDICATES: usize = 6;
+
+/// The configuration options which have a single value on a given target, contrarily to `feature` or `target_feature`.
+const SINGLE_VALUED_OPTIONS: [&str; 8] = ["target_arch", "target_os", "target_env", "target_abi",
+  "target_endian", "target_pointer_width", "target_vendor", "panic"];
+
+#[derive(Clone)]
+enum Predicate
+{
+  Atom(usize),
+  All(Vec<Predicate>),
+  Any(Vec<Predicate>),
+  Not(Box<Predicate>)
+}
+
+pub struct Configuration
+{
+  /// The atomic predicates of the grammar, such as `feature = "x"` or `unix`.
+  atoms: Vec<syn::Meta>,
+  /// The value of each atomic predicate in this configuration.
+  assignment: Vec<bool>
+}
+
+impl Configuration
+{
+  /// Builds all the configurations of the grammar, there is a single one if no `#[cfg(..)]` attribute is used.
+  /// The assignments that no target can select, such as `unix` and `windows` both enabled, are not configurations.
+  /// Returns `None` if an error was emitted.
+  pub fn all(grammar: &FGrammar) -> Option<Vec<Configuration>> {
+    let mut atoms = vec![];
+    let mut no_error = true;
+    for attr in Self::cfg_attributes(grammar) {
+      no_error &= Self::parse_cfg(attr, &mut atoms).is_some();
+    }
+    if no_error && atoms.len() > MAX_ATOMIC_PREDICATES {
+      grammar.start_span.error(format!(
+        "the grammar uses {} distinct `#[cfg(..)]` predicates, but at most {} are supported because the grammar is compiled once per configuration.",
+        atoms.len(), MAX_ATOMIC_PREDICATES))
+      .emit();
+      no_error = false;
+    }
+    if !no_error {
+      return None;
+    }
+    Some((0..(1usize << atoms.len()))
+      .map(|bits| (0..atoms.len()).map(|i| bits & (1 << i) != 0).collect())
+      .filter(|assignment: &Vec<bool>| Self::is_possible(&atoms, assignment))
+      .map(|assignment| Configuration { atoms: atoms.clone(), assignment })
+      .collect())
+  }
+
+  /// `false` if two atomic predicates enabled in `assignment` cannot hold on the same target.
+  fn is_possible(atoms: &[syn::Meta], assignment: &[bool]) -> bool {
+    let enabled: Vec<&syn::Meta> = atoms.iter().zip(assignment.iter())
+      .filter(|&(_, &enabled)| enabled)
+      .map(|(atom, _)| atom)
+      .collect();
+    enabled.iter().enumerate()
+      .all(|(i, a)| enabled[i+1..].iter().all(|b| !Self::are_exclusive(a, b)))
+  }
+
+  /// `unix` and `windows` are exclusive, and so are two values of a configuration option taking a single value, such as `target_os = "linux"` and `target_os = "macos"`.
+  /// The atomic predicates are distinct, so two predicates on the same option have different values.
+  fn are_exclusive(a: &syn::Meta, b: &syn::Meta) -> bool {
+    match (a, b) {
+      (&syn::Meta::Path(ref a), &syn::Meta::Path(ref b)) =>
+        (a.is_ident("unix") && b.is_ident("windows")) || (a.is_ident("windows") && b.is_ident("unix")),
+      (&syn::Meta::NameValue(ref a), &syn::Meta::NameValue(ref b)) =>
+        SINGLE_VALUED_OPTIONS.iter().any(|option| a.path.is_ident(option) && b.path.is_ident(option)),
+      _ => false
+    }
+  }
+
+  /// The value of each atomic predicate in this configuration, in the order of their first occurrence in the grammar.
+  pub fn assignment(&self) -> &[bool] {
+    &self.assignment
+  }
+
+  fn cfg_attributes<'a>(grammar: &'a FGrammar) -> Vec<&'a syn::Attribute> {
+    grammar.rules.iter()
+      .flat_map(|rule| rule.attributes.iter())
+      .chain(grammar.branch_attributes.values().flat_map(|attrs| attrs.iter()))
+      .filter(|attr| attr.path.is_ident("cfg"))
+      .collect()
+  }
+
+  /// Parses `#[cfg(p)]` and registers the atomic predicates of `p` in `atoms`.
+  fn parse_cfg(attr: &syn::Attribute, atoms: &mut Vec<syn::Meta>) -> Option<Predicate> {
+    match attr.parse_meta() {
+      Ok(syn::Meta::List(ref list)) if list.nested.len() == 1 => {
+        Self::parse_predicate(&list.nested[0], atoms)
+      }
+      _ => {
+        attr.span().error(
+          "expected a single predicate, for example `#[cfg(feature = \"x\")]`.")
+        .emit();
+        None
+      }
+    }
+  }
+
+  fn parse_predicate(pred: &syn::NestedMeta, atoms: &mut Vec<syn::Meta>) -> Option<Predicate> {
+    match pred {
+      &syn::NestedMeta::Meta(syn::Meta::List(ref list)) => {
+        let mut preds = vec![];
+        for nested in &list.nested {
+          preds.push(Self::parse_predicate(nested, atoms)?);
+        }
+        if list.path.is_ident("all") { Some(Predicate::All(preds)) }
+        else if list.path.is_ident("any") { Some(Predicate::Any(preds)) }
+        else if list.path.is_ident("not") && preds.len() == 1 {
+          Some(Predicate::Not(Box::new(preds.pop().unwrap())))
+        }
+        else {
+          list.span().error(
+            "expected `all(..)`, `any(..)` or `not(..)` with a single predicate.")
+          .emit();
+          None
+        }
+      }
+      &syn::NestedMeta::Meta(ref atom) => {
+        let text = quote!(#atom).to_string();
+        match atoms.iter().position(|a| quote!(#a).to_string() == text) {
+          Some(i) => Some(Predicate::Atom(i)),
+          None => {
+            atoms.push(atom.clone());
+            Some(Predicate::Atom(atoms.len() - 1))
+          }
+        }
+      }
+      &syn::NestedMeta::Lit(ref lit) => {
+        lit.span().error(
+          "a literal is not a valid `cfg` predicate.")
+        .emit();
+        None
+      }
+    }
+  }
+
+  fn eval(&self, pred: &Predicate) -> bool {
+    match pred {
+      &Predicate::Atom(i) => self.assignment[i],
+      &Predicate::All(ref preds) => preds.iter().all(|p| self.eval(p)),
+      &Predicate::Any(ref preds) => preds.iter().any(|p| self.eval(p)),
+      &Predicate::Not(ref pred) => !self.eval(pred)
+    }
+  }
+
+  /// `true` if all the `#[cfg(..)]` attributes in `attrs` hold in this configuration.
+  fn is_enabled(&self, attrs: &Vec<syn::Attribute>) -> bool {
+    let mut atoms = self.atoms.clone();
+    attrs.iter()
+      .filter(|attr| attr.path.is_ident("cfg"))
+      .filter_map(|attr| Self::parse_cfg(attr, &mut atoms))
+      .all(|pred| self.eval(&pred))
+  }
+
+  /// The attribute enabling the code generated for this configuration, `None` if the grammar has no `#[cfg(..)]` attribute.
+  pub fn cfg_attribute(&self) -> Option<syn::Attribute> {
+    if self.atoms.is_empty() {
+      None
+    }
+    else {
+      let preds: Vec<proc_macro2::TokenStream> = self.atoms.iter()
+        .zip(self.assignment.iter())
+        .map(|(atom, &enabled)|
+          if enabled { quote!(#atom) }
+          else { quote!(not(#atom)) })
+        .collect();
+      Some(parse_quote!(#[cfg(all(#(#preds),*))]))
+    }
+  }
+
+  /// Inserts the name of this configuration before the extension of `path`, e.g. `typing.unix.not_test.json` for `typing.json`, so each configuration writes its own file.
+  /// `path` is unchanged if the grammar has no `#[cfg(..)]` attribute.
+  pub fn file_name(&self, path: &str) -> String {
+    if self.atoms.is_empty() {
+      return path.to_string();
+    }
+    let name: Vec<String> = self.atoms.iter()
+      .zip(self.assignment.iter())
+      .map(|(atom, &enabled)| {
+        let atom: String = quote!(#atom).to_string().chars()
+          .filter(|c| !c.is_whitespace() && *c != '"')
+          .map(|c| if c.is_alphanumeric() { c } else { '_' })
+          .collect();
+        if enabled { atom } else { format!("not_{}", atom) }
+      })
+      .collect();
+    let path = std::path::Path::new(path);
+    let file_name = match (path.file_stem(), path.extension()) {
+      (Some(stem), Some(ext)) => format!("{}.{}.{}", stem.to_string_lossy(), name.join("."), ext.to_string_lossy()),
+      _ => format!("{}.{}", path.file_name().map_or(String::new(), |f| f.to_string_lossy().into_owned()), name.join("."))
+    };
+    path.with_file_name(file_name).to_string_lossy().into_owned()
+  }
+
+  fn display(&self) -> String {
+    let cfg = self.cfg_attribute();
+    quote!(#cfg).to_string()
+  }
+
+  /// Removes the rules and alternatives disabled in this configuration.
+  /// It is an error to call a disabled rule from an enabled one, or to disable all the alternatives of a choice.
+  pub fn configure(&self, mut grammar: FGrammar) -> Partial<FGrammar> {
+    if self.atoms.is_empty() {
+      return Partial::Value(grammar);
+    }
+    let (enabled, disabled): (Vec<Rule>, Vec<Rule>) = grammar.rules.drain(..)
+      .partition(|rule| self.is_enabled(&rule.attributes));
+    grammar.rules = enabled.into_iter()
+      .map(|mut rule| { rule.attributes.retain(|attr| !attr.path.is_ident("cfg")); rule })
+      .collect();
+    let mut no_error = self.remove_disabled_branches(&mut grammar);
+    if no_error {
+      let disabled = disabled.into_iter()
+        .filter(|rule| !grammar.rules.iter().any(|r| r.ident() == rule.ident()))
+        .collect();
+      let mut checker = DisabledRuleCall { grammar: &grammar, config: self, disabled, no_error: true };
+      for rule in &grammar.rules {
+        checker.visit_expr(rule.expr_idx);
+      }
+      no_error = checker.no_error;
+    }
+    grammar.branch_attributes.clear();
+    if no_error {
+      Self::remove_unreachable_exprs(&mut grammar);
+      Partial::Value(grammar)
+    }
+    else { Partial::Nothing }
+  }
+
+  /// The expressions of the disabled rules and alternatives are not reachable anymore, and the typing phase expects every expression to be reachable from a rule.
+  /// We compact the arena of expressions by keeping only the reachable ones.
+  fn remove_unreachable_exprs(grammar: &mut FGrammar) {
+    let mut new_index = vec![None; grammar.exprs.len()];
+    let mut worklist: Vec<usize> = grammar.rules.iter().map(|r| r.expr_idx).collect();
+    let mut reachable = vec![];
+    while let Some(idx) = worklist.pop() {
+      if new_index[idx].is_none() {
+        new_index[idx] = Some(0);
+        reachable.push(idx);
+        worklist.extend(grammar.exprs[idx].children());
+      }
+    }
+    reachable.sort();
+    for (new_idx, &idx) in reachable.iter().enumerate() {
+      new_index[idx] = Some(new_idx);
+    }
+    let reindex = |idx: usize| new_index[idx].expect("[bug] child of a reachable expression must be reachable.");
+    let exprs = reachable.iter().map(|&idx| map_children(grammar.exprs[idx].clone(), &reindex)).collect();
+    let exprs_info = reachable.iter().map(|&idx| grammar.exprs_info[idx].clone()).collect();
+    grammar.exprs = exprs;
+    grammar.exprs_info = exprs_info;
+    for rule in &mut grammar.rules {
+      rule.expr_idx = reindex(rule.expr_idx);
+    }
+  }
+
+  fn remove_disabled_branches(&self, grammar: &mut FGrammar) -> bool {
+    let mut no_error = true;
+    for idx in 0..grammar.exprs.len() {
+      if let Expression::Choice(ref mut branches) = grammar.exprs[idx] {
+        let attributes = &grammar.branch_attributes;
+        branches.retain(|branch| attributes.get(branch).map_or(true, |attrs| self.is_enabled(attrs)));
+        if branches.is_empty() {
+          grammar.exprs_info[idx].span.error(format!(
+            "all the alternatives of this choice are disabled in the configuration `{}`.", self.display()))
+          .emit();
+          no_error = false;
+        }
+      }
+    }
+    no_error
+  }
+}
+
+fn map_children<F>(expr: Expression, f: &F) -> Expression where
+  F: Fn(usize) -> usize
+{
+  use ast::Expression::*;
+  match expr {
+    Sequence(exprs) => Sequence(exprs.into_iter().map(f).collect()),
+    Choice(exprs) => Choice(exprs.into_iter().map(f).collect()),
+    ZeroOrMore(e) => ZeroOrMore(f(e)),
+    OneOrMore(e) => OneOrMore(f(e)),
+    ZeroOrOne(e) => ZeroOrOne(f(e)),
+    NotPredicate(e) => NotPredicate(f(e)),
+    AndPredicate(e) => AndPredicate(f(e)),
+    SemanticAction(e, boxed, action) => SemanticAction(f(e), boxed, action),
+    TypeAscription(e, ty) => TypeAscription(f(e), ty),
+    SpannedExpr(e) => SpannedExpr(f(e)),
+    RangeExpr(e) => RangeExpr(f(e)),
+    expr => expr
+  }
+}
+
+/// Reports the calls to rules which are disabled in the current configuration.
+struct DisabledRuleCall<'a>
+{
+  grammar: &'a FGrammar,
+  config: &'a Configuration,
+  disabled: Vec<Rule>,
+  no_error: bool
+}
+
+impl<'a> ExprByIndex for DisabledRuleCall<'a>
+{
+  fn expr_by_index(&self, index: usize) -> Expression {
+    self.grammar.exprs[index].clone()
+  }
+}
+
+impl<'a> Visitor<()> for DisabledRuleCall<'a>
+{
+  unit_visitor_impl!(sequence);
+  unit_visitor_impl!(choice);
+
+  fn visit_external_non_terminal_symbol(&mut self, this: usize, name: &syn::Path) {
+    if let Some(rule) = self.disabled.iter().find(|r| name.is_ident(&r.ident())) {
+      self.grammar.exprs_info[this].span.error(format!(
+        "the rule `{}` is disabled in the configuration `{}`, but it is called here.",
+        rule.ident(), self.config.display()))
+      .span_note(rule.span(), format!("rule `{}` declared here.", rule.ident()))
+      .emit();
+      self.no_error = false;
+    }
+  }
+}
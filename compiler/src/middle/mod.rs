@@ -18,26 +18,51 @@
 
 use middle::typing::ast::*;
 use middle::analysis::ast::AGrammar;
+use middle::configuration::Configuration;
 
 pub use front::ast::FGrammar;
 use partial::*;
 
+pub mod configuration;
 pub mod analysis;
 pub mod typing;
 
-pub fn typecheck(fgrammar: FGrammar) -> TGrammar {
+/// Returns `None` if an error was emitted.
+pub fn typecheck(fgrammar: FGrammar, config: &Configuration) -> Option<TGrammar> {
+  let grammar = analyse_configuration(fgrammar, config)
+    // We do not type a grammar with errors in the analysis phase.
+    .and_next(|grammar| extract_stream_type(grammar))
+    .and_then(|grammar| typing::type_inference(grammar));
+  into_option(grammar)
+}
+
+/// Analyses the grammar without typing it, returns `None` if an error was emitted.
+pub fn analyse(fgrammar: FGrammar, config: &Configuration) -> Option<AGrammar> {
+  into_option(analyse_configuration(fgrammar, config))
+}
+
+fn analyse_configuration(fgrammar: FGrammar, config: &Configuration) -> Partial<AGrammar> {
   Partial::Value(fgrammar)
+    .and_then(|grammar| config.configure(grammar))
     .and_then(|grammar| at_least_one_rule_declared(grammar))
     .and_then(|grammar| analysis::analyse(grammar))
-    .ensure("aborting due to previous error (analysis phase).")
-    .and_then(|grammar| extract_stream_type(grammar))
-    .and_then(|grammar| typing::type_inference(grammar))
-    .expect("aborting due to previous error (typing phase).")
+    .map(|mut grammar| {
+      grammar.attributes.export_typing = grammar.attributes.export_typing.take()
+        .map(|path| config.file_name(&path));
+      grammar
+    })
+}
+
+fn into_option<T>(partial: Partial<T>) -> Option<T> {
+  match partial {
+    Partial::Value(value) => Some(value),
+    _ => None
+  }
 }
 
 fn at_least_one_rule_declared(fgrammar: FGrammar) -> Partial<FGrammar> {
   if fgrammar.rules.len() == 0 {
-    fgrammar.start_span.unstable()
+    fgrammar.start_span
       .error("At least one rule must be declared.")
       .emit();
     Partial::Nothing
@@ -0,0 +1,199 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks the parameters of the semantic actions declared in the grammar against the inferred types of their arguments.
+//! In `e > f`, the function `f` receives one argument per value produced by `e`: none if `e` is unit, the fields if `e` is a tuple, and the value of `e` otherwise.
+//! Without this check, a mismatch is reported by rustc inside the generated code.
+//!
+//! Type aliases cannot be resolved at this stage, therefore a parameter is only rejected if both its type and the inferred type are built from types known to Oak (`char`, `Vec`, `Option`, tuples, primitive types,...).
+
+use middle::typing::ast::*;
+use middle::typing::ast::IType::*;
+use middle::typing::ast::Type::*;
+
+use syn::parse_quote;
+
+static KNOWN_TYPES: [&str; 21] = ["char", "Vec", "Option", "Box", "String", "bool", "str",
+  "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32", "f64"];
+
+pub struct ActionSignature<'a>
+{
+  grammar: &'a IGrammar,
+  error: bool
+}
+
+impl<'a> ActionSignature<'a>
+{
+  /// Returns `false` if the signature of a semantic action does not match its arguments.
+  pub fn check(grammar: &'a IGrammar) -> bool {
+    let mut checker = ActionSignature { grammar, error: false };
+    for idx in 0..grammar.span_ty_idx() {
+      if let Expression::SemanticAction(child, _, ref action) = grammar.exprs[idx] {
+        checker.check_action(idx, child, action);
+      }
+    }
+    !checker.error
+  }
+
+  fn check_action(&mut self, this: usize, child: usize, action: &syn::Expr) {
+    // The action is not called when the expression is under a unit type.
+    if self.grammar.type_of(this).is_unit_kind() {
+      return;
+    }
+    let fun = match action {
+      &syn::Expr::Path(ref expr_path) => expr_path.path.get_ident()
+        .and_then(|ident| self.grammar.rust_functions.get(ident)),
+      _ => None
+    };
+    if let Some(fun) = fun {
+      let params: Option<Vec<&syn::PatType>> = fun.sig.inputs.iter()
+        .map(|arg| match arg {
+          &syn::FnArg::Typed(ref param) => Some(param),
+          &syn::FnArg::Receiver(_) => None
+        })
+        .collect();
+      if let Some(params) = params {
+        let args = self.arguments(child);
+        if params.len() != args.len() {
+          self.error_arity(this, child, fun, &params, &args);
+        }
+        else {
+          for (param, arg) in params.iter().zip(args.iter()) {
+            if !compatible(&param.ty, &self.rust_type(*arg)) {
+              self.error_param_type(this, fun, &params, &args, param, *arg);
+            }
+          }
+        }
+      }
+    }
+  }
+
+  /// The expressions whose values are passed to the semantic action.
+  fn arguments(&self, child: usize) -> Vec<usize> {
+    match self.grammar.type_of(child) {
+      Invisible | Regular(Unit) => vec![],
+      Regular(Tuple(indexes)) => indexes,
+      _ => vec![child]
+    }
+  }
+
+  /// The Rust type of the expression `idx`, `_` if it is not known.
+  fn rust_type(&self, idx: usize) -> syn::Type {
+    match self.grammar.type_of(idx) {
+      Invisible | Regular(Unit) => parse_quote!(()),
+      Regular(Atom) => parse_quote!(char),
+      Regular(List(child)) => {
+        let ty = self.rust_type(child);
+        parse_quote!(Vec<#ty>)
+      }
+      Regular(Optional(child)) => {
+        let ty = self.rust_type(child);
+        parse_quote!(Option<#ty>)
+      }
+      Regular(Tuple(indexes)) => {
+        let tys: Vec<_> = indexes.into_iter().map(|idx| self.rust_type(idx)).collect();
+        parse_quote!((#(#tys),*))
+      }
+      Regular(Rust(ty)) => ty,
+      _ => parse_quote!(_)
+    }
+  }
+
+  fn expected_signature(&self, fun: &syn::ItemFn, params: &Vec<&syn::PatType>, args: &Vec<usize>) -> String {
+    let named = params.len() == args.len();
+    let params: Vec<String> = args.iter().enumerate()
+      .map(|(i, arg)| {
+        let name = if named { quote_string(&params[i].pat) } else { format!("arg{}", i) };
+        format!("{}: {}", name, self.grammar.type_of(*arg).display(self.grammar))
+      })
+      .collect();
+    format!("fn {}({}) {}", fun.sig.ident, params.join(", "), quote_string(&fun.sig.output))
+  }
+
+  fn error_arity(&mut self, this: usize, child: usize, fun: &syn::ItemFn,
+    params: &Vec<&syn::PatType>, args: &Vec<usize>)
+  {
+    self.error = true;
+    self.grammar[this].span().error(format!(
+      "the semantic action `{}` takes {} parameter{} but `{}` produces {} value{}.",
+      fun.sig.ident, params.len(), plural(params.len()),
+      self.grammar.display_expr(child), args.len(), plural(args.len())))
+    .note(format!("expected signature: `{}`", self.expected_signature(fun, params, args)))
+    .emit();
+  }
+
+  fn error_param_type(&mut self, this: usize, fun: &syn::ItemFn, params: &Vec<&syn::PatType>,
+    args: &Vec<usize>, param: &syn::PatType, arg: usize)
+  {
+    self.error = true;
+    self.grammar[this].span().error(format!(
+      "the parameter `{}` of the semantic action `{}` has type `{}` but `{}` has type `{}`.",
+      quote_string(&param.pat), fun.sig.ident, quote_string(&param.ty),
+      self.grammar.display_expr(arg), self.grammar.type_of(arg).display(self.grammar)))
+    .note(format!("expected signature: `{}`", self.expected_signature(fun, params, args)))
+    .emit();
+  }
+}
+
+/// `false` if a parameter of type `param` cannot receive a value of type `expected`.
+fn compatible(param: &syn::Type, expected: &syn::Type) -> bool {
+  match (param, expected) {
+    (_, &syn::Type::Infer(_)) => true,
+    (&syn::Type::Paren(ref param), _) => compatible(&param.elem, expected),
+    (&syn::Type::Tuple(ref param), &syn::Type::Tuple(ref expected)) =>
+      param.elems.len() == expected.elems.len() &&
+      param.elems.iter().zip(expected.elems.iter()).all(|(p, e)| compatible(p, e)),
+    (&syn::Type::Path(ref param), &syn::Type::Path(ref expected)) if param.qself.is_none() && expected.qself.is_none() => {
+      match (param.path.segments.last(), expected.path.segments.last()) {
+        (Some(p), Some(e)) if p.ident == e.ident => {
+          let (p_args, e_args) = (type_arguments(&p.arguments), type_arguments(&e.arguments));
+          p_args.len() != e_args.len() ||
+          p_args.into_iter().zip(e_args.into_iter()).all(|(p, e)| compatible(p, e))
+        }
+        _ => !(is_known_path(param) && is_known_path(expected))
+      }
+    }
+    _ => !(is_known(param) && is_known(expected))
+  }
+}
+
+fn type_arguments(arguments: &syn::PathArguments) -> Vec<&syn::Type> {
+  match arguments {
+    &syn::PathArguments::AngleBracketed(ref args) => args.args.iter()
+      .filter_map(|arg| match arg {
+        &syn::GenericArgument::Type(ref ty) => Some(ty),
+        _ => None
+      })
+      .collect(),
+    _ => vec![]
+  }
+}
+
+/// `true` if the type is built from a type known to Oak, and thus cannot be an alias of another type.
+fn is_known(ty: &syn::Type) -> bool {
+  match ty {
+    &syn::Type::Tuple(_) => true,
+    &syn::Type::Path(ref path) => is_known_path(path),
+    _ => false
+  }
+}
+
+fn is_known_path(path: &syn::TypePath) -> bool {
+  path.qself.is_none() && path.path.segments.last()
+    .map_or(false, |seg| KNOWN_TYPES.iter().any(|known| seg.ident == known))
+}
+
+fn plural(n: usize) -> &'static str {
+  if n == 1 { "" } else { "s" }
+}
@@ -51,7 +51,7 @@ impl IGrammar
   }
 
   fn error_unit_action_type(span: Span) {
-    span.unstable().error("a semantic action with type unit (`()`) will never be called as a semantic action is supposed to produce data.\n\
+    span.error("a semantic action with type unit (`()`) will never be called as a semantic action is supposed to produce data.\n\
       If this is intentional because the function has side-effects, return a custom type such as `MyUnit` with `type MyUnit = ()`.\n")
     .emit();
   }
@@ -0,0 +1,99 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! With `#![bytecode]`, the virtual machine stores the values of the Rust types as `oak_runtime::vm::Value::Rust(Box<dyn Any>)`, thus these types must be `'static`.
+//! A rule whose type borrows data, for instance `&'a str`, is rejected here instead of failing in the generated code.
+//!
+//! The span type `..` borrows the stream in its signature, but its value is converted by the machine and is not checked.
+
+use middle::typing::ast::*;
+use middle::typing::ast::Type::*;
+
+pub fn check_static_values(grammar: TGrammar) -> Partial<TGrammar> {
+  if !grammar.attributes.bytecode {
+    return Partial::Value(grammar);
+  }
+  let mut error = false;
+  for rule in &grammar.rules {
+    if let Some(ty) = borrowing_type(&grammar, rule.expr_idx) {
+      error = true;
+      rule.name.span().error(format!(
+        "the type of the rule `{}` borrows data in `{}`, the values of `#![bytecode]` are stored as `Box<dyn Any>` and must be `'static`.",
+        rule.ident(), quote_string(&ty)))
+      .help("consider an owned type, for instance `String` instead of `&str`.")
+      .emit();
+    }
+  }
+  if error { Partial::Nothing } else { Partial::Value(grammar) }
+}
+
+/// The first Rust type in the type of `expr_idx` which is not `'static`.
+fn borrowing_type(grammar: &TGrammar, expr_idx: usize) -> Option<syn::Type> {
+  match grammar[expr_idx].ty.clone() {
+    Unit | Atom => None,
+    Optional(child) | List(child) => borrowing_type(grammar, child),
+    Tuple(indexes) => indexes.into_iter().filter_map(|idx| borrowing_type(grammar, idx)).next(),
+    Rust(ty) =>
+      if ty != grammar.span_type() && borrows(&ty) { Some(ty) }
+      else { None }
+  }
+}
+
+/// `true` if `ty` contains a reference or a lifetime other than `'static`, the lifetimes elided in a path, such as `Cow<str>`, are not detected.
+fn borrows(ty: &syn::Type) -> bool {
+  match ty {
+    &syn::Type::Reference(ref reference) =>
+      !is_static(reference.lifetime.as_ref()) || borrows(&reference.elem),
+    &syn::Type::Path(ref path) =>
+      path.qself.as_ref().map_or(false, |qself| borrows(&qself.ty)) || path_borrows(&path.path),
+    &syn::Type::Tuple(ref tuple) => tuple.elems.iter().any(borrows),
+    &syn::Type::Array(ref array) => borrows(&array.elem),
+    &syn::Type::Slice(ref slice) => borrows(&slice.elem),
+    &syn::Type::Paren(ref paren) => borrows(&paren.elem),
+    &syn::Type::Group(ref group) => borrows(&group.elem),
+    &syn::Type::TraitObject(ref object) => bounds_borrow(object.bounds.iter()),
+    &syn::Type::ImplTrait(ref object) => bounds_borrow(object.bounds.iter()),
+    _ => false
+  }
+}
+
+fn path_borrows(path: &syn::Path) -> bool {
+  path.segments.iter().any(|segment| match &segment.arguments {
+    &syn::PathArguments::AngleBracketed(ref args) => args.args.iter().any(|arg| match arg {
+      &syn::GenericArgument::Lifetime(ref lifetime) => !is_static(Some(lifetime)),
+      &syn::GenericArgument::Type(ref ty) => borrows(ty),
+      &syn::GenericArgument::Binding(ref binding) => borrows(&binding.ty),
+      _ => false
+    }),
+    &syn::PathArguments::Parenthesized(ref args) => args.inputs.iter().any(borrows)
+      || match &args.output {
+        &syn::ReturnType::Type(_, ref ty) => borrows(ty),
+        &syn::ReturnType::Default => false
+      },
+    &syn::PathArguments::None => false
+  })
+}
+
+fn bounds_borrow<'a, I>(mut bounds: I) -> bool where
+ I: Iterator<Item=&'a syn::TypeParamBound>
+{
+  bounds.any(|bound| match bound {
+    &syn::TypeParamBound::Lifetime(ref lifetime) => !is_static(Some(lifetime)),
+    &syn::TypeParamBound::Trait(ref bound) => path_borrows(&bound.path)
+  })
+}
+
+fn is_static(lifetime: Option<&syn::Lifetime>) -> bool {
+  lifetime.map_or(false, |lifetime| lifetime.ident == "static")
+}
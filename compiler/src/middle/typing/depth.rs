@@ -18,11 +18,16 @@ use middle::typing::ast::Type::*;
 use middle::typing::ast::IType::*;
 use middle::typing::surface::*;
 use middle::typing::typing_printer::*;
+use middle::typing::typing_export::*;
+use middle::typing::explain_type::*;
+use middle::typing::action_signature::*;
 
 pub struct Depth
 {
   surface: Surface,
   exprs_info: Vec<ExprType>,
+  /// Recursive paths whose type is replaced by `(^)`.
+  rec_paths: Vec<RecPath>,
   under_unit: bool,
   under_ty_ascription: Option<IType>
 }
@@ -39,6 +44,7 @@ impl Depth
     if engine.surface.error { return Partial::Nothing }
     engine.reduce_all_invisible();
     engine.check_all_rules_have_type();
+    engine.surface.error |= !ActionSignature::check(&engine.surface.grammar);
     let grammar = engine.surface.grammar;
     if grammar.attributes.print_typing.debug() {
       println!("After applying Depth.");
@@ -48,6 +54,8 @@ impl Depth
       Partial::Nothing
     }
     else {
+      explain_types(&grammar, &engine.rec_paths);
+      export_typing(&grammar, &engine.rec_paths);
       Partial::Value(grammar.map_exprs_info(engine.exprs_info))
     }
   }
@@ -56,6 +64,7 @@ impl Depth
     Depth {
       surface: Surface::new(grammar),
       exprs_info: vec![],
+      rec_paths: vec![],
       under_unit: false,
       under_ty_ascription: None
     }
@@ -89,7 +98,7 @@ impl Depth
     for rule in self.surface.grammar.rules.clone() {
       if self.type_of(rule.expr_idx).contains_external(&self.surface.grammar) {
         self.surface.error = true;
-        rule.name.span().unstable()
+        rule.name.span()
           .error(format!("could not infer the type of this rule, please use type ascription, e.g. `r: Expr = e`."))
           .emit();
       }
@@ -114,9 +123,10 @@ impl Depth
       }
     }
     rec_set = rec_set.keep_only_polymorphic_paths();
+    self.rec_paths = rec_set.path_set.clone();
     if !rec_set.is_empty() {
       for rec_path in rec_set.path_set {
-        self.surface.grammar.find_rule_by_ident(&rec_path.path[0]).span().unstable()
+        self.surface.grammar.find_rule_by_ident(&rec_path.path[0]).span()
           .warning(format!("infinite recursive type automatically replaced by `(^)`: {}\n\
             Semantic actions along the path are ignored.", rec_path.display()))
           .emit();
@@ -134,7 +144,7 @@ impl Depth
   fn error_if_not_match_ty_ascription(&mut self, this: usize, ty: IType, aty: IType) {
     if !ty.syntactic_eq(&self.surface.grammar, &aty) {
       self.surface.error = true;
-      self.surface.grammar[this].span().unstable()
+      self.surface.grammar[this].span()
         .error(format!("found type {} but expected type {}",
           ty.display(&self.surface.grammar), aty.display(&self.surface.grammar)))
         .emit();
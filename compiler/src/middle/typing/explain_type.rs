@@ -0,0 +1,157 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Explains how the type of a rule annotated with `#[explain_type]` was inferred.
+//! A note is emitted on the rule with one line per sub-expression, giving its type and why it has this type, for example:
+//!
+//! ```text
+//! the type of the rule `list` is `(Vec<char>, Vec<Vec<char>>)`:
+//!   `"[" number ("," number)* "]"`: `(Vec<char>, Vec<Vec<char>>)`, tuple of `number`, `("," number)*`
+//!     `"["`: `(^)`, string literals are invisible
+//!     `number`: `Vec<char>`, type of the rule `number`
+//!     `("," number)*`: `Vec<Vec<char>>`, list of the type of `"," number`
+//!       `"," number`: `Vec<char>`, `number` is the only visible sub-expression, the tuple is flattened
+//!   ...
+//! ```
+//!
+//! The sub-expressions of invisible expressions and the rules called are not detailed.
+
+use middle::typing::ast::*;
+use middle::typing::ast::IType::*;
+use middle::typing::ast::Type::*;
+
+pub fn explain_types(grammar: &IGrammar, rec_paths: &Vec<RecPath>) {
+  let explanation = TypeExplanation::new(grammar, rec_paths);
+  for rule in grammar.rules.iter().filter(|rule| rule.explain_type()) {
+    let mut lines = vec![format!("the type of the rule `{}` is `{}`:",
+      rule.ident(), explanation.type_of(rule.expr_idx))];
+    explanation.explain_expr(rule.expr_idx, 1, &mut lines);
+    rule.span().note(lines.join("\n")).emit();
+  }
+}
+
+pub struct TypeExplanation<'a>
+{
+  grammar: &'a IGrammar,
+  rec_paths: &'a Vec<RecPath>,
+  /// The parent of each expression, `None` for the expressions of the rules.
+  parents: Vec<Option<usize>>
+}
+
+impl<'a> TypeExplanation<'a>
+{
+  pub fn new(grammar: &'a IGrammar, rec_paths: &'a Vec<RecPath>) -> Self {
+    let mut parents = vec![None; grammar.exprs.len()];
+    for idx in 0..grammar.span_ty_idx() {
+      for child in grammar.exprs[idx].children() {
+        parents[child] = Some(idx);
+      }
+    }
+    TypeExplanation { grammar, rec_paths, parents }
+  }
+
+  pub fn type_of(&self, idx: usize) -> String {
+    self.grammar.type_of(idx).display(self.grammar)
+  }
+
+  fn is_invisible(&self, idx: usize) -> bool {
+    self.grammar.type_of(idx).is_unit_kind()
+  }
+
+  /// Explains why `idx` has the type `(^)` or `()`, `None` if it has another type.
+  pub fn invisible_reason(&self, idx: usize) -> Option<String> {
+    if !self.is_invisible(idx) {
+      return None;
+    }
+    if let Some(rec_path) = self.replaced_rec_path(idx) {
+      return Some(format!("infinite recursive type replaced by `(^)`: {}", rec_path.display()));
+    }
+    let reason = match &self.grammar.exprs[idx] {
+      &Expression::StrLiteral(_) => format!("string literals are invisible"),
+      &Expression::NotPredicate(_) | &Expression::AndPredicate(_) => format!("syntactic predicates are invisible"),
+      &Expression::TypeAscription(_, ref ty) => format!("annotated with `{}`", ty.display(self.grammar)),
+      &Expression::NonTerminalSymbol(ref rule) => format!("calls the rule `{}` of type `{}`", rule, self.type_of(idx)),
+      _ => match self.parents[idx] {
+        Some(parent) if self.is_invisible(parent) =>
+          format!("sub-expression of the invisible expression `{}`", self.grammar.display_expr(parent)),
+        _ => format!("all its sub-expressions are invisible")
+      }
+    };
+    Some(reason)
+  }
+
+  /// The recursive path forcing the type of the rule of `idx` to `(^)`, if `idx` is the expression of such a rule.
+  fn replaced_rec_path(&self, idx: usize) -> Option<&RecPath> {
+    let rule = self.grammar.rules.iter().find(|rule| rule.expr_idx == idx)?;
+    self.rec_paths.iter().find(|rec_path| rec_path.path.iter().any(|r| *r == rule.ident()))
+  }
+
+  /// Explains why a visible expression has its type, `None` if there is nothing more to say than its type.
+  fn visible_reason(&self, idx: usize) -> Option<String> {
+    let ty = self.grammar.type_of(idx);
+    match &self.grammar.exprs[idx] {
+      &Expression::AnySingleChar | &Expression::CharacterClass(_) => Some(format!("matches a single character")),
+      &Expression::NonTerminalSymbol(ref rule) => Some(format!("type of the rule `{}`", rule)),
+      &Expression::ExternalNonTerminalSymbol(_) => Some(format!("type of the external parser")),
+      &Expression::ZeroOrMore(child) | &Expression::OneOrMore(child) =>
+        Some(format!("list of the type of `{}`", self.grammar.display_expr(child))),
+      &Expression::ZeroOrOne(child) =>
+        Some(format!("option of the type of `{}`", self.grammar.display_expr(child))),
+      &Expression::Choice(_) => Some(format!("type shared by all the branches")),
+      &Expression::SemanticAction(_, _, _) => Some(format!("returned by the semantic action")),
+      &Expression::TypeAscription(_, _) => Some(format!("annotated with this type")),
+      &Expression::Sequence(ref children) => Some(self.sequence_reason(children, ty)),
+      _ => None
+    }
+  }
+
+  /// Explains which sub-expressions of a sequence contribute to its type and which tuples are flattened.
+  fn sequence_reason(&self, children: &Vec<usize>, ty: IType) -> String {
+    let visible: Vec<usize> = children.iter().cloned()
+      .filter(|child| !self.is_invisible(*child))
+      .collect();
+    match ty {
+      Regular(Tuple(ref indexes)) => {
+        let fields: Vec<String> = indexes.iter()
+          .map(|idx| format!("`{}`", self.grammar.display_expr(*idx)))
+          .collect();
+        let mut reason = format!("tuple of {}", fields.join(", "));
+        for child in visible {
+          if let Regular(Tuple(_)) = self.grammar.type_of(child) {
+            reason.push_str(&format!(", the tuple of `{}` is flattened into it", self.grammar.display_expr(child)));
+          }
+        }
+        reason
+      }
+      _ if visible.len() == 1 =>
+        format!("`{}` is the only visible sub-expression, the tuple is flattened", self.grammar.display_expr(visible[0])),
+      _ => format!("tuple of its visible sub-expressions")
+    }
+  }
+
+  fn explain_expr(&self, idx: usize, depth: usize, lines: &mut Vec<String>) {
+    let reason = self.invisible_reason(idx).or_else(|| self.visible_reason(idx));
+    lines.push(format!("{}`{}`: `{}`{}", "  ".repeat(depth), self.grammar.display_expr(idx), self.type_of(idx),
+      reason.map_or(String::new(), |reason| format!(", {}", reason))));
+    match &self.grammar.exprs[idx] {
+      &Expression::NonTerminalSymbol(_) => (),
+      _ if self.is_invisible(idx) => (),
+      expr => {
+        for child in expr.children() {
+          self.explain_expr(child, depth + 1, lines);
+        }
+      }
+    }
+  }
+}
@@ -15,14 +15,20 @@
 use middle::analysis::ast::AGrammar;
 use middle::typing::ast::*;
 use middle::typing::depth::*;
+use middle::typing::bytecode_value::*;
 
 pub mod ast;
 mod surface;
 mod depth;
 mod type_rewriting;
 mod typing_printer;
+mod typing_export;
+mod explain_type;
+mod action_signature;
+mod bytecode_value;
 
 pub fn type_inference(agrammar: AGrammar) -> Partial<TGrammar> {
   let grammar = IGrammar::from(agrammar);
   Depth::infer(grammar)
+    .and_then(|grammar| check_static_values(grammar))
 }
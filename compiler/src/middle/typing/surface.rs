@@ -94,15 +94,15 @@ impl Surface
 
   fn type_mismatch_branches(&mut self, rec_set: RecSet, sum_expr: usize, branches: Vec<usize>, tys: Vec<IType>) {
     self.error = true;
-    let mut diagnostic = self.grammar[sum_expr].span().unstable().error(
+    let mut diagnostic = self.grammar[sum_expr].span().error(
       format!("Type mismatch between branches of the choice operator."));
     for i in 0..branches.len() {
-      diagnostic = diagnostic.span_note(self.grammar[branches[i]].span().unstable(),
+      diagnostic = diagnostic.span_note(self.grammar[branches[i]].span(),
         format!("branch {} of type `{}`", i+1, tys[i].display(&self.grammar)));
     }
     if !rec_set.is_empty() {
       let entry_point = self.grammar.find_rule_by_ident(&rec_set.entry_point());
-      diagnostic = diagnostic.span_note(entry_point.span().unstable(),
+      diagnostic = diagnostic.span_note(entry_point.span(),
         format!("Types annotated with `*` have been reduced to (^) because they are involved \
           in one of the following rule cycle (generating recursive types):\n{}",
           rec_set.display()));
@@ -134,7 +134,7 @@ impl Visitor<IType> for Surface
 
   // Axioms
 
-  fn visit_str_literal(&mut self, _this: usize, _lit: String) -> IType {
+  fn visit_str_literal(&mut self, _this: usize, _lit: StrLiteralExpr) -> IType {
     IType::Invisible
   }
 
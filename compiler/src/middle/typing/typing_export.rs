@@ -0,0 +1,119 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Writes the result of the type inference in a JSON document when the grammar is annotated with `#![export_typing(path = "...")]`, the path being relative to the directory of the crate manifest.
+//! When the grammar has several configurations, the path contains the name of the configuration being typed (see `Configuration::file_name`).
+//! The document has the following form:
+//!
+//! ```text
+//! {
+//!   "rules": [{"name": "sum", "expr": 3, "type": "Vec<char>"}, ...],
+//!   "exprs": [{"index": 3, "expr": "...", "span": {...}, "type": "(^)", "invisible": "string literals are invisible"}, ...],
+//!   "recursive_types": [["expr", "term", "expr"], ...]
+//! }
+//! ```
+//!
+//! `invisible` explains why an expression has the type `(^)` or `()`, it is `null` otherwise.
+//! `recursive_types` lists the recursive paths of rules whose type was replaced by `(^)`.
+
+use middle::typing::ast::*;
+use middle::typing::explain_type::*;
+
+use std::fs;
+use std::path::PathBuf;
+
+pub fn export_typing(grammar: &IGrammar, rec_paths: &Vec<RecPath>) {
+  if let Some(ref path) = grammar.attributes.export_typing {
+    let exporter = TypingExport::new(grammar, rec_paths);
+    let path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join(path);
+    if let Err(err) = fs::write(&path, exporter.json()) {
+      grammar.start_span.warning(format!(
+        "could not export the typing in `{}`: {}.", path.display(), err))
+      .emit();
+    }
+  }
+}
+
+struct TypingExport<'a>
+{
+  grammar: &'a IGrammar,
+  rec_paths: &'a Vec<RecPath>,
+  explanation: TypeExplanation<'a>
+}
+
+impl<'a> TypingExport<'a>
+{
+  fn new(grammar: &'a IGrammar, rec_paths: &'a Vec<RecPath>) -> Self {
+    TypingExport { grammar, rec_paths, explanation: TypeExplanation::new(grammar, rec_paths) }
+  }
+
+  fn json(&self) -> String {
+    let rules: Vec<String> = self.grammar.rules.iter()
+      .map(|rule| format!("    {{\"name\": {}, \"expr\": {}, \"type\": {}}}",
+        json_str(&rule.ident().to_string()), rule.expr_idx, json_str(&self.type_of(rule.expr_idx))))
+      .collect();
+    // The last expression is the span type allocated during typing, it is not part of the grammar.
+    let exprs: Vec<String> = (0..self.grammar.span_ty_idx())
+      .map(|idx| format!("    {{\"index\": {}, \"expr\": {}, \"span\": {}, \"type\": {}, \"invisible\": {}}}",
+        idx, json_str(&self.grammar.display_expr(idx)), self.span(idx), json_str(&self.type_of(idx)),
+        self.invisible_reason(idx).map_or(format!("null"), |reason| json_str(&reason))))
+      .collect();
+    let rec_paths: Vec<String> = self.rec_paths.iter()
+      .map(|rec_path| {
+        let mut path: Vec<String> = rec_path.path.iter().map(|r| json_str(&r.to_string())).collect();
+        path.push(path[0].clone());
+        format!("    [{}]", path.join(", "))
+      })
+      .collect();
+    format!("{{\n  \"rules\": {},\n  \"exprs\": {},\n  \"recursive_types\": {}\n}}\n",
+      json_array(rules), json_array(exprs), json_array(rec_paths))
+  }
+
+  fn type_of(&self, idx: usize) -> String {
+    self.explanation.type_of(idx)
+  }
+
+  fn span(&self, idx: usize) -> String {
+    let span = self.grammar[idx].span();
+    let (start, end) = (span.start(), span.end());
+    format!("{{\"file\": {}, \"start\": {{\"line\": {}, \"column\": {}}}, \"end\": {{\"line\": {}, \"column\": {}}}}}",
+      json_str(&span.file()), start.line, start.column + 1, end.line, end.column + 1)
+  }
+
+  fn invisible_reason(&self, idx: usize) -> Option<String> {
+    self.explanation.invisible_reason(idx)
+  }
+}
+
+fn json_array(items: Vec<String>) -> String {
+  if items.is_empty() { format!("[]") }
+  else { format!("[\n{}\n  ]", items.join(",\n")) }
+}
+
+fn json_str(text: &str) -> String {
+  let mut json = String::from("\"");
+  for c in text.chars() {
+    match c {
+      '"' => json.push_str("\\\""),
+      '\\' => json.push_str("\\\\"),
+      '\n' => json.push_str("\\n"),
+      '\r' => json.push_str("\\r"),
+      '\t' => json.push_str("\\t"),
+      c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+      c => json.push(c)
+    }
+  }
+  json.push('"');
+  json
+}
@@ -24,7 +24,7 @@ pub trait Visitor<R: Default> : ExprByIndex
     walk_expr(self, this)
   }
 
-  fn visit_str_literal(&mut self, _this: usize, _lit: String) -> R { R::default() }
+  fn visit_str_literal(&mut self, _this: usize, _lit: StrLiteralExpr) -> R { R::default() }
   fn visit_non_terminal_symbol(&mut self, _this: usize, _rule: &Ident) -> R { R::default() }
   fn visit_external_non_terminal_symbol(&mut self, _this: usize, _rule: &syn::Path) -> R { R::default() }
   fn visit_atom(&mut self, _this: usize) -> R { R::default() }
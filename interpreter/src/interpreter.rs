@@ -0,0 +1,158 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Walks the expressions of the grammar against the input.
+//! The position and the expected items are kept in a `ParseState` as in the compiled parsers, so the errors are the same.
+//! An expression that fails restores the position and the trees built before it was tried.
+
+use {Grammar, Expr};
+
+use oak_runtime::{ParseStateWith, StrStream, Stream, ExpectationTable, ConsumePrefix, ConsumePrefixIgnoreCase};
+
+use std::mem;
+use std::ops::Range;
+
+/// The node of a rule matched in the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tree
+{
+  pub rule: String,
+  /// The bytes of the input matched by the rule.
+  pub span: Range<usize>,
+  /// The trees of the rules matched by this rule, in the order of the input.
+  pub children: Vec<Tree>
+}
+
+impl Tree
+{
+  /// The text matched by the rule in `input`.
+  pub fn text<'a>(&self, input: &'a str) -> &'a str {
+    &input[self.span.clone()]
+  }
+}
+
+pub struct Interpreter<'g, 'a>
+{
+  grammar: &'g Grammar,
+  expectations: &'g ExpectationTable<'g>,
+  state: ParseStateWith<'g, StrStream<'a>, ()>,
+  /// The trees of the rules matched so far in the rule being parsed.
+  trees: Vec<Tree>
+}
+
+impl<'g, 'a> Interpreter<'g, 'a>
+{
+  pub fn parse(grammar: &'g Grammar, expectations: &'g ExpectationTable<'g>, rule: usize, input: &'a str)
+    -> ParseStateWith<'g, StrStream<'a>, Tree>
+  {
+    let mut interpreter = Interpreter { grammar, expectations, state: ParseStateWith::new(input.stream()), trees: vec![] };
+    let success = interpreter.rule(rule);
+    let mut state = interpreter.state;
+    match interpreter.trees.pop() {
+      Some(tree) if success => {
+        state.failed = false;
+        state.success(tree)
+      }
+      _ => {
+        state.failed = true;
+        state.failure()
+      }
+    }
+  }
+
+  fn offset(&self) -> usize {
+    self.state.current.bytes_offset()
+  }
+
+  fn error(&mut self, expect: usize) -> bool {
+    self.state.error(self.expectations, expect);
+    false
+  }
+
+  fn rule(&mut self, rule: usize) -> bool {
+    let start = self.offset();
+    let outer = mem::replace(&mut self.trees, vec![]);
+    let success = self.expr(self.grammar.rules[rule].expr_idx);
+    let children = mem::replace(&mut self.trees, outer);
+    if success {
+      self.trees.push(Tree {
+        rule: self.grammar.rules[rule].name.clone(),
+        span: start..self.offset(),
+        children
+      });
+    }
+    success
+  }
+
+  /// Returns `true` if the expression matches, otherwise the state is restored as before the call.
+  fn expr(&mut self, idx: usize) -> bool {
+    let mark = self.state.current.clone();
+    let trees = self.trees.len();
+    let success = self.match_expr(idx);
+    if !success {
+      self.state.current = mark;
+      self.trees.truncate(trees);
+    }
+    success
+  }
+
+  fn match_expr(&mut self, idx: usize) -> bool {
+    match &self.grammar.exprs[idx] {
+      &Expr::StrLiteral(ref lit, ignore_case, expect) => {
+        let consumed =
+          if ignore_case { self.state.consume_prefix_ignore_case(lit.as_str()) }
+          else { self.state.consume_prefix(lit.as_str()) };
+        consumed || self.error(expect)
+      }
+      &Expr::AnySingleChar(expect) => self.state.current.next().is_some() || self.error(expect),
+      &Expr::CharacterClass(ref class, expect) => {
+        match self.state.current.current_char() {
+          Some(c) if class.contains(c) => {
+            self.state.current.next();
+            true
+          }
+          _ => self.error(expect)
+        }
+      }
+      &Expr::NonTerminalSymbol(rule) => self.rule(rule),
+      &Expr::Sequence(ref children) => children.iter().all(|&child| self.expr(child)),
+      &Expr::Choice(ref children) => children.iter().any(|&child| self.expr(child)),
+      &Expr::ZeroOrMore(child) => {
+        while self.expr(child) {}
+        true
+      }
+      &Expr::OneOrMore(child) => {
+        let success = self.expr(child);
+        while success && self.expr(child) {}
+        success
+      }
+      &Expr::ZeroOrOne(child) => {
+        self.expr(child);
+        true
+      }
+      &Expr::NotPredicate(child) => !self.predicate(child),
+      &Expr::AndPredicate(child) => self.predicate(child)
+    }
+  }
+
+  /// Matches `child` without consuming the input nor building trees.
+  fn predicate(&mut self, child: usize) -> bool {
+    let mark = self.state.current.clone();
+    let trees = self.trees.len();
+    let success = self.expr(child);
+    self.state.current = mark;
+    self.trees.truncate(trees);
+    success
+  }
+}
@@ -0,0 +1,274 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interprets the grammars only known at run time, for example the grammars of small languages defined by the users of an application.
+//! The grammar is written as inside `oak! { ... }`, it goes through the same parsing and analysis as a compiled grammar, but it is not typed: the semantic actions are ignored and parsing builds an untyped `Tree` with one node per rule matched.
+//!
+//! ```
+//! # use oak_interpreter::Grammar;
+//! let grammar = Grammar::new(r#"
+//!   sum = number ("+" number)*
+//!   number = ["0-9"]+
+//! "#).unwrap();
+//! let tree = grammar.parse("sum", "1+2").unwrap();
+//! assert_eq!(tree.children.len(), 2);
+//! ```
+//!
+//! The parse errors are the same as those of the compiled parsers, for example ```1:3: unexpected `x`, expecting `["0-9"]`.```
+
+extern crate oak_compiler;
+extern crate oak_runtime;
+
+mod interpreter;
+
+pub use interpreter::Tree;
+pub use oak_runtime::ParseError;
+
+use oak_compiler::ast::{self, Expression, ItemIdent, Spanned};
+use oak_compiler::back::expectation::{str_literal_item, char_class_item, any_single_char_item};
+use oak_compiler::diagnostic::{self, SpanDiagnostic, RenderedDiagnostics};
+use oak_compiler::middle;
+use oak_compiler::middle::analysis::ast::AGrammar;
+use oak_compiler::middle::configuration::Configuration;
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+
+/// A grammar checked and ready to parse inputs.
+pub struct Grammar
+{
+  rules: Vec<Rule>,
+  rules_by_name: HashMap<String, usize>,
+  /// The expressions indexed as in the analysed grammar.
+  exprs: Vec<Expr>,
+  /// The expectation table of the parser, borrowed by the parse states of `interpreter::Interpreter`.
+  expectations: Vec<String>,
+  warnings: Vec<String>
+}
+
+struct Rule
+{
+  name: String,
+  expr_idx: usize
+}
+
+/// The parsing expressions without the constructions only meaningful to the typing, such as semantic actions.
+enum Expr
+{
+  /// The literal, `true` if it ignores the case, and the index of the literal in the expectation table.
+  StrLiteral(String, bool, usize),
+  AnySingleChar(usize),
+  CharacterClass(CharacterClass, usize),
+  NonTerminalSymbol(usize),
+  Sequence(Vec<usize>),
+  Choice(Vec<usize>),
+  ZeroOrMore(usize),
+  OneOrMore(usize),
+  ZeroOrOne(usize),
+  NotPredicate(usize),
+  AndPredicate(usize)
+}
+
+struct CharacterClass
+{
+  intervals: Vec<(char, char)>,
+  sets: Vec<&'static [(char, char)]>,
+  negated: bool
+}
+
+impl CharacterClass
+{
+  fn contains(&self, c: char) -> bool {
+    let contained = self.intervals.iter().any(|&(lo, hi)| lo <= c && c <= hi)
+      || self.sets.iter().any(|set| oak_runtime::unicode::contains(set, c));
+    contained != self.negated
+  }
+}
+
+impl Grammar
+{
+  /// Parses and analyses the grammar `source`, the errors are reported as the compiler does for `oak!`.
+  pub fn new(source: &str) -> Result<Grammar, GrammarError> {
+    // The diagnostics pending on this thread, for instance after a grammar compiled by a panicking thread, are not reported with those of this grammar.
+    let (grammar, diagnostics) = diagnostic::collect_diagnostics(|| Self::analyse(source));
+    let diagnostics = RenderedDiagnostics::new(diagnostics, "<grammar>");
+    match grammar {
+      Some(grammar) if diagnostics.errors.is_empty() => Ok(Grammar::from(grammar, diagnostics.warnings)),
+      _ => Err(GrammarError { diagnostics: diagnostics.errors })
+    }
+  }
+
+  fn analyse(source: &str) -> Option<AGrammar> {
+    let ast = oak_compiler::parse(source)?;
+    let configs = Configuration::all(&ast)?;
+    if configs.len() > 1 {
+      ast.start_span.error("`#[cfg(..)]` attributes cannot be evaluated in a grammar loaded at run time.").emit();
+      return None;
+    }
+    let grammar = middle::analyse(ast, configs.first()?)?;
+    let mut external = false;
+    for (idx, expr) in grammar.exprs.iter().enumerate() {
+      if let &Expression::ExternalNonTerminalSymbol(ref path) = expr {
+        external = true;
+        grammar[idx].span().error(format!(
+          "`{}` is not a rule of the grammar, the external parsers cannot be called in a grammar loaded at run time.",
+          ast::quote_string(path)))
+        .emit();
+      }
+    }
+    if external { None } else { Some(grammar) }
+  }
+
+  fn from(grammar: AGrammar, warnings: Vec<String>) -> Grammar {
+    let rules: Vec<Rule> = grammar.rules.iter()
+      .map(|rule| Rule { name: rule.ident().to_string(), expr_idx: rule.expr_idx })
+      .collect();
+    let rules_by_name = rules.iter().enumerate()
+      .map(|(i, rule)| (rule.name.clone(), i))
+      .collect();
+    let mut expectations = vec![];
+    let exprs = (0..grammar.exprs.len())
+      .map(|idx| Self::convert_expr(&grammar, &rules_by_name, &mut expectations, idx))
+      .collect();
+    Grammar { rules, rules_by_name, exprs, expectations, warnings }
+  }
+
+  fn convert_expr(grammar: &AGrammar, rules_by_name: &HashMap<String, usize>,
+    expectations: &mut Vec<String>, idx: usize) -> Expr
+  {
+    let target = |idx| Self::skip_typing_exprs(grammar, idx);
+    let mut expect = |item: String| {
+      match expectations.iter().position(|i| *i == item) {
+        Some(pos) => pos,
+        None => { expectations.push(item); expectations.len() - 1 }
+      }
+    };
+    match &grammar.exprs[idx] {
+      &Expression::StrLiteral(ref lit) =>
+        Expr::StrLiteral(lit.value.clone(), lit.ignore_case, expect(str_literal_item(lit))),
+      &Expression::AnySingleChar => Expr::AnySingleChar(expect(any_single_char_item())),
+      &Expression::CharacterClass(ref classes) => {
+        let class = CharacterClass {
+          intervals: classes.intervals.iter().map(|interval| (interval.lo, interval.hi)).collect(),
+          sets: classes.sets.iter()
+            .map(|set| oak_runtime::unicode::set(set).expect("Unicode sets are checked by the parser."))
+            .collect(),
+          negated: classes.negated
+        };
+        Expr::CharacterClass(class, expect(char_class_item(classes)))
+      }
+      &Expression::NonTerminalSymbol(ref id) => Expr::NonTerminalSymbol(rules_by_name[&id.to_string()]),
+      &Expression::Sequence(ref children) => Expr::Sequence(children.iter().map(|&c| target(c)).collect()),
+      &Expression::Choice(ref children) => Expr::Choice(children.iter().map(|&c| target(c)).collect()),
+      &Expression::ZeroOrMore(child) => Expr::ZeroOrMore(target(child)),
+      &Expression::OneOrMore(child) => Expr::OneOrMore(target(child)),
+      &Expression::ZeroOrOne(child) => Expr::ZeroOrOne(target(child)),
+      &Expression::NotPredicate(child) => Expr::NotPredicate(target(child)),
+      &Expression::AndPredicate(child) => Expr::AndPredicate(target(child)),
+      &Expression::ExternalNonTerminalSymbol(_) => unreachable!("External parsers are rejected when the grammar is loaded."),
+      // Only reachable from the rules, the other expressions skip it.
+      expr => Expr::Sequence(expr.children())
+    }
+  }
+
+  /// The semantic actions, type ascriptions and spans do not change the tree built, so we skip them.
+  fn skip_typing_exprs(grammar: &AGrammar, idx: usize) -> usize {
+    match &grammar.exprs[idx] {
+      &Expression::SemanticAction(child, _, _) | &Expression::TypeAscription(child, _)
+    | &Expression::SpannedExpr(child) | &Expression::RangeExpr(child) => Self::skip_typing_exprs(grammar, child),
+      _ => idx
+    }
+  }
+
+  /// The names of the rules, in their order of declaration.
+  pub fn rules(&self) -> Vec<&str> {
+    self.rules.iter().map(|rule| rule.name.as_str()).collect()
+  }
+
+  /// The warnings of the analysis, such as the unused rules.
+  pub fn warnings(&self) -> &[String] {
+    &self.warnings
+  }
+
+  /// Parses the full input with the rule `rule`.
+  pub fn parse(&self, rule: &str, input: &str) -> Result<Tree, ParseFailure> {
+    let rule = self.rule_index(rule)?;
+    let expectations = self.expectation_table();
+    let result = interpreter::Interpreter::parse(self, &expectations, rule, input).into_full_result();
+    Ok(result?)
+  }
+
+  /// Parses a prefix of the input with the rule `rule`, and returns the number of bytes read.
+  pub fn parse_prefix(&self, rule: &str, input: &str) -> Result<(Tree, usize), ParseFailure> {
+    let rule = self.rule_index(rule)?;
+    let expectations = self.expectation_table();
+    let result = interpreter::Interpreter::parse(self, &expectations, rule, input).into_prefix_result();
+    Ok(result.map(|(tree, rest)| (tree, rest.bytes_offset()))?)
+  }
+
+  fn rule_index(&self, rule: &str) -> Result<usize, ParseFailure> {
+    self.rules_by_name.get(rule).cloned()
+      .ok_or_else(|| ParseFailure::UnknownRule(rule.to_string()))
+  }
+
+  /// The expectation table of the runtime only holds the names of the items, they are borrowed from the grammar during a parse.
+  fn expectation_table(&self) -> Vec<&str> {
+    self.expectations.iter().map(String::as_str).collect()
+  }
+}
+
+/// The errors of `Grammar::parse` and `Grammar::parse_prefix`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseFailure
+{
+  /// The rule called is not a rule of the grammar.
+  UnknownRule(String),
+  /// The input does not match the rule, the error is the same as the one of the compiled parsers.
+  Parse(ParseError)
+}
+
+impl From<ParseError> for ParseFailure
+{
+  fn from(error: ParseError) -> ParseFailure {
+    ParseFailure::Parse(error)
+  }
+}
+
+impl fmt::Display for ParseFailure
+{
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      &ParseFailure::UnknownRule(ref rule) => write!(f, "`{}` is not a rule of the grammar.", rule),
+      &ParseFailure::Parse(ref error) => error.fmt(f)
+    }
+  }
+}
+
+impl error::Error for ParseFailure {}
+
+/// The errors of a grammar, with their positions in the source of the grammar.
+#[derive(Debug)]
+pub struct GrammarError
+{
+  pub diagnostics: Vec<String>
+}
+
+impl fmt::Display for GrammarError
+{
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "the grammar has errors:\n{}", self.diagnostics.join("\n"))
+  }
+}
+
+impl error::Error for GrammarError {}
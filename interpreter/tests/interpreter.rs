@@ -0,0 +1,157 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate oak;
+extern crate oak_compiler;
+extern crate oak_runtime;
+extern crate oak_interpreter;
+
+use oak_interpreter::{Grammar, GrammarError, ParseFailure, Tree};
+
+static CALC: &str = r#"
+  sum = product ("+" spacing product)*
+  product = number ("*" spacing number)*
+  number = ["0-9"]+ spacing > to_number
+  spacing = [" "]*:(^)
+
+  fn to_number(digits: Vec<char>) -> u32 {
+    digits.into_iter().collect::<String>().parse().unwrap()
+  }
+"#;
+
+/// The same grammar compiled, to compare the errors.
+mod calc {
+  use oak::oak;
+  use oak_runtime::*;
+
+  oak! {
+    sum = product ("+" spacing product)*
+    product = number ("*" spacing number)*
+    number = ["0-9"]+ spacing > to_number
+    spacing = [" "]*:(^)
+
+    fn to_number(digits: Vec<char>) -> u32 {
+      digits.into_iter().collect::<String>().parse().unwrap()
+    }
+  }
+}
+
+fn shape(tree: &Tree, input: &str) -> String {
+  if tree.children.is_empty() {
+    format!("{}({:?})", tree.rule, tree.text(input))
+  }
+  else {
+    let children: Vec<_> = tree.children.iter().map(|child| shape(child, input)).collect();
+    format!("{}[{}]", tree.rule, children.join(", "))
+  }
+}
+
+#[test]
+fn test_tree() {
+  let grammar = Grammar::new(CALC).unwrap();
+  assert_eq!(grammar.rules(), vec!["sum", "product", "number", "spacing"]);
+  let input = "1 + 2*3";
+  let tree = grammar.parse("sum", input).unwrap();
+  assert_eq!(tree.span, 0..7);
+  assert_eq!(shape(&tree, input),
+    "sum[product[number[spacing(\" \")]], spacing(\" \"), \
+      product[number[spacing(\"\")], spacing(\"\"), number[spacing(\"\")]]]");
+  assert_eq!(tree.children[2].text(input), "2*3");
+}
+
+#[test]
+fn test_prefix() {
+  let grammar = Grammar::new(CALC).unwrap();
+  let (tree, read) = grammar.parse_prefix("product", "2*3+4").unwrap();
+  assert_eq!(read, 3);
+  assert_eq!(tree.rule, "product");
+}
+
+#[test]
+fn test_same_errors_as_compiled_parsers() {
+  use oak_runtime::*;
+  let grammar = Grammar::new(CALC).unwrap();
+  for input in &["", "1+", "1 + x", "1*2 3", "a"] {
+    let expected = calc::parse_sum(input.into_state()).into_full_result().unwrap_err();
+    assert_eq!(grammar.parse("sum", input).unwrap_err(), ParseFailure::Parse(expected), "input `{}`", input);
+  }
+}
+
+#[test]
+fn test_unknown_rule() {
+  let grammar = Grammar::new(CALC).unwrap();
+  let unknown = ParseFailure::UnknownRule("expr".to_string());
+  assert_eq!(grammar.parse("expr", "1").unwrap_err(), unknown);
+  assert_eq!(grammar.parse_prefix("expr", "1").unwrap_err(), unknown);
+  assert_eq!(unknown.to_string(), "`expr` is not a rule of the grammar.");
+}
+
+#[test]
+fn test_expressions() {
+  let grammar = Grammar::new(r#"
+    keyword = "select"i !ident_char
+    ident_char = ["a-zA-Z0-9_"]
+    ident = &["\\p{XID_Start}"] ident_char+
+    not_digit = [^"0-9"]
+    any = . .?
+  "#).unwrap();
+  assert!(grammar.parse("keyword", "SeLeCt").is_ok());
+  assert!(grammar.parse("keyword", "selected").is_err());
+  assert!(grammar.parse("ident", "été").is_err());
+  assert!(grammar.parse("ident", "x_1").is_ok());
+  assert!(grammar.parse("ident", "1x").is_err());
+  assert!(grammar.parse("not_digit", "a").is_ok());
+  assert!(grammar.parse("not_digit", "1").is_err());
+  assert!(grammar.parse("any", "ab").is_ok());
+  assert!(grammar.parse("any", "abc").is_err());
+}
+
+fn grammar_error(source: &str) -> GrammarError {
+  match Grammar::new(source) {
+    Err(err) => err,
+    Ok(_) => panic!("the grammar `{}` has no error.", source)
+  }
+}
+
+#[test]
+fn test_grammar_errors() {
+  let err = grammar_error("sum = number\nsum = number\nnumber = [\"0-9\"]");
+  assert!(err.diagnostics.iter().any(|d| d.contains("<grammar>:2:1")), "{}", err);
+  let err = grammar_error("sum = number\nnumber = other::number");
+  assert!(err.to_string().contains("`other::number` is not a rule of the grammar"), "{}", err);
+  let err = grammar_error("sum = (");
+  assert!(!err.diagnostics.is_empty());
+}
+
+#[test]
+fn test_warnings() {
+  let grammar = Grammar::new("#![private_rules]\nsum = number\nnumber = [\"0-9\"]\nunused = number").unwrap();
+  assert_eq!(grammar.warnings().len(), 1);
+  assert!(grammar.warnings()[0].contains("rule `unused` is never used"));
+  // Without `#![private_rules]`, the rules are exposed but only the first one is an entry rule.
+  let grammar = Grammar::new("sum = number\nnumber = [\"0-9\"]\nunused = number").unwrap();
+  assert_eq!(grammar.warnings().len(), 1);
+  assert!(grammar.warnings()[0].contains("rule `unused` is never used"));
+}
+
+#[test]
+fn test_configuration_errors() {
+  let err = grammar_error("#[cfg(a, b)]\nr = \"a\"");
+  assert!(err.to_string().contains("expected a single predicate"), "{}", err);
+  let err = grammar_error("#[cfg(unix)]\nr = \"a\"");
+  assert!(err.to_string().contains("cannot be evaluated in a grammar loaded at run time"), "{}", err);
+  // An error left pending on this thread is not reported with the next grammar.
+  assert!(oak_compiler::parse("r = (").is_none());
+  assert!(Grammar::new("r = \"a\" / \"ab\"").is_ok());
+}
@@ -0,0 +1,114 @@
+#!/usr/bin/env python3
+# Copyright 2026 Pierre Talbot
+
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+
+#     http://www.apache.org/licenses/LICENSE-2.0
+
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+# Generates `src/unicode/tables.rs` from the Unicode database of Python and the case folding of the same Unicode version:
+#   python3 scripts/gen_unicode_tables.py CaseFolding.txt > src/unicode/tables.rs
+# where `CaseFolding.txt` is downloaded from https://www.unicode.org/Public/<version>/ucd/CaseFolding.txt, `<version>` being printed by `python3 -c "import unicodedata; print(unicodedata.unidata_version)"`.
+# The list of the sets must be kept in sync with `UNICODE_SETS` in `liboak/ast.rs`.
+
+import sys
+import unicodedata
+
+CATEGORIES = [
+  "L", "Lu", "Ll", "Lt", "Lm", "Lo",
+  "M", "Mn", "Mc", "Me",
+  "N", "Nd", "Nl", "No",
+  "P", "Pc", "Pd", "Ps", "Pe", "Pi", "Pf", "Po",
+  "S", "Sm", "Sc", "Sk", "So",
+  "Z", "Zs", "Zl", "Zp",
+  "Cc", "Cf"
+]
+
+def chars():
+  for code in range(0x110000):
+    if not (0xD800 <= code <= 0xDFFF):
+      yield chr(code)
+
+def is_xid_start(c):
+  # Python identifiers start with `XID_Start` or `_`.
+  return c != "_" and c.isidentifier()
+
+def is_xid_continue(c):
+  return ("a" + c).isidentifier()
+
+def ranges(predicate):
+  res = []
+  for c in chars():
+    if predicate(c):
+      code = ord(c)
+      if res and (res[-1][1] + 1 == code or (res[-1][1] == 0xD7FF and code == 0xE000)):
+        res[-1][1] = code
+      else:
+        res.append([code, code])
+  return res
+
+def category(name):
+  return lambda c: unicodedata.category(c).startswith(name)
+
+def table(name, predicate):
+  rs = ranges(predicate)
+  lines = ["/// `\\p{%s}`" % name,
+    "pub static %s: &'static [(char, char)] = &[" % name.upper()]
+  for i in range(0, len(rs), 4):
+    lines.append("  " + " ".join("('\\u{%x}', '\\u{%x}')," % (lo, hi) for lo, hi in rs[i:i+4]))
+  lines.append("];")
+  return "\n".join(lines)
+
+def simple_case_folding(path):
+  # The simple case folding is made of the mappings with the status `C` (common) and `S` (simple), the statuses `F` (full) and `T` (Turkic) are ignored.
+  mappings = []
+  with open(path, encoding="utf-8") as f:
+    version = f.readline()
+    if unicodedata.unidata_version not in version:
+      sys.exit("%s is not the case folding of Unicode %s: %s" % (path, unicodedata.unidata_version, version.strip()))
+    for line in f:
+      fields = [field.strip() for field in line.split("#")[0].split(";")]
+      if len(fields) >= 3 and fields[1] in ("C", "S"):
+        mappings.append((int(fields[0], 16), int(fields[2], 16)))
+  return sorted(mappings)
+
+def case_folding(path):
+  mappings = simple_case_folding(path)
+  lines = ["/// Simple case folding (the statuses `C` and `S` of `CaseFolding.txt`), the pairs `(c, fold(c))` are sorted by `c`.",
+    "pub static CASE_FOLDING: &'static [(char, char)] = &["]
+  for i in range(0, len(mappings), 4):
+    lines.append("  " + " ".join("('\\u{%x}', '\\u{%x}')," % (c, f) for c, f in mappings[i:i+4]))
+  lines.append("];")
+  return "\n".join(lines)
+
+def main():
+  if len(sys.argv) != 2:
+    sys.exit("usage: python3 scripts/gen_unicode_tables.py CaseFolding.txt > src/unicode/tables.rs")
+  out = sys.stdout
+  out.write("// Copyright 2026 Pierre Talbot\n\n")
+  out.write("// Licensed under the Apache License, Version 2.0 (the \"License\");\n")
+  out.write("// you may not use this file except in compliance with the License.\n")
+  out.write("// You may obtain a copy of the License at\n\n")
+  out.write("//     http://www.apache.org/licenses/LICENSE-2.0\n\n")
+  out.write("// Unless required by applicable law or agreed to in writing, software\n")
+  out.write("// distributed under the License is distributed on an \"AS IS\" BASIS,\n")
+  out.write("// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.\n")
+  out.write("// See the License for the specific language governing permissions and\n")
+  out.write("// limitations under the License.\n\n")
+  out.write("//! Generated by `scripts/gen_unicode_tables.py` from Unicode %s, do not edit.\n" % unicodedata.unidata_version)
+  out.write("//! Every table is a sorted list of disjoint intervals.\n\n")
+  tables = [table(name, category(name)) for name in CATEGORIES]
+  tables.append(table("XID_Start", is_xid_start))
+  tables.append(table("XID_Continue", is_xid_continue))
+  tables.append(case_folding(sys.argv[1]))
+  out.write("\n\n".join(tables))
+  out.write("\n")
+
+main()
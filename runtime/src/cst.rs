@@ -0,0 +1,477 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lossless concrete syntax trees built by the grammars declared with `#![cst]`.
+//!
+//! The rule functions of such grammars record a node in the parsing state each time a rule matches a non-empty input, independently of the value built by the semantic actions.
+//! When a parsing expression backtracks, the nodes read after the restored position are removed.
+//! The tree is built from these nodes with `ParseState::syntax_tree`: the text of a node not covered by its children is stored in token leaves, thus the spacing and the comments are kept and the text of the tree is exactly the input read.
+//!
+//! The tree has two layers:
+//!
+//! * The green tree (`GreenNode`) is immutable and only contains the kinds of the nodes, which are the names of the rules, and the text of the tokens.
+//! * The red tree (`SyntaxNode`, `SyntaxToken`) is built on demand on top of the green tree, it knows the position of the elements in the input and their parent, which allows to navigate in every direction.
+
+use stream::*;
+use str_stream::{Span, Range};
+use make_span;
+use std::rc::Rc;
+use std::fmt::{Formatter, Debug, Display, Error};
+
+/// A node recorded in the parsing state, from `start` to `end`.
+#[derive(Clone)]
+pub struct RecordedNode<S>
+{
+  pub kind: &'static str,
+  pub start: S,
+  pub end: S,
+  /// The farthest position examined to parse this node, it is at least `end`.
+  pub examined: S,
+  /// The tree of the node if it is reused from a previous tree, its children are not recorded.
+  pub green: Option<Rc<GreenNode>>
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum GreenElement
+{
+  Node(Rc<GreenNode>),
+  Token(Rc<str>)
+}
+
+impl GreenElement
+{
+  pub fn text_len(&self) -> usize {
+    match self {
+      &GreenElement::Node(ref node) => node.text_len(),
+      &GreenElement::Token(ref text) => text.len()
+    }
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct GreenNode
+{
+  kind: &'static str,
+  text_len: usize,
+  /// The number of bytes examined after the end of the node, when it was parsed.
+  lookahead: usize,
+  children: Vec<GreenElement>
+}
+
+impl GreenNode
+{
+  pub fn new(kind: &'static str, children: Vec<GreenElement>) -> GreenNode {
+    let text_len = children.iter().map(|child| child.text_len()).sum();
+    GreenNode { kind, text_len, lookahead: 0, children }
+  }
+
+  /// The name of the rule of this node.
+  pub fn kind(&self) -> &'static str {
+    self.kind
+  }
+
+  /// The length in bytes of the text of this node.
+  pub fn text_len(&self) -> usize {
+    self.text_len
+  }
+
+  /// The number of bytes examined after the end of the node when it was parsed, the text of the node only depends on the input up to there.
+  pub fn lookahead(&self) -> usize {
+    self.lookahead
+  }
+
+  pub fn children(&self) -> &[GreenElement] {
+    &self.children
+  }
+
+  /// Modifies the children of this node and updates its length.
+  pub fn update_children<F>(&mut self, f: F) where
+   F: FnOnce(&mut Vec<GreenElement>)
+  {
+    f(&mut self.children);
+    self.text_len = self.children.iter().map(|child| child.text_len()).sum();
+  }
+
+  pub fn text(&self) -> String {
+    let mut text = String::with_capacity(self.text_len);
+    self.push_text(&mut text);
+    text
+  }
+
+  fn push_text(&self, text: &mut String) {
+    for child in &self.children {
+      match child {
+        &GreenElement::Node(ref node) => node.push_text(text),
+        &GreenElement::Token(ref token) => text.push_str(token)
+      }
+    }
+  }
+}
+
+struct NodeData
+{
+  green: Rc<GreenNode>,
+  parent: Option<SyntaxNode>,
+  /// The index of this node in the children of its parent.
+  index: usize,
+  /// The offset in bytes of this node in the input.
+  offset: usize
+}
+
+/// A node of the red tree, it is cheap to clone.
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+impl SyntaxNode
+{
+  /// The root of the tree `green` starting at the byte `offset` of the input.
+  pub fn new_root(green: Rc<GreenNode>, offset: usize) -> SyntaxNode {
+    SyntaxNode(Rc::new(NodeData { green, parent: None, index: 0, offset }))
+  }
+
+  pub fn green(&self) -> &Rc<GreenNode> {
+    &self.0.green
+  }
+
+  pub fn kind(&self) -> &'static str {
+    self.0.green.kind()
+  }
+
+  pub fn span(&self) -> Span {
+    make_span(self.0.offset, self.0.offset + self.0.green.text_len())
+  }
+
+  pub fn text(&self) -> String {
+    self.0.green.text()
+  }
+
+  pub fn parent(&self) -> Option<SyntaxNode> {
+    self.0.parent.clone()
+  }
+
+  /// The parent of this node, its grand-parent, and so on up to the root.
+  pub fn ancestors(&self) -> Vec<SyntaxNode> {
+    let mut ancestors = vec![];
+    let mut node = self.parent();
+    while let Some(parent) = node {
+      node = parent.parent();
+      ancestors.push(parent);
+    }
+    ancestors
+  }
+
+  /// The nodes and tokens directly under this node.
+  pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+    let mut offset = self.0.offset;
+    let mut children = vec![];
+    for (index, child) in self.0.green.children().iter().enumerate() {
+      children.push(self.child(index, child, offset));
+      offset += child.text_len();
+    }
+    children
+  }
+
+  /// The nodes directly under this node.
+  pub fn children(&self) -> Vec<SyntaxNode> {
+    self.children_with_tokens().into_iter()
+      .filter_map(|child| child.into_node())
+      .collect()
+  }
+
+  pub fn first_child(&self) -> Option<SyntaxNode> {
+    self.children().into_iter().next()
+  }
+
+  /// The first node of kind `kind` in this subtree, this node included, in pre-order.
+  pub fn find(&self, kind: &str) -> Option<SyntaxNode> {
+    if self.kind() == kind {
+      return Some(self.clone());
+    }
+    self.children().iter().filter_map(|child| child.find(kind)).next()
+  }
+
+  pub fn next_sibling(&self) -> Option<SyntaxElement> {
+    self.sibling(self.0.index + 1)
+  }
+
+  pub fn prev_sibling(&self) -> Option<SyntaxElement> {
+    self.0.index.checked_sub(1).and_then(|index| self.sibling(index))
+  }
+
+  fn sibling(&self, index: usize) -> Option<SyntaxElement> {
+    self.parent().and_then(|parent| parent.child_at(index))
+  }
+
+  fn child_at(&self, index: usize) -> Option<SyntaxElement> {
+    let children = self.0.green.children();
+    children.get(index).map(|child| {
+      let offset = self.0.offset + children[..index].iter().map(|c| c.text_len()).sum::<usize>();
+      self.child(index, child, offset)
+    })
+  }
+
+  fn child(&self, index: usize, child: &GreenElement, offset: usize) -> SyntaxElement {
+    match child {
+      &GreenElement::Node(ref green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+        green: green.clone(),
+        parent: Some(self.clone()),
+        index, offset
+      }))),
+      &GreenElement::Token(ref text) => SyntaxElement::Token(SyntaxToken {
+        text: text.clone(),
+        parent: self.clone(),
+        index, offset
+      })
+    }
+  }
+}
+
+impl PartialEq for SyntaxNode
+{
+  fn eq(&self, other: &SyntaxNode) -> bool {
+    Rc::ptr_eq(&self.0.green, &other.0.green) && self.0.offset == other.0.offset
+  }
+}
+
+impl Eq for SyntaxNode {}
+
+/// Prints the text of the node.
+impl Display for SyntaxNode
+{
+  fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error> {
+    formatter.write_str(&self.text())
+  }
+}
+
+/// Prints the tree with one element per line, indented by depth, such as `number@0..2` for a node and `"12"@0..2` for a token.
+impl Debug for SyntaxNode
+{
+  fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error> {
+    fn print(node: &SyntaxNode, depth: usize, formatter: &mut Formatter) -> Result<(), Error> {
+      let (lo, hi) = (node.0.offset, node.0.offset + node.0.green.text_len());
+      formatter.write_fmt(format_args!("{:indent$}{}@{}..{}\n", "", node.kind(), lo, hi, indent = depth * 2))?;
+      for child in node.children_with_tokens() {
+        match child {
+          SyntaxElement::Node(child) => print(&child, depth + 1, formatter)?,
+          SyntaxElement::Token(token) => {
+            let (lo, hi) = (token.offset, token.offset + token.text.len());
+            formatter.write_fmt(format_args!("{:indent$}{:?}@{}..{}\n", "", token.text(), lo, hi, indent = (depth + 1) * 2))?;
+          }
+        }
+      }
+      Ok(())
+    }
+    print(self, 0, formatter)
+  }
+}
+
+/// A leaf of the red tree, it contains the text of the input not covered by the children nodes of its parent.
+#[derive(Clone)]
+pub struct SyntaxToken
+{
+  text: Rc<str>,
+  parent: SyntaxNode,
+  index: usize,
+  offset: usize
+}
+
+impl SyntaxToken
+{
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  pub fn span(&self) -> Span {
+    make_span(self.offset, self.offset + self.text.len())
+  }
+
+  pub fn parent(&self) -> SyntaxNode {
+    self.parent.clone()
+  }
+
+  pub fn next_sibling(&self) -> Option<SyntaxElement> {
+    self.parent.child_at(self.index + 1)
+  }
+
+  pub fn prev_sibling(&self) -> Option<SyntaxElement> {
+    self.index.checked_sub(1).and_then(|index| self.parent.child_at(index))
+  }
+}
+
+impl Debug for SyntaxToken
+{
+  fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error> {
+    formatter.write_fmt(format_args!("{:?}@{}..{}", self.text(), self.offset, self.offset + self.text.len()))
+  }
+}
+
+#[derive(Clone, Debug)]
+pub enum SyntaxElement
+{
+  Node(SyntaxNode),
+  Token(SyntaxToken)
+}
+
+impl SyntaxElement
+{
+  pub fn span(&self) -> Span {
+    match self {
+      &SyntaxElement::Node(ref node) => node.span(),
+      &SyntaxElement::Token(ref token) => token.span()
+    }
+  }
+
+  pub fn text(&self) -> String {
+    match self {
+      &SyntaxElement::Node(ref node) => node.text(),
+      &SyntaxElement::Token(ref token) => token.text().to_string()
+    }
+  }
+
+  pub fn parent(&self) -> Option<SyntaxNode> {
+    match self {
+      &SyntaxElement::Node(ref node) => node.parent(),
+      &SyntaxElement::Token(ref token) => Some(token.parent())
+    }
+  }
+
+  pub fn into_node(self) -> Option<SyntaxNode> {
+    match self {
+      SyntaxElement::Node(node) => Some(node),
+      SyntaxElement::Token(_) => None
+    }
+  }
+
+  pub fn into_token(self) -> Option<SyntaxToken> {
+    match self {
+      SyntaxElement::Node(_) => None,
+      SyntaxElement::Token(token) => Some(token)
+    }
+  }
+}
+
+/// Builds the green tree of the nodes recorded in post-order, under a root of kind `root` covering the input from `start` to `end`.
+/// The root is the last node recorded if it covers the same input, otherwise the recorded nodes are its children.
+pub fn build_tree<S>(root: &'static str, nodes: &[RecordedNode<S>], start: S, end: S) -> GreenNode where
+ S: Ord + Clone,
+ Range<S>: StreamText
+{
+  // The completed elements which do not have a parent yet, with their position.
+  let mut stack: Vec<(S, S, GreenElement)> = vec![];
+  for node in nodes {
+    let green = match node.green {
+      Some(ref green) => green.clone(),
+      None => {
+        let first_child = stack.iter().rposition(|&(ref lo, _, _)| *lo < node.start).map_or(0, |pos| pos + 1);
+        let children = stack.split_off(first_child);
+        let mut green = green_node(node.kind, children, node.start.clone(), node.end.clone());
+        if node.examined > node.end {
+          green.lookahead = Range { start: node.end.clone(), end: node.examined.clone() }.stream_text().len();
+        }
+        Rc::new(green)
+      }
+    };
+    stack.push((node.start.clone(), node.end.clone(), GreenElement::Node(green)));
+  }
+  match stack.last() {
+    Some(&(ref lo, ref hi, GreenElement::Node(ref node)))
+      if stack.len() == 1 && node.kind() == root && *lo == start && *hi == end => return (**node).clone(),
+    _ => ()
+  }
+  green_node(root, stack, start, end)
+}
+
+/// The node of kind `kind` from `start` to `end` where the input between the `children` is stored in tokens.
+fn green_node<S>(kind: &'static str, children: Vec<(S, S, GreenElement)>, start: S, end: S) -> GreenNode where
+ S: Ord + Clone,
+ Range<S>: StreamText
+{
+  let mut elements = vec![];
+  let mut pos = start;
+  for (lo, hi, child) in children {
+    push_token(&mut elements, pos, lo);
+    elements.push(child);
+    pos = hi;
+  }
+  push_token(&mut elements, pos, end);
+  GreenNode::new(kind, elements)
+}
+
+fn push_token<S>(elements: &mut Vec<GreenElement>, start: S, end: S) where
+ S: Ord,
+ Range<S>: StreamText
+{
+  if start < end {
+    elements.push(GreenElement::Token(Range { start, end }.stream_text().into()));
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use str_stream::StrStream;
+
+  fn stream_at(input: &'static str, offset: usize) -> StrStream<'static> {
+    let mut stream = input.stream();
+    for _ in 0..offset { stream.next(); }
+    stream
+  }
+
+  fn node(input: &'static str, kind: &'static str, start: usize, end: usize) -> RecordedNode<StrStream<'static>> {
+    RecordedNode { kind, start: stream_at(input, start), end: stream_at(input, end), examined: stream_at(input, end), green: None }
+  }
+
+  #[test]
+  fn test_build_tree() {
+    let input = "let x = 12;";
+    // `stmt = "let" spacing ident spacing "=" spacing number ";"`
+    let nodes = vec![
+      node(input, "spacing", 3, 4),
+      node(input, "ident", 4, 5),
+      node(input, "spacing", 5, 6),
+      node(input, "spacing", 7, 8),
+      node(input, "number", 8, 10),
+      node(input, "stmt", 0, 11)
+    ];
+    let green = build_tree("stmt", &nodes, stream_at(input, 0), stream_at(input, 11));
+    let root = SyntaxNode::new_root(Rc::new(green), 0);
+    assert_eq!(root.text(), input);
+    assert_eq!(root.kind(), "stmt");
+    assert_eq!(root.children().len(), 5);
+    assert_eq!(root.children_with_tokens().len(), 8);
+
+    let number = root.find("number").unwrap();
+    assert_eq!(number.text(), "12");
+    assert_eq!(number.span(), make_span(8, 10));
+    assert_eq!(number.parent(), Some(root.clone()));
+    let semi = number.next_sibling().unwrap().into_token().unwrap();
+    assert_eq!(semi.text(), ";");
+    assert_eq!(semi.span(), make_span(10, 11));
+    assert!(semi.next_sibling().is_none());
+    assert_eq!(semi.prev_sibling().unwrap().into_node(), Some(number.clone()));
+    assert_eq!(number.ancestors(), vec![root.clone()]);
+    assert_eq!(format!("{:?}", root.first_child().unwrap()), "spacing@3..4\n  \" \"@3..4\n");
+  }
+
+  #[test]
+  fn test_build_tree_root() {
+    let input = "a b";
+    let nodes = vec![node(input, "letter", 0, 1), node(input, "letter", 2, 3)];
+    let root = SyntaxNode::new_root(Rc::new(
+      build_tree("letters", &nodes, stream_at(input, 0), stream_at(input, 3))), 0);
+    assert_eq!(format!("{:?}", root), "letters@0..3\n  letter@0..1\n    \"a\"@0..1\n  \" \"@1..2\n  letter@2..3\n    \"b\"@2..3\n");
+    let empty = build_tree("letters", &[], stream_at(input, 0), stream_at(input, 0));
+    assert_eq!(empty.children().len(), 0);
+  }
+}
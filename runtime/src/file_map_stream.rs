@@ -109,13 +109,20 @@ impl<'a> CodeSnippet for FileMapStream<'a>
   }
 }
 
-impl<'a> ConsumePrefix<&'static str> for FileMapStream<'a>
+impl<'a, 'b> ConsumePrefix<&'b str> for FileMapStream<'a>
 {
-  fn consume_prefix(&mut self, prefix: &'static str) -> bool {
+  fn consume_prefix(&mut self, prefix: &'b str) -> bool {
     self.str_stream.consume_prefix(prefix)
   }
 }
 
+impl<'a, 'b> ConsumePrefixIgnoreCase<&'b str> for FileMapStream<'a>
+{
+  fn consume_prefix_ignore_case(&mut self, prefix: &'b str) -> bool {
+    self.str_stream.consume_prefix_ignore_case(prefix)
+  }
+}
+
 impl<'a> HasNext for FileMapStream<'a>
 {
   fn has_next(&self) -> bool {
@@ -134,6 +141,16 @@ impl<'a> StreamSpan for Range<FileMapStream<'a>>
   }
 }
 
+impl<'a> StreamText for Range<FileMapStream<'a>>
+{
+  fn stream_text(&self) -> String {
+    Range {
+      start: self.start.str_stream.clone(),
+      end: self.end.str_stream.clone()
+    }.stream_text()
+  }
+}
+
 #[cfg(test)]
 mod test {
   extern crate syntex_syntax;
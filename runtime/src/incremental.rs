@@ -0,0 +1,185 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Incremental reparsing of a concrete syntax tree (see `cst`) after an edit of its input.
+//!
+//! Each node of a tree remembers how many bytes after its end were examined to parse it (`GreenNode::lookahead`).
+//! A node is not affected by an edit if the edit does not intersect the input from its start to the end of the input examined, thus its rule still produces the same node at its new position.
+//! The new input is parsed with the recognizer of the start rule, and when a rule is called at the position of an unaffected node of the same kind, the node is reused instead of running the rule again.
+//! The entry point `reparse_cst` is generated for the start rules of the grammars declared with `#![cst]`, it can be called when the stream implements `TextStream`, such as `StrStream`.
+
+use parse_state::*;
+use stream::*;
+use str_stream::StrStream;
+use cst::*;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// Replaces the bytes `range` of a text by `replacement`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit
+{
+  pub range: Range<usize>,
+  pub replacement: String
+}
+
+impl TextEdit
+{
+  pub fn new(range: Range<usize>, replacement: &str) -> TextEdit {
+    TextEdit { range, replacement: replacement.to_string() }
+  }
+
+  pub fn apply(&self, text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + self.replacement.len());
+    result.push_str(&text[..self.range.start]);
+    result.push_str(&self.replacement);
+    result.push_str(&text[self.range.end..]);
+    result
+  }
+
+  /// The offset in the edited text of `offset`, which is after the edit.
+  fn shift(&self, offset: usize) -> usize {
+    offset - self.range.end + self.range.start + self.replacement.len()
+  }
+}
+
+/// A stream of a text whose positions are byte offsets, the nodes of a tree are moved in the edited text with these offsets.
+pub trait TextStream<'a>: Ord + Clone + HasNext + Location + CodeSnippet
+{
+  /// The stream of `text` positioned at the byte `offset`.
+  fn at(text: &'a str, offset: usize) -> Self;
+  fn bytes_offset(&self) -> usize;
+}
+
+impl<'a> TextStream<'a> for StrStream<'a>
+{
+  fn at(text: &'a str, offset: usize) -> Self {
+    StrStream::at(text, offset)
+  }
+
+  fn bytes_offset(&self) -> usize {
+    StrStream::bytes_offset(self)
+  }
+}
+
+pub struct Reparse
+{
+  /// The tree of the edited text.
+  pub tree: SyntaxNode,
+  /// The bytes of the edited text whose tree changed, outside of this range the nodes and tokens are the same as in the previous tree.
+  pub changed: Range<usize>
+}
+
+/// Parses `text`, which is the input of `tree` modified by `edit`, with the recognizer of the root of `tree`.
+/// `lookahead` is the number of bytes that can be examined after the farthest position reached by the parser, for instance when a string literal does not match.
+/// It is generated as `OAK_LOOKAHEAD` in the module of a grammar declared with `#![cst]`, whose entry point `reparse_cst` calls this function.
+/// The reused nodes do not report the items they expected, thus the text is parsed again without reusing nodes to report an error.
+pub fn reparse<'a, S, R>(tree: &SyntaxNode, edit: &TextEdit, text: &'a str, lookahead: usize,
+  recognizer: R) -> Result<Reparse, ParseError> where
+ S: TextStream<'a>,
+ Range<S>: StreamText + StreamSpan<Output=::Span>,
+ R: Fn(ParseState<S, ()>) -> ParseState<S, ()>
+{
+  let mut reusable: Vec<RecordedNode<S>> = vec![];
+  collect_reusable(tree, edit, text, lookahead, &mut reusable);
+  reusable.sort_by_key(|node| node.start.bytes_offset());
+  let start = S::at(text, 0);
+  let mut state = ParseState::new(start.clone());
+  state.cst = Some(CstState::new(start.clone(), reusable));
+  let state = recognizer(state);
+  let new_tree = state.syntax_tree(tree.kind(), start.clone());
+  if state.into_full_result().is_err() {
+    recognizer(ParseState::new(start)).into_full_result()?;
+  }
+  let changed = changed_region(tree.green(), new_tree.green(), 0);
+  Ok(Reparse { tree: new_tree, changed })
+}
+
+/// The descendants of `node` not affected by `edit`, at their position in `text`.
+fn collect_reusable<'a, S>(node: &SyntaxNode, edit: &TextEdit, text: &'a str, lookahead: usize,
+  reusable: &mut Vec<RecordedNode<S>>) where
+ S: TextStream<'a>
+{
+  for child in node.children() {
+    let span = child.span();
+    let (lo, hi) = (span.lo.0 as usize, span.hi.0 as usize);
+    let examined = hi + child.green().lookahead();
+    let shifted =
+      if examined + lookahead <= edit.range.start { Some((lo, hi, examined)) }
+      else if lo >= edit.range.end { Some((edit.shift(lo), edit.shift(hi), edit.shift(examined))) }
+      else { None };
+    if let Some((lo, hi, examined)) = shifted {
+      reusable.push(RecordedNode {
+        kind: child.kind(),
+        start: S::at(text, lo),
+        end: S::at(text, hi),
+        examined: S::at(text, examined),
+        green: Some(child.green().clone())
+      });
+    }
+    collect_reusable(&child, edit, text, lookahead, reusable);
+  }
+}
+
+/// The region of `new` which differs from `old`, it is empty if the trees are equal.
+/// When a single child differs in both nodes and has the same kind, the region is searched in this child.
+fn changed_region(old: &Rc<GreenNode>, new: &Rc<GreenNode>, offset: usize) -> Range<usize> {
+  let (old_children, new_children) = (old.children(), new.children());
+  let common = ::std::cmp::min(old_children.len(), new_children.len());
+  let prefix = old_children.iter().zip(new_children.iter())
+    .take_while(|&(o, n)| o == n)
+    .count();
+  let suffix = old_children.iter().rev().zip(new_children.iter().rev())
+    .take(common - prefix)
+    .take_while(|&(o, n)| o == n)
+    .count();
+  let start = offset + new_children[..prefix].iter().map(|c| c.text_len()).sum::<usize>();
+  let end = offset + new.text_len()
+    - new_children[new_children.len() - suffix..].iter().map(|c| c.text_len()).sum::<usize>();
+  if old_children.len() == prefix + suffix + 1 && new_children.len() == prefix + suffix + 1 {
+    match (&old_children[prefix], &new_children[prefix]) {
+      (&GreenElement::Node(ref o), &GreenElement::Node(ref n)) if o.kind() == n.kind() =>
+        return changed_region(o, n, start),
+      _ => ()
+    }
+  }
+  start..end
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_text_edit() {
+    let edit = TextEdit::new(2..4, "xyz");
+    assert_eq!(edit.apply("abcdef"), "abxyzef");
+    assert_eq!(edit.shift(4), 5);
+    assert_eq!(edit.shift(6), 7);
+    assert_eq!(TextEdit::new(0..0, "a").apply(""), "a");
+  }
+
+  #[test]
+  fn test_changed_region() {
+    let token = |text: &str| GreenElement::Token(text.into());
+    let node = |kind, children| Rc::new(GreenNode::new(kind, children));
+    let a = node("a", vec![token("1")]);
+    let old = node("root", vec![GreenElement::Node(a.clone()), token(" "), GreenElement::Node(node("b", vec![token("2")]))]);
+    let new = node("root", vec![GreenElement::Node(a.clone()), token(" "), GreenElement::Node(node("b", vec![token("23")]))]);
+    assert_eq!(changed_region(&old, &new, 0), 2..4);
+    let new = node("root", vec![GreenElement::Node(a.clone()), token("  "), GreenElement::Node(node("b", vec![token("2")]))]);
+    assert_eq!(changed_region(&old, &new, 0), 1..3);
+    assert_eq!(changed_region(&old, &old, 0), 3..3);
+  }
+}
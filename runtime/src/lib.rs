@@ -28,6 +28,11 @@ pub mod str_stream;
 pub mod parse_state;
 pub mod stream;
 pub mod file_map_stream;
+pub mod unicode;
+pub mod vm;
+pub mod cst;
+pub mod incremental;
+pub mod printer;
 
 pub fn make_span(lo: usize, hi: usize) -> Span {
   mk_sp(
@@ -15,10 +15,12 @@
 //! A parsing state indicates the current status of the parsing. It is mainly used by compiled PEG combinators.
 
 use stream::*;
+use cst::*;
 use self::ParseResult::*;
-use std::collections::hash_set::HashSet;
+use std::ops::Range;
+use std::rc::Rc;
 use std::cmp::Ord;
-use std::fmt::{Formatter, Debug, Error};
+use std::fmt::{Formatter, Debug, Display, Error};
 
 pub trait IntoState<S, T>
 {
@@ -34,29 +36,106 @@ impl<S, T, R> IntoState<S, T> for R where
   }
 }
 
-pub struct ParseExpectation<S>
+/// The items a grammar can expect, generated once per grammar (`OAK_EXPECTATIONS`). An expected item is referred to by its index in this table.
+/// The tables of the generated parsers are static, the lifetime `'e` allows a table built at run time, for instance by `oak_interpreter`.
+pub type ExpectationTable<'e> = [&'e str];
+
+/// Set of the items expected at the farthest position read. It only stores indexes into expectation tables so reporting an error does not allocate once the set reached its capacity. The names are looked up when the error is displayed.
+#[derive(Clone, Default)]
+pub struct ExpectedSet<'e>
+{
+  /// A bitset for each expectation table. Several tables are present when a grammar calls the rules of another grammar.
+  sets: Vec<(&'e ExpectationTable<'e>, Vec<u64>)>
+}
+
+impl<'e> ExpectedSet<'e>
+{
+  pub fn new() -> ExpectedSet<'e> {
+    ExpectedSet { sets: vec![] }
+  }
+
+  /// Empties the set while keeping its capacity.
+  pub fn clear(&mut self) {
+    for &mut (_, ref mut bits) in &mut self.sets {
+      for word in bits.iter_mut() {
+        *word = 0;
+      }
+    }
+  }
+
+  pub fn insert(&mut self, table: &'e ExpectationTable<'e>, id: usize) {
+    let (word, bit) = (id / 64, id % 64);
+    let bits = self.bits_of(table);
+    if bits.len() <= word {
+      bits.resize(word + 1, 0);
+    }
+    bits[word] |= 1 << bit;
+  }
+
+  fn bits_of(&mut self, table: &'e ExpectationTable<'e>) -> &mut Vec<u64> {
+    let pos = match self.sets.iter().position(|&(t, _)| ::std::ptr::eq(t, table)) {
+      Some(pos) => pos,
+      None => {
+        self.sets.push((table, vec![0; (table.len() + 63) / 64]));
+        self.sets.len() - 1
+      }
+    };
+    &mut self.sets[pos].1
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.sets.iter().all(|&(_, ref bits)| bits.iter().all(|word| *word == 0))
+  }
+
+  /// Names of the expected items, without duplicates.
+  pub fn names(&self) -> Vec<&'e str> {
+    let mut names = vec![];
+    for &(table, ref bits) in &self.sets {
+      for (w, word) in bits.iter().enumerate() {
+        for bit in 0..64 {
+          if word & (1 << bit) != 0 {
+            let name = table[w * 64 + bit];
+            if !names.contains(&name) {
+              names.push(name);
+            }
+          }
+        }
+      }
+    }
+    names
+  }
+}
+
+pub struct ParseExpectation<'e, S>
 {
-  expected: HashSet<&'static str>,
+  /// The expected items, their names are looked up in the expectation tables only when they are requested.
+  expected: ExpectedSet<'e>,
   farthest_read: S
 }
 
-impl<S> ParseExpectation<S>
+impl<'e, S> ParseExpectation<'e, S>
 {
-  pub fn new(farthest_read: S, expected: Vec<&'static str>) -> ParseExpectation<S> {
+  pub fn new(farthest_read: S, expected: ExpectedSet<'e>) -> ParseExpectation<'e, S> {
     ParseExpectation {
-      expected: expected.into_iter().collect(),
+      expected: expected,
       farthest_read: farthest_read
     }
   }
+
+  /// Names of the expected items, without duplicates.
+  pub fn expected(&self) -> Vec<&'e str> {
+    self.expected.names()
+  }
 }
 
-impl<S> ParseExpectation<S> where
+impl<'e, S> ParseExpectation<'e, S> where
  S: Location + CodeSnippet
 {
   pub fn expected_items(&self) -> String {
     let mut desc = String::new();
-    if self.expected.len() > 0 {
-      for expect in &self.expected {
+    let expected = self.expected();
+    if expected.len() > 0 {
+      for expect in expected {
         desc.push('`');
         desc.push_str(expect);
         desc.push_str("` or ");
@@ -68,8 +147,21 @@ impl<S> ParseExpectation<S> where
   }
 }
 
+impl<'e, S> ParseExpectation<'e, S> where
+ S: Location + CodeSnippet
+{
+  /// Renders the expectation into an error independent of the stream.
+  pub fn into_error(self) -> ParseError {
+    ParseError {
+      location: self.farthest_read.location(),
+      unexpected: self.farthest_read.code_snippet(10usize),
+      expected: self.expected().into_iter().map(String::from).collect()
+    }
+  }
+}
+
 /// Prints an error message of the form: ```1:1: unexpected `a+1`, expecting `(` or `["0-9"]`.``` where `1:1` is the line and the column where the error occurred.
-impl<S> Debug for ParseExpectation<S> where
+impl<'e, S> Debug for ParseExpectation<'e, S> where
  S: Location + CodeSnippet
 {
   fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error> {
@@ -81,14 +173,55 @@ impl<S> Debug for ParseExpectation<S> where
   }
 }
 
-pub enum ParseResult<S, T>
+/// Error returned by the entry points `parse` and `parse_prefix` generated with the grammar attribute `#![start(rule)]`. Contrarily to `ParseExpectation`, it does not borrow the input.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ParseError
+{
+  /// Position of the error, for example `2:5` for line 2 and column 5.
+  pub location: String,
+  /// Code snippet starting at `location`.
+  pub unexpected: String,
+  /// Items expected at `location`, without duplicates.
+  pub expected: Vec<String>
+}
+
+impl ParseError
+{
+  pub fn expected_items(&self) -> String {
+    let expected: Vec<_> = self.expected.iter()
+      .map(|expect| format!("`{}`", expect))
+      .collect();
+    expected.join(" or ")
+  }
+}
+
+/// Prints an error message of the form: ```1:1: unexpected `a+1`, expecting `(` or `["0-9"]`.```, similarly to `ParseExpectation`.
+impl Display for ParseError
+{
+  fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error> {
+    formatter.write_fmt(
+      format_args!("{}: unexpected `{}`, expecting {}.",
+        self.location, self.unexpected, self.expected_items()))
+  }
+}
+
+impl Debug for ParseError
+{
+  fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error> {
+    Display::fmt(self, formatter)
+  }
+}
+
+impl ::std::error::Error for ParseError {}
+
+pub enum ParseResult<'e, S, T>
 {
   Success(T),
-  Partial(T, ParseExpectation<S>),
-  Failure(ParseExpectation<S>)
+  Partial(T, ParseExpectation<'e, S>),
+  Failure(ParseExpectation<'e, S>)
 }
 
-impl<S, T> Debug for ParseResult<S, T> where
+impl<'e, S, T> Debug for ParseResult<'e, S, T> where
  T: Debug,
  S: HasNext + Location + CodeSnippet
 {
@@ -112,35 +245,65 @@ impl<S, T> Debug for ParseResult<S, T> where
   }
 }
 
-/// `ParseState<S, T>` reads value from the stream `S` and build an AST of type `T`.
+/// `ParseState<S, T>` reads value from the stream `S` and build an AST of type `T`, the expected items are reported with static expectation tables as in the generated parsers.
+pub type ParseState<S, T> = ParseStateWith<'static, S, T>;
+
+/// The parsing state with the expectation tables borrowed for `'e`, see `ParseState`.
 /// Error strategy: Even in case of success, we keep error information in case we fail later. Think about parsing "abaa" with `"ab"* "c"`, it will directly fails on `"c"`, so it is better to report an error such as `expected "ab" but got "aa"` since the input partially matches "ab"`.
-pub struct ParseState<S, T>
+pub struct ParseStateWith<'e, S, T>
 {
   /// The farthest read into the stream at which we encountered an error.
   pub farthest_read: S,
-  /// Expected items at position `farthest_read`. Duplicate entries are possible.
-  pub expected: Vec<&'static str>,
+  /// Expected items at position `farthest_read`.
+  pub expected: ExpectedSet<'e>,
   pub failed: bool,
   /// The current stream that can be partially or fully consumed.
   pub current: S,
   /// Contains the AST if the current state is successful and `None` if it is erroneous.
-  pub data: Option<T>
+  pub data: Option<T>,
+  /// The concrete syntax tree recorded by the rules of the grammars declared with `#![cst]`, only when it is requested with `with_cst`.
+  pub cst: Option<CstState<S>>
+}
+
+/// The part of the parsing state building the concrete syntax tree, see `ParseStateWith::with_cst`.
+pub struct CstState<S>
+{
+  /// The nodes of the concrete syntax tree in post-order.
+  pub nodes: Vec<RecordedNode<S>>,
+  /// The farthest position read before backtracking, with `farthest_read` it bounds the input examined to parse a node.
+  pub examined: S,
+  /// The nodes of a previous tree which can be reused when their rule is called at their position, ordered by position (see `incremental`).
+  pub reusable: Vec<RecordedNode<S>>
 }
 
-impl<S, T> ParseState<S, T> where
+impl<S> CstState<S>
+{
+  pub fn new(start: S, reusable: Vec<RecordedNode<S>>) -> CstState<S> {
+    CstState { nodes: vec![], examined: start, reusable }
+  }
+}
+
+impl<'e, S, T> ParseStateWith<'e, S, T> where
  S: Ord + Clone + HasNext
 {
   #[inline]
-  pub fn new(stream: S) -> ParseState<S, T> {
-    ParseState {
+  pub fn new(stream: S) -> ParseStateWith<'e, S, T> {
+    ParseStateWith {
       farthest_read: stream.clone(),
-      expected: vec![],
+      expected: ExpectedSet::new(),
       failed: false,
       current: stream,
-      data: None
+      data: None,
+      cst: None
     }
   }
 
+  /// Records the concrete syntax tree from the current position, see `syntax_tree`.
+  pub fn with_cst(mut self) -> Self {
+    self.cst = Some(CstState::new(self.current.clone(), vec![]));
+    self
+  }
+
   pub fn is_failed(&self) -> bool {
     self.failed
   }
@@ -149,38 +312,42 @@ impl<S, T> ParseState<S, T> where
     !self.is_failed()
   }
 
+  /// Records that the item at index `expect` in `table` was expected at the current position.
   #[inline(always)]
-  pub fn error(&mut self, expect: &'static str) {
+  pub fn error(&mut self, table: &'e ExpectationTable<'e>, expect: usize) {
     self.failed = true;
     if self.current > self.farthest_read {
       self.farthest_read = self.current.clone();
-      self.expected = vec![expect];
+      self.expected.clear();
+      self.expected.insert(table, expect);
     }
     else if self.current == self.farthest_read {
-      self.expected.push(expect);
+      self.expected.insert(table, expect);
     }
   }
 
   // TODO: find a way to specialize success when U = T.
   #[inline]
-  pub fn success<U>(self, data: U) -> ParseState<S, U> {
-    ParseState {
+  pub fn success<U>(self, data: U) -> ParseStateWith<'e, S, U> {
+    ParseStateWith {
       farthest_read: self.farthest_read,
       expected: self.expected,
       failed: false,
       current: self.current,
-      data: Some(data)
+      data: Some(data),
+      cst: self.cst
     }
   }
 
   #[inline]
-  pub fn failure<U>(self) -> ParseState<S, U> {
-    ParseState {
+  pub fn failure<U>(self) -> ParseStateWith<'e, S, U> {
+    ParseStateWith {
       farthest_read: self.farthest_read,
       expected: self.expected,
       failed: true,
       current: self.current,
-      data: None
+      data: None,
+      cst: self.cst
     }
   }
 
@@ -189,29 +356,94 @@ impl<S, T> ParseState<S, T> where
     self.current.clone()
   }
 
-  pub fn restore_from_failure(self, mark: S) -> ParseState<S, ()> {
+  pub fn restore_from_failure(self, mark: S) -> ParseStateWith<'e, S, ()> {
     assert!(self.failed, "Restoring a successful ParseState is not allowed.");
     self.restore(mark)
   }
 
-  pub fn restore(self, mark: S) -> ParseState<S, ()> {
+  pub fn restore(mut self, mark: S) -> ParseStateWith<'e, S, ()> {
     assert!(self.data.is_none(), "Restoring a ParseState with data is not allowed.");
-    ParseState {
+    self.rewind(mark);
+    ParseStateWith {
       farthest_read: self.farthest_read,
       expected: self.expected,
       failed: false,
-      current: mark,
-      data: None
+      current: self.current,
+      data: None,
+      cst: self.cst
+    }
+  }
+
+  /// Moves the current position back to `mark` and removes the nodes recorded after it.
+  /// The recorded nodes are not empty, hence those read after `mark` are the last ones and they end after `mark`.
+  pub fn rewind(&mut self, mark: S) {
+    if let Some(ref mut cst) = self.cst {
+      while cst.nodes.last().map_or(false, |node| node.end > mark) {
+        cst.nodes.pop();
+      }
+      if self.current > cst.examined {
+        cst.examined = self.current.clone();
+      }
+    }
+    self.current = mark;
+  }
+
+  /// Records a node of kind `kind` from `start` to the current position if the state is successful and the node is not empty. It is called by the rule functions of the grammars declared with `#![cst]`, and does nothing if the tree is not requested.
+  pub fn record_node(mut self, kind: &'static str, start: S) -> Self {
+    if self.is_successful() && start < self.current {
+      if let Some(ref mut cst) = self.cst {
+        let examined = ::std::cmp::max(&cst.examined, &self.farthest_read).clone();
+        let end = self.current.clone();
+        cst.nodes.push(RecordedNode { kind, start, examined: ::std::cmp::max(examined, end.clone()), end, green: None });
+      }
+    }
+    self
+  }
+
+  /// The index of the reusable node of kind `kind` starting at the current position, if any.
+  pub fn reusable_node(&self, kind: &'static str) -> Option<usize> {
+    let reusable = match self.cst {
+      Some(ref cst) => &cst.reusable,
+      None => return None
+    };
+    let first = reusable.partition_point(|node| node.start < self.current);
+    reusable[first..].iter()
+      .take_while(|node| node.start == self.current)
+      .position(|node| node.kind == kind)
+      .map(|pos| first + pos)
+  }
+
+  /// Skips the input of the reusable node at `index` (see `reusable_node`) and records it, instead of calling the rule of this node. It is called by the recognizers of the grammars declared with `#![cst]`.
+  pub fn reuse_node(mut self, index: usize) -> ParseStateWith<'e, S, ()> {
+    {
+      let cst = self.cst.as_mut().expect("Reusing a node is only possible when the concrete syntax tree is recorded.");
+      let node = cst.reusable[index].clone();
+      if node.examined > cst.examined {
+        cst.examined = node.examined.clone();
+      }
+      self.current = node.end.clone();
+      cst.nodes.push(node);
     }
+    self.success(())
+  }
+
+  /// The concrete syntax tree of the input read from `start`, its root is of kind `root` (see `cst::build_tree`).
+  /// Only the root is created if the tree is not recorded (see `with_cst`).
+  pub fn syntax_tree(&self, root: &'static str, start: S) -> SyntaxNode where
+   Range<S>: StreamText + StreamSpan<Output=::Span>
+  {
+    let offset = Range { start: start.clone(), end: start.clone() }.stream_span().lo.0 as usize;
+    let nodes = self.cst.as_ref().map_or(&[][..], |cst| &cst.nodes[..]);
+    let green = build_tree(root, nodes, start, self.current.clone());
+    SyntaxNode::new_root(Rc::new(green), offset)
   }
 
   /// Transforms `self` into a more usable `ParseResult` value. It is useful when the state is terminal or if the state will not be further transformed.
-  pub fn into_result(self) -> ParseResult<S, T> {
-    let expectation = ParseExpectation::new(self.farthest_read, self.expected);
+  pub fn into_result(self) -> ParseResult<'e, S, T> {
     match self.data {
       Some(data) => {
         if self.current.has_next() {
-          Partial(data, expectation)
+          Partial(data, ParseExpectation::new(self.farthest_read, self.expected))
         }
         else {
           Success(data)
@@ -219,21 +451,57 @@ impl<S, T> ParseState<S, T> where
       }
       None => {
         assert!(self.failed, "Failure status must be true when extracting a failed result.");
-        Failure(expectation)
+        Failure(ParseExpectation::new(self.farthest_read, self.expected))
       }
     }
   }
 
-  pub fn extract_data(self) -> (ParseState<S, ()>, T) {
+  /// Returns the data if the state is successful and the full input has been consumed. It is used by the entry point `parse` generated with `#![start(rule)]`.
+  /// When the input is partially consumed, the error is reported at the farthest position read, which might be beyond the end of the data parsed.
+  pub fn into_full_result(self) -> Result<T, ParseError> where
+   S: Location + CodeSnippet
+  {
+    if self.data.is_none() || !self.current.has_next() {
+      return self.into_prefix_result().map(|(data, _)| data);
+    }
+    if self.farthest_read > self.current {
+      return Err(ParseExpectation::new(self.farthest_read, self.expected).into_error());
+    }
+    let mut expected: Vec<String> =
+      if self.farthest_read == self.current { self.expected.names().into_iter().map(String::from).collect() }
+      else { vec![] };
+    expected.push("<end-of-input>".to_string());
+    Err(ParseError {
+      location: self.current.location(),
+      unexpected: self.current.code_snippet(10usize),
+      expected: expected
+    })
+  }
+
+  /// Returns the data and the stream positioned after the last item read if the state is successful. It is used by the entry point `parse_prefix` generated with `#![start(rule)]`.
+  pub fn into_prefix_result(self) -> Result<(T, S), ParseError> where
+   S: Location + CodeSnippet
+  {
+    match self.data {
+      Some(data) => Ok((data, self.current)),
+      None => {
+        assert!(self.failed, "Failure status must be true when extracting a failed result.");
+        Err(ParseExpectation::new(self.farthest_read, self.expected).into_error())
+      }
+    }
+  }
+
+  pub fn extract_data(self) -> (ParseStateWith<'e, S, ()>, T) {
     assert!(self.is_successful() && self.data.is_some(),
       "Data extraction is only possible if the state is successful and contains data.");
     let data = self.data.unwrap();
-    let state = ParseState {
+    let state = ParseStateWith {
       farthest_read: self.farthest_read,
       expected: self.expected,
       failed: self.failed,
       current: self.current,
-      data: None
+      data: None,
+      cst: self.cst
     };
     (state, data)
   }
@@ -243,7 +511,7 @@ impl<S, T> ParseState<S, T> where
   }
 }
 
-impl<S> ParseState<S, ()>
+impl<'e, S> ParseStateWith<'e, S, ()>
 {
   // This is specific to recognizer where unit data does not need to be extracted. We also want to preserve the "no-data" precondition of `restore`.
   pub fn discard_data(&mut self) {
@@ -251,7 +519,7 @@ impl<S> ParseState<S, ()>
   }
 }
 
-impl<S, T, I> Iterator for ParseState<S, T> where
+impl<'e, S, T, I> Iterator for ParseStateWith<'e, S, T> where
  S: Iterator<Item=I>
 {
   type Item = I;
@@ -260,10 +528,137 @@ impl<S, T, I> Iterator for ParseState<S, T> where
   }
 }
 
-impl<S, T, P> ConsumePrefix<P> for ParseState<S, T> where
+impl<'e, S, T, P> ConsumePrefix<P> for ParseStateWith<'e, S, T> where
   S: ConsumePrefix<P>
 {
   fn consume_prefix(&mut self, prefix: P) -> bool {
     self.current.consume_prefix(prefix)
   }
 }
+
+impl<'e, S, T, P> ConsumePrefixIgnoreCase<P> for ParseStateWith<'e, S, T> where
+  S: ConsumePrefixIgnoreCase<P>
+{
+  fn consume_prefix_ignore_case(&mut self, prefix: P) -> bool {
+    self.current.consume_prefix_ignore_case(prefix)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  static TABLE1: [&'static str; 3] = ["a", "b", "c"];
+  static TABLE2: [&'static str; 2] = ["x", "a"];
+
+  #[test]
+  fn test_expected_set() {
+    let mut expected = ExpectedSet::new();
+    assert!(expected.is_empty());
+    expected.insert(&TABLE1, 2);
+    expected.insert(&TABLE1, 0);
+    expected.insert(&TABLE1, 2);
+    assert_eq!(expected.names(), vec!["a", "c"]);
+    expected.insert(&TABLE2, 0);
+    expected.insert(&TABLE2, 1);
+    assert_eq!(expected.names(), vec!["a", "c", "x"]);
+    expected.clear();
+    assert!(expected.is_empty());
+    assert!(expected.names().is_empty());
+  }
+
+  #[test]
+  fn test_farthest_error() {
+    let mut state: ParseState<_, ()> = "ab".into_state();
+    state.error(&TABLE1, 1);
+    state.error(&TABLE1, 1);
+    assert_eq!(state.expected.names(), vec!["b"]);
+    let mark = state.current.clone();
+    let mut state = state.restore(mark);
+    state.next();
+    state.error(&TABLE1, 2);
+    assert_eq!(state.expected.names(), vec!["c"]);
+  }
+
+  #[test]
+  fn test_expectation() {
+    // A table built at run time, the expectation borrows it until the error is displayed.
+    let table: Vec<&str> = vec!["a", "b"];
+    let mut state: ParseStateWith<_, ()> = ParseStateWith::new("ab".stream());
+    state.error(&table, 1);
+    state.error(&table, 0);
+    match state.into_result() {
+      Failure(expectation) => {
+        assert_eq!(expectation.expected(), vec!["a", "b"]);
+        assert_eq!(format!("{:?}", expectation), "1:1: unexpected `ab`, expecting `a` or `b`.");
+      }
+      _ => panic!("the state is failed")
+    }
+  }
+
+  #[test]
+  fn test_full_result() {
+    let state: ParseState<_, ()> = "ab".into_state();
+    let mark = state.mark();
+    let mut state = state.success(());
+    state.next();
+    let err = state.into_full_result().unwrap_err();
+    assert_eq!(err.location, "1:2");
+    assert_eq!(err.expected, vec!["<end-of-input>"]);
+    assert_eq!(format!("{}", err), "1:2: unexpected `b`, expecting `<end-of-input>`.");
+
+    let mut state: ParseState<_, ()> = "ab".into_state();
+    state.next();
+    state.next();
+    state.error(&TABLE1, 2);
+    let mut state = state.restore(mark).success(());
+    state.next();
+    let err = state.into_full_result().unwrap_err();
+    assert_eq!(err.location, "1:3");
+    assert_eq!(err.expected, vec!["c"]);
+
+    let mut state: ParseState<_, char> = "ab".into_state();
+    state.next();
+    state.next();
+    let state = state.success('a');
+    assert_eq!(state.into_full_result(), Ok('a'));
+  }
+
+  #[test]
+  fn test_prefix_result() {
+    let mut state: ParseState<_, ()> = "ab".into_state();
+    state.next();
+    let (_, rest) = state.success('a').into_prefix_result().unwrap();
+    assert_eq!(rest.bytes_offset(), 1);
+    let mut state: ParseState<_, ()> = "ab".into_state();
+    state.error(&TABLE1, 0);
+    let err = state.failure::<()>().into_prefix_result().unwrap_err();
+    assert_eq!(format!("{}", err), "1:1: unexpected `ab`, expecting `a`.");
+  }
+
+  #[test]
+  fn test_record_nodes() {
+    let state: ParseState<_, ()> = "ab c".into_state().with_cst();
+    let start = state.mark();
+    let mut state = state.record_node("empty", start.clone());
+    state.next();
+    let mut state = state.record_node("a", start.clone());
+    let mark = state.mark();
+    state.next();
+    let mut state = state.record_node("b", mark.clone());
+    state.error(&TABLE1, 2);
+    let mut state = state.failure::<()>().record_node("failed", start.clone()).restore_from_failure(mark);
+    assert_eq!(state.cst.as_ref().unwrap().nodes.iter().map(|node| node.kind).collect::<Vec<_>>(), vec!["a"]);
+    state.next();
+    state.next();
+    state.next();
+    let state = state.record_node("abc", start.clone());
+    assert_eq!(format!("{:?}", state.syntax_tree("abc", start)),
+      "abc@0..4\n  a@0..1\n    \"a\"@0..1\n  \"b c\"@1..4\n");
+    // Without `with_cst`, the nodes are not recorded.
+    let mut state: ParseState<_, ()> = "a".into_state();
+    let start = state.mark();
+    state.next();
+    assert!(state.record_node("a", start).cst.is_none());
+  }
+}
@@ -0,0 +1,394 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Printing of a concrete syntax tree (see `cst`) as a sequence of tokens separated by spacing.
+//!
+//! The layout of the nodes of each rule is computed from the grammar when it is compiled with `#![cst]`:
+//!
+//!  * the text of a `Trivia` node, such as spacing or comments, is gathered between two tokens,
+//!  * the text of a `Token` node, such as a number or an identifier, is a single token formatted by the user,
+//!  * the children of a `Structure` node are matched against the expression of its rule, each literal is a token and the text matched between them is a single token.
+//!
+//! The literals are printed with their text in the tree, for instance `LET` for `"let"i`.
+//! If the children of a node do not match its rule, for instance after a transformation of the tree, its tokens are printed as they are.
+//! Between two tokens, the printer asks for the spacing with the previous token, the trivia found between them and the next token.
+//! The printer of a grammar is generated in its module `printer`.
+
+use cst::*;
+use unicode::simple_fold;
+use vm::CharacterClass;
+
+/// A string literal of a rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Literal
+{
+  pub text: &'static str,
+  pub ignore_case: bool
+}
+
+impl Literal
+{
+  /// The number of items of `items` matched by the literal, if they start with it.
+  fn match_len(&self, items: &[Item]) -> Option<usize> {
+    let mut items = items.iter();
+    for p in self.text.chars() {
+      match items.next() {
+        Some(&Item::Char(c)) if c == p || (self.ignore_case && simple_fold(c) == simple_fold(p)) => (),
+        _ => return None
+      }
+    }
+    Some(self.text.chars().count())
+  }
+}
+
+/// The expression of a rule, without the semantic actions.
+#[derive(Clone, Copy, Debug)]
+pub enum Pattern
+{
+  Literal(Literal),
+  AnySingleChar,
+  CharacterClass(&'static CharacterClass),
+  /// A node of the rule `kind`, `true` if the rule matches the empty input and thus its node can be absent.
+  Rule(&'static str, bool),
+  /// An external parser, it matches the text up to the next node.
+  External,
+  Sequence(&'static [Pattern]),
+  Choice(&'static [Pattern]),
+  ZeroOrMore(&'static Pattern),
+  OneOrMore(&'static Pattern),
+  ZeroOrOne(&'static Pattern),
+  NotPredicate(&'static Pattern),
+  AndPredicate(&'static Pattern)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum RuleLayout
+{
+  Trivia,
+  Token,
+  Structure(&'static Pattern)
+}
+
+pub trait PrintRules
+{
+  fn layout(&self, kind: &str) -> RuleLayout;
+  fn format_token(&mut self, kind: &str, text: &str) -> String;
+  /// The text printed between `previous` and `next`, `previous` is empty before the first token and `next` is empty after the last one.
+  fn spacing(&mut self, previous: &str, trivia: &str, next: &str) -> String;
+}
+
+pub fn print<R: PrintRules + ?Sized>(rules: &mut R, tree: &SyntaxNode) -> String {
+  let mut printer = TreePrinter {
+    rules,
+    output: String::new(),
+    previous: String::new(),
+    trivia: String::new()
+  };
+  printer.print_node(tree);
+  printer.finish()
+}
+
+struct TreePrinter<'a, R: PrintRules + ?Sized + 'a>
+{
+  rules: &'a mut R,
+  output: String,
+  previous: String,
+  trivia: String
+}
+
+impl<'a, R: PrintRules + ?Sized> TreePrinter<'a, R>
+{
+  fn print_node(&mut self, node: &SyntaxNode) {
+    match self.rules.layout(node.kind()) {
+      RuleLayout::Trivia => self.trivia.push_str(&node.text()),
+      RuleLayout::Token => {
+        // The trivia nested in a token, for instance `number = ["0-9"]+ spacing`, separates its text in several tokens.
+        let mut text = String::new();
+        for child in node.children_with_tokens() {
+          match child {
+            SyntaxElement::Token(token) => text.push_str(token.text()),
+            SyntaxElement::Node(child) => {
+              self.format_token(node.kind(), &mut text);
+              self.print_node(&child);
+            }
+          }
+        }
+        self.format_token(node.kind(), &mut text);
+      }
+      RuleLayout::Structure(pattern) => {
+        let items: Vec<Item> = node.children_with_tokens().into_iter()
+          .flat_map(|child| match child {
+            SyntaxElement::Token(token) => token.text().chars().map(Item::Char).collect(),
+            SyntaxElement::Node(child) => vec![Item::Node(child)]
+          })
+          .collect();
+        let mut matcher = PatternMatcher { items: &items, pieces: vec![] };
+        if matcher.matches(pattern, 0) == Some(items.len()) {
+          self.print_pieces(&items, matcher.pieces);
+        }
+        else {
+          let pieces = (0..items.len()).map(|idx| match items[idx] {
+            Item::Char(_) => Piece::Text(idx),
+            Item::Node(_) => Piece::Node(idx)
+          });
+          self.print_pieces(&items, pieces.collect());
+        }
+      }
+    }
+  }
+
+  fn format_token(&mut self, kind: &str, text: &mut String) {
+    if !text.is_empty() {
+      let formatted = self.rules.format_token(kind, text);
+      self.print_token(&formatted);
+      text.clear();
+    }
+  }
+
+  /// Prints each literal as a token, the characters between two literals or nodes are printed as a single token.
+  fn print_pieces(&mut self, items: &[Item], pieces: Vec<Piece>) {
+    let mut text = String::new();
+    for piece in pieces {
+      match piece {
+        Piece::Text(idx) => text.push(items[idx].char()),
+        Piece::Literal(start, end) => {
+          self.print_text(&mut text);
+          let literal: String = items[start..end].iter().map(Item::char).collect();
+          self.print_token(&literal);
+        }
+        Piece::Node(idx) => {
+          self.print_text(&mut text);
+          if let Item::Node(ref child) = items[idx] {
+            self.print_node(child);
+          }
+        }
+      }
+    }
+    self.print_text(&mut text);
+  }
+
+  fn print_text(&mut self, text: &mut String) {
+    if !text.is_empty() {
+      self.print_token(text);
+      text.clear();
+    }
+  }
+
+  fn print_token(&mut self, token: &str) {
+    let spacing = self.rules.spacing(&self.previous, &self.trivia, token);
+    self.output.push_str(&spacing);
+    self.output.push_str(token);
+    self.previous = token.to_string();
+    self.trivia.clear();
+  }
+
+  fn finish(mut self) -> String {
+    let spacing = self.rules.spacing(&self.previous, &self.trivia, "");
+    self.output.push_str(&spacing);
+    self.output
+  }
+}
+
+/// The children of a node, its tokens are split into characters.
+enum Item
+{
+  Char(char),
+  Node(SyntaxNode)
+}
+
+impl Item
+{
+  fn char(&self) -> char {
+    match self {
+      &Item::Char(c) => c,
+      &Item::Node(_) => unreachable!("a node is not a character of a token.")
+    }
+  }
+}
+
+/// The parts of the children matched by a pattern, given by the indexes of their items.
+enum Piece
+{
+  Text(usize),
+  Literal(usize, usize),
+  Node(usize)
+}
+
+/// Matches the children of a node against the pattern of its rule as the parser does, the choices are ordered and the repetitions are greedy.
+/// A rule called in a syntactic predicate does not record its node, thus the predicates calling rules are always satisfied (see the generator of the printer).
+struct PatternMatcher<'a>
+{
+  items: &'a [Item],
+  pieces: Vec<Piece>
+}
+
+impl<'a> PatternMatcher<'a>
+{
+  /// The position after the items matched by `pattern` from `pos`, the pieces are only kept if it matches.
+  fn matches(&mut self, pattern: &Pattern, pos: usize) -> Option<usize> {
+    let mark = self.pieces.len();
+    let next = self.match_pattern(pattern, pos);
+    if next.is_none() {
+      self.pieces.truncate(mark);
+    }
+    next
+  }
+
+  fn match_pattern(&mut self, pattern: &Pattern, pos: usize) -> Option<usize> {
+    match *pattern {
+      Pattern::Literal(literal) => {
+        let len = literal.match_len(&self.items[pos..])?;
+        self.pieces.push(Piece::Literal(pos, pos + len));
+        Some(pos + len)
+      }
+      Pattern::AnySingleChar => self.match_char(pos, |_| true),
+      Pattern::CharacterClass(class) => self.match_char(pos, |c| class.contains(c)),
+      Pattern::Rule(kind, nullable) => {
+        match self.items.get(pos) {
+          Some(&Item::Node(ref node)) if node.kind() == kind => {
+            self.pieces.push(Piece::Node(pos));
+            Some(pos + 1)
+          }
+          _ if nullable => Some(pos),
+          _ => None
+        }
+      }
+      Pattern::External => {
+        let mut pos = pos;
+        while let Some(next) = self.match_char(pos, |_| true) {
+          pos = next;
+        }
+        Some(pos)
+      }
+      Pattern::Sequence(patterns) => {
+        let mut pos = pos;
+        for pattern in patterns {
+          pos = self.matches(pattern, pos)?;
+        }
+        Some(pos)
+      }
+      Pattern::Choice(patterns) => patterns.iter().filter_map(|pattern| self.matches(pattern, pos)).next(),
+      Pattern::ZeroOrMore(pattern) => Some(self.repeat(pattern, pos)),
+      Pattern::OneOrMore(pattern) => {
+        let pos = self.matches(pattern, pos)?;
+        Some(self.repeat(pattern, pos))
+      }
+      Pattern::ZeroOrOne(pattern) => Some(self.matches(pattern, pos).unwrap_or(pos)),
+      Pattern::NotPredicate(pattern) => if self.predicate(pattern, pos) { None } else { Some(pos) },
+      Pattern::AndPredicate(pattern) => if self.predicate(pattern, pos) { Some(pos) } else { None }
+    }
+  }
+
+  fn match_char<F: Fn(char) -> bool>(&mut self, pos: usize, accept: F) -> Option<usize> {
+    match self.items.get(pos) {
+      Some(&Item::Char(c)) if accept(c) => {
+        self.pieces.push(Piece::Text(pos));
+        Some(pos + 1)
+      }
+      _ => None
+    }
+  }
+
+  fn repeat(&mut self, pattern: &Pattern, mut pos: usize) -> usize {
+    while let Some(next) = self.matches(pattern, pos) {
+      if next == pos { break; }
+      pos = next;
+    }
+    pos
+  }
+
+  fn predicate(&mut self, pattern: &Pattern, pos: usize) -> bool {
+    let mark = self.pieces.len();
+    let matched = self.matches(pattern, pos).is_some();
+    self.pieces.truncate(mark);
+    matched
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::rc::Rc;
+
+  static DIGITS: CharacterClass = CharacterClass { intervals: &[('0', '9')], sets: &[], negated: false };
+
+  // binding = spacing "let"i spacing ident "=" ["0-9"]+ spacing
+  static BINDING: Pattern = Pattern::Sequence(&[
+    Pattern::Rule("spacing", true),
+    Pattern::Literal(Literal { text: "let", ignore_case: true }),
+    Pattern::Rule("spacing", true),
+    Pattern::Rule("ident", false),
+    Pattern::Literal(Literal { text: "=", ignore_case: false }),
+    Pattern::OneOrMore(&Pattern::CharacterClass(&DIGITS)),
+    Pattern::Rule("spacing", true)
+  ]);
+
+  // value = "=" (!" " .)+
+  static VALUE: Pattern = Pattern::Sequence(&[
+    Pattern::Literal(Literal { text: "=", ignore_case: false }),
+    Pattern::OneOrMore(&Pattern::Sequence(&[
+      Pattern::NotPredicate(&Pattern::Literal(Literal { text: " ", ignore_case: false })),
+      Pattern::AnySingleChar
+    ]))
+  ]);
+
+  struct Rules;
+
+  impl PrintRules for Rules {
+    fn layout(&self, kind: &str) -> RuleLayout {
+      match kind {
+        "spacing" => RuleLayout::Trivia,
+        "ident" => RuleLayout::Token,
+        "binding" => RuleLayout::Structure(&BINDING),
+        _ => RuleLayout::Structure(&VALUE)
+      }
+    }
+
+    fn format_token(&mut self, _kind: &str, text: &str) -> String {
+      text.to_uppercase()
+    }
+
+    fn spacing(&mut self, previous: &str, trivia: &str, next: &str) -> String {
+      if previous.is_empty() || next.is_empty() { String::new() }
+      else { format!("[{}]", trivia) }
+    }
+  }
+
+  fn token(text: &str) -> GreenElement {
+    GreenElement::Token(text.into())
+  }
+
+  fn node(kind: &'static str, children: Vec<GreenElement>) -> GreenElement {
+    GreenElement::Node(Rc::new(GreenNode::new(kind, children)))
+  }
+
+  fn print_root(kind: &'static str, children: Vec<GreenElement>) -> String {
+    let tree = SyntaxNode::new_root(Rc::new(GreenNode::new(kind, children)), 0);
+    print(&mut Rules, &tree)
+  }
+
+  #[test]
+  fn test_print() {
+    let spacing = |text| node("spacing", vec![token(text)]);
+    assert_eq!(print_root("binding", vec![
+      spacing(" "), token("LeT"), spacing(" "),
+      node("ident", vec![token("x"), spacing(" ")]),
+      token("=1"), spacing("\n")
+    ]), "LeT[ ]X[ ]=[]1");
+    // The second `=` is matched by `.`, it is not a literal.
+    assert_eq!(print_root("value", vec![token("=a=b")]), "=[]a=b");
+    // The children do not match the rule, the token is printed as it is.
+    assert_eq!(print_root("binding", vec![token("x=1")]), "x=1");
+  }
+}
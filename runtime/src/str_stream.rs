@@ -16,6 +16,7 @@
 
 use stream::*;
 use std::cmp::{Ordering, min};
+use unicode::simple_fold;
 use super::*;
 pub use std::ops::Range;
 pub use syntex_pos::Span;
@@ -47,9 +48,15 @@ pub struct StrStream<'a>
 impl<'a> StrStream<'a>
 {
   fn new(raw_data: &'a str) -> StrStream<'a> {
+    StrStream::at(raw_data, 0)
+  }
+
+  /// The stream positioned at the byte `bytes_offset` of `raw_data`, which must be at a character boundary.
+  pub(crate) fn at(raw_data: &'a str, bytes_offset: usize) -> StrStream<'a> {
+    debug_assert!(raw_data.is_char_boundary(bytes_offset));
     StrStream {
       raw_data: raw_data,
-      bytes_offset: 0
+      bytes_offset: bytes_offset
     }
   }
 
@@ -146,9 +153,9 @@ impl<'a> CodeSnippet for StrStream<'a>
   }
 }
 
-impl<'a> ConsumePrefix<&'static str> for StrStream<'a>
+impl<'a, 'b> ConsumePrefix<&'b str> for StrStream<'a>
 {
-  fn consume_prefix(&mut self, prefix: &'static str) -> bool {
+  fn consume_prefix(&mut self, prefix: &'b str) -> bool {
     let current_offset = self.bytes_offset;
     let end_offset = current_offset + prefix.len();
     if end_offset <= self.raw_data.len()
@@ -162,6 +169,24 @@ impl<'a> ConsumePrefix<&'static str> for StrStream<'a>
   }
 }
 
+impl<'a, 'b> ConsumePrefixIgnoreCase<&'b str> for StrStream<'a>
+{
+  fn consume_prefix_ignore_case(&mut self, prefix: &'b str) -> bool {
+    let mut input = self.raw_data[self.bytes_offset..].char_indices();
+    let mut end_offset = 0;
+    for p in prefix.chars() {
+      match input.next() {
+        Some((offset, c)) if simple_fold(c) == simple_fold(p) => {
+          end_offset = offset + c.len_utf8();
+        }
+        _ => return false
+      }
+    }
+    self.bytes_offset += end_offset;
+    true
+  }
+}
+
 impl<'a> HasNext for StrStream<'a>
 {
   fn has_next(&self) -> bool {
@@ -179,6 +204,14 @@ impl<'a> StreamSpan for Range<StrStream<'a>>
   }
 }
 
+impl<'a> StreamText for Range<StrStream<'a>>
+{
+  fn stream_text(&self) -> String {
+    self.start.assert_same_raw_data(&self.end);
+    self.start.raw_data[self.start.bytes_offset..self.end.bytes_offset].to_string()
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
@@ -201,6 +234,25 @@ mod test {
     consume_prefix_test(s1, "z", false, Some('a'));
   }
 
+  fn consume_prefix_ignore_case_test<'a>(stream: &StrStream<'a>, prefix: &'static str,
+    prefix_match: bool, next_char: Option<char>)
+  {
+    let mut s2 = stream.clone();
+    assert_eq!(s2.consume_prefix_ignore_case(prefix), prefix_match);
+    assert!(s2.next() == next_char);
+  }
+
+  #[test]
+  fn test_consume_prefix_ignore_case() {
+    let s1 = &"SeLect ÉTÉ".stream();
+    consume_prefix_ignore_case_test(s1, "select", true, Some(' '));
+    consume_prefix_ignore_case_test(s1, "SELECT ÉTÉ", true, None);
+    consume_prefix_ignore_case_test(s1, "select été", true, None);
+    consume_prefix_ignore_case_test(s1, "", true, Some('S'));
+    consume_prefix_ignore_case_test(s1, "selected", false, Some('S'));
+    consume_prefix_ignore_case_test(s1, "sel_", false, Some('S'));
+  }
+
   fn test_str_stream<'a, I>(mut s1: StrStream<'a>, chars: I) where
    I: Iterator<Item=char>
   {
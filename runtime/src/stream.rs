@@ -41,6 +41,12 @@ pub trait ConsumePrefix<P>
   fn consume_prefix(&mut self, prefix: P) -> bool;
 }
 
+/// Consumes `prefix` if it matches from the current position in the stream up to the case of the characters (with the Unicode simple case folding). If it does not match, the stream is not altered and `false` is returned.
+pub trait ConsumePrefixIgnoreCase<P>
+{
+  fn consume_prefix_ignore_case(&mut self, prefix: P) -> bool;
+}
+
 /// Returns `true` if an item can be read from the stream with `Iterator::next`.
 pub trait HasNext
 {
@@ -52,3 +58,9 @@ pub trait StreamSpan
   type Output;
   fn stream_span(&self) -> Self::Output;
 }
+
+/// The text read in a range of the stream, used to build the concrete syntax trees (see `cst`).
+pub trait StreamText
+{
+  fn stream_text(&self) -> String;
+}
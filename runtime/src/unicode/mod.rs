@@ -0,0 +1,90 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Unicode sets used by the character classes of the grammars, such as `["\\p{L}"]` or `["\\p{XID_Start}"]`.
+//! The generated code calls `contains(XID_START, c)` to test if a character belongs to a set.
+//! It also provides the simple case folding used by the case-insensitive string literals (`"select"i`).
+
+pub use self::tables::*;
+
+mod tables;
+
+/// `true` if `c` is in one of the intervals of `table`, which must be sorted and disjoint.
+pub fn contains(table: &[(char, char)], c: char) -> bool {
+  table.binary_search_by(|&(lo, hi)| {
+    if hi < c { std::cmp::Ordering::Less }
+    else if lo > c { std::cmp::Ordering::Greater }
+    else { std::cmp::Ordering::Equal }
+  }).is_ok()
+}
+
+/// The table of the Unicode set `name` (e.g. `"Lu"` or `"XID_Start"`), used when the grammar is not compiled.
+pub fn set(name: &str) -> Option<&'static [(char, char)]> {
+  let table = match name {
+    "L" => L, "Lu" => LU, "Ll" => LL, "Lt" => LT, "Lm" => LM, "Lo" => LO,
+    "M" => M, "Mn" => MN, "Mc" => MC, "Me" => ME,
+    "N" => N, "Nd" => ND, "Nl" => NL, "No" => NO,
+    "P" => P, "Pc" => PC, "Pd" => PD, "Ps" => PS, "Pe" => PE, "Pi" => PI, "Pf" => PF, "Po" => PO,
+    "S" => S, "Sm" => SM, "Sc" => SC, "Sk" => SK, "So" => SO,
+    "Z" => Z, "Zs" => ZS, "Zl" => ZL, "Zp" => ZP,
+    "Cc" => CC, "Cf" => CF,
+    "XID_Start" => XID_START, "XID_Continue" => XID_CONTINUE,
+    _ => return None
+  };
+  Some(table)
+}
+
+/// The simple case folding of `c`, two characters are equal up to the case if they have the same folding.
+pub fn simple_fold(c: char) -> char {
+  match CASE_FOLDING.binary_search_by_key(&c, |&(from, _)| from) {
+    Ok(i) => CASE_FOLDING[i].1,
+    Err(_) => c
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_contains() {
+    assert!(contains(L, 'a'));
+    assert!(contains(L, 'é'));
+    assert!(!contains(L, '1'));
+    assert!(contains(ND, '٣'));
+    assert!(contains(XID_START, 'x'));
+    assert!(!contains(XID_START, '_'));
+    assert!(contains(XID_CONTINUE, '_'));
+    assert!(!contains(XID_CONTINUE, '-'));
+    assert!(!contains(&[], 'a'));
+  }
+
+  #[test]
+  fn test_set() {
+    assert!(contains(set("Lu").unwrap(), 'A'));
+    assert!(!contains(set("Lu").unwrap(), 'a'));
+    assert!(contains(set("XID_Start").unwrap(), 'x'));
+    assert!(set("Unknown").is_none());
+  }
+
+  #[test]
+  fn test_simple_fold() {
+    assert_eq!(simple_fold('A'), 'a');
+    assert_eq!(simple_fold('a'), 'a');
+    assert_eq!(simple_fold('ſ'), 's');
+    assert_eq!(simple_fold('ς'), simple_fold('Σ'));
+    assert_eq!(simple_fold('ẞ'), 'ß');
+    assert_eq!(simple_fold('1'), '1');
+  }
+}
@@ -0,0 +1,432 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Virtual machine executing the grammars compiled with `#![bytecode]`.
+//! The rules are compiled to a static array of instructions in the style of LPeg: the choices push a backtrack point on a stack, and a failure returns to the last one.
+//!
+//! The values are built on a stack of untyped `Value`s: a parsing expression pushes one value per component of its type, for instance a character class pushes its character, and `e*` pushes a list.
+//! The generated code converts them back to their Rust types, when calling a semantic action (through a table of functions indexed by the `Action` instructions) and in the parser function of each rule.
+
+use parse_state::*;
+use stream::*;
+use unicode;
+
+use std::any::Any;
+use std::ops::Range;
+use std::vec;
+
+/// The addresses are indexes in the array of instructions.
+#[derive(Clone, Copy, Debug)]
+pub enum Instruction
+{
+  /// Consumes the literal, the number is the index of the literal in the expectation table.
+  Literal(&'static str, usize),
+  LiteralIgnoreCase(&'static str, usize),
+  /// Consumes a character, which is pushed on the value stack if the flag is `true`.
+  AnySingleChar(usize, bool),
+  CharacterClass(&'static CharacterClass, usize, bool),
+  /// Pushes a backtrack point: if an instruction fails before it is discarded, the position and the values are restored and the execution continues at the address.
+  Choice(usize),
+  /// Discards the last backtrack point and jumps to the address.
+  Commit(usize),
+  /// Discards the last backtrack point after restoring its position and jumps to the address, it succeeds the syntactic predicate `&e`.
+  BackCommit(usize),
+  /// Discards the last backtrack point and fails, it fails the syntactic predicate `!e`.
+  FailTwice,
+  Fail,
+  Call(usize),
+  Return,
+  /// Calls the external parser at this index of the external parsers, its value is pushed if the flag is `true`.
+  External(usize, bool),
+  /// Calls the semantic action at this index of the action table, it replaces its arguments by its result on the value stack.
+  Action(usize),
+  PushList,
+  /// Appends the `n` last values (as a tuple if `n > 1`) to the list pushed before them.
+  Append(usize),
+  PushNone,
+  /// Replaces the `n` last values by an optional value containing them.
+  PushSome(usize),
+  /// Saves the current position, the start of the next `Span`.
+  Mark,
+  /// Inserts the span from the last mark to the current position below the `n` last values.
+  Span(usize)
+}
+
+#[derive(Debug)]
+pub struct CharacterClass
+{
+  pub intervals: &'static [(char, char)],
+  /// The Unicode sets of `unicode`.
+  pub sets: &'static [&'static &'static [(char, char)]],
+  pub negated: bool
+}
+
+impl CharacterClass
+{
+  pub fn contains(&self, c: char) -> bool {
+    let contained = self.intervals.iter().any(|&(lo, hi)| lo <= c && c <= hi)
+      || self.sets.iter().any(|set| unicode::contains(set, c));
+    contained != self.negated
+  }
+}
+
+/// The value of a parsing expression before its conversion to its Rust type.
+/// The values of the semantic actions, the external parsers and the spans have any Rust type, and thus must be `'static`.
+#[derive(Debug)]
+pub enum Value
+{
+  Char(char),
+  List(Vec<Value>),
+  Optional(Option<Box<Value>>),
+  Tuple(Vec<Value>),
+  Rust(Box<dyn Any>)
+}
+
+impl Value
+{
+  pub fn rust<T: 'static>(value: T) -> Value {
+    Value::Rust(Box::new(value))
+  }
+
+  pub fn into_char(self) -> char {
+    match self {
+      Value::Char(c) => c,
+      value => type_mismatch("a character", value)
+    }
+  }
+
+  pub fn into_rust<T: 'static>(self) -> T {
+    match self {
+      Value::Rust(value) => match value.downcast() {
+        Ok(value) => *value,
+        Err(_) => panic!("[bug] the value of the bytecode does not have the expected Rust type (please report this issue).")
+      },
+      value => type_mismatch("a Rust value", value)
+    }
+  }
+
+  pub fn into_list<T, F>(self, f: F) -> Vec<T> where
+   F: FnMut(Value) -> T
+  {
+    match self {
+      Value::List(values) => values.into_iter().map(f).collect(),
+      value => type_mismatch("a list", value)
+    }
+  }
+
+  pub fn into_optional<T, F>(self, f: F) -> Option<T> where
+   F: FnOnce(Value) -> T
+  {
+    match self {
+      Value::Optional(value) => value.map(|value| f(*value)),
+      value => type_mismatch("an optional value", value)
+    }
+  }
+
+  /// The components of a tuple, in order.
+  pub fn into_fields(self) -> vec::IntoIter<Value> {
+    match self {
+      Value::Tuple(values) => values.into_iter(),
+      value => type_mismatch("a tuple", value)
+    }
+  }
+}
+
+fn type_mismatch(expected: &str, value: Value) -> ! {
+  panic!("[bug] expected {} in the bytecode but found `{:?}` (please report this issue).", expected, value)
+}
+
+/// Removes the `n` last values of the stack and returns them in order, they are the arguments of a semantic action.
+pub fn pop_values(values: &mut Vec<Value>, n: usize) -> vec::IntoIter<Value> {
+  let len = values.len();
+  values.split_off(len - n).into_iter()
+}
+
+/// The `n` last values as a single value.
+fn pop_value(values: &mut Vec<Value>, n: usize) -> Value {
+  if n == 1 {
+    values.pop().expect("[bug] the value stack of the bytecode is empty (please report this issue).")
+  }
+  else {
+    Value::Tuple(pop_values(values, n).collect())
+  }
+}
+
+/// A semantic action pops its arguments and pushes its result.
+pub type Action = fn(&mut Vec<Value>);
+
+/// An external parser, see `external`.
+pub type External<S> = fn(ParseState<S, ()>) -> ParseState<S, Value>;
+
+/// Wraps the data of the external parser into a `Value`.
+pub fn external<S, T: 'static>(state: ParseState<S, T>) -> ParseState<S, Value> where
+ S: Ord + Clone + HasNext
+{
+  map(state, Value::rust)
+}
+
+/// Converts the data of the state if it is successful.
+pub fn map<S, T, U, F>(state: ParseState<S, T>, f: F) -> ParseState<S, U> where
+ S: Ord + Clone + HasNext,
+ F: FnOnce(T) -> U
+{
+  if state.is_successful() {
+    let (state, data) = state.extract_data();
+    state.success(f(data))
+  }
+  else {
+    state.failure()
+  }
+}
+
+enum Frame<S>
+{
+  Return(usize),
+  Backtrack {
+    alternative: usize,
+    mark: S,
+    values: usize,
+    marks: usize
+  }
+}
+
+pub struct Machine<'m, S>
+{
+  code: &'static [Instruction],
+  expectations: &'static ExpectationTable<'static>,
+  actions: &'static [Action],
+  externals: &'m [External<S>],
+  span: Option<fn(Range<S>) -> Value>
+}
+
+impl<'m, S> Machine<'m, S> where
+ S: Iterator<Item=char> + ConsumePrefix<&'static str> + ConsumePrefixIgnoreCase<&'static str>
+  + Ord + Clone + HasNext
+{
+  pub fn new(code: &'static [Instruction], expectations: &'static ExpectationTable<'static>,
+    actions: &'static [Action], externals: &'m [External<S>]) -> Self
+  {
+    Machine { code, expectations, actions, externals, span: None }
+  }
+
+  /// Builds the values of the `Span` instructions, required if the grammar has spanned expressions (`.. e`).
+  pub fn with_span(mut self, span: fn(Range<S>) -> Value) -> Self {
+    self.span = Some(span);
+    self
+  }
+
+  /// Executes the rule starting at `entry` until it returns.
+  /// The data of the state are the values of the rule: a tuple of values if there is not exactly one.
+  pub fn run(&self, entry: usize, mut state: ParseState<S, ()>) -> ParseState<S, Value> {
+    let mut pc = entry;
+    let mut values = vec![];
+    let mut marks = vec![];
+    let mut frames = vec![];
+    loop {
+      let success = match self.code[pc] {
+        Instruction::Literal(lit, expect) =>
+          state.consume_prefix(lit) || self.error(&mut state, expect),
+        Instruction::LiteralIgnoreCase(lit, expect) =>
+          state.consume_prefix_ignore_case(lit) || self.error(&mut state, expect),
+        Instruction::AnySingleChar(expect, push) => match state.next() {
+          Some(c) => {
+            if push { values.push(Value::Char(c)); }
+            true
+          }
+          None => self.error(&mut state, expect)
+        },
+        Instruction::CharacterClass(class, expect, push) => {
+          let mark = state.mark();
+          match state.next() {
+            Some(c) if class.contains(c) => {
+              if push { values.push(Value::Char(c)); }
+              true
+            }
+            _ => {
+              state.rewind(mark);
+              self.error(&mut state, expect)
+            }
+          }
+        }
+        Instruction::Choice(alternative) => {
+          frames.push(Frame::Backtrack {
+            alternative,
+            mark: state.mark(),
+            values: values.len(),
+            marks: marks.len()
+          });
+          true
+        }
+        Instruction::Commit(target) => {
+          frames.pop();
+          pc = target;
+          continue;
+        }
+        Instruction::BackCommit(target) => {
+          if let Some(Frame::Backtrack { mark, values: len, marks: marks_len, .. }) = frames.pop() {
+            state.rewind(mark);
+            values.truncate(len);
+            marks.truncate(marks_len);
+          }
+          pc = target;
+          continue;
+        }
+        Instruction::FailTwice => {
+          frames.pop();
+          false
+        }
+        Instruction::Fail => false,
+        Instruction::Call(target) => {
+          frames.push(Frame::Return(pc + 1));
+          pc = target;
+          continue;
+        }
+        Instruction::Return => match frames.pop() {
+          Some(Frame::Return(ret)) => {
+            pc = ret;
+            continue;
+          }
+          _ => {
+            let n = values.len();
+            return state.success(pop_value(&mut values, n));
+          }
+        },
+        Instruction::External(idx, push) => {
+          let result = (self.externals[idx])(state);
+          if result.is_successful() {
+            let (rest, value) = result.extract_data();
+            state = rest;
+            if push { values.push(value); }
+            true
+          }
+          else {
+            state = result.failure();
+            false
+          }
+        }
+        Instruction::Action(idx) => {
+          (self.actions[idx])(&mut values);
+          true
+        }
+        Instruction::PushList => {
+          values.push(Value::List(vec![]));
+          true
+        }
+        Instruction::Append(n) => {
+          let element = pop_value(&mut values, n);
+          match values.last_mut() {
+            Some(&mut Value::List(ref mut list)) => list.push(element),
+            _ => panic!("[bug] `Append` without a list in the bytecode (please report this issue).")
+          }
+          true
+        }
+        Instruction::PushNone => {
+          values.push(Value::Optional(None));
+          true
+        }
+        Instruction::PushSome(n) => {
+          let value = pop_value(&mut values, n);
+          values.push(Value::Optional(Some(Box::new(value))));
+          true
+        }
+        Instruction::Mark => {
+          marks.push(state.mark());
+          true
+        }
+        Instruction::Span(n) => {
+          let span = self.span.expect("[bug] `Span` instruction without a span function (please report this issue).");
+          let start = marks.pop().expect("[bug] `Span` without a mark in the bytecode (please report this issue).");
+          let value = span(Range { start, end: state.mark() });
+          let len = values.len();
+          values.insert(len - n, value);
+          true
+        }
+      };
+      if success {
+        pc += 1;
+      }
+      else {
+        // Returns to the last backtrack point, discarding the calls in between.
+        loop {
+          match frames.pop() {
+            Some(Frame::Backtrack { alternative, mark, values: len, marks: marks_len }) => {
+              state.rewind(mark);
+              state.failed = false;
+              values.truncate(len);
+              marks.truncate(marks_len);
+              pc = alternative;
+              break;
+            }
+            Some(Frame::Return(_)) => (),
+            None => return state.failure()
+          }
+        }
+      }
+    }
+  }
+
+  fn error(&self, state: &mut ParseState<S, ()>, expect: usize) -> bool {
+    state.error(self.expectations, expect);
+    false
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use super::Instruction::{Choice, Commit, Literal, Call, Return, PushSome, PushNone, PushList, Append};
+  use str_stream::StrStream;
+
+  static EXPECTATIONS: [&'static str; 3] = ["a", "b", "[\"0-9\"]"];
+  static DIGIT: CharacterClass = CharacterClass { intervals: &[('0', '9')], sets: &[], negated: false };
+
+  fn count(values: &mut Vec<Value>) {
+    let digits = pop_values(values, 1).next().unwrap().into_list(|c| c.into_char());
+    values.push(Value::rust(digits.len()));
+  }
+
+  static ACTIONS: [Action; 1] = [count];
+
+  // start = ("a" / "b") digits "a"?
+  // digits = ["0-9"]+ > count
+  static CODE: [Instruction; 20] = [
+    Choice(3), Literal("a", 0), Commit(4),
+    Literal("b", 1),
+    Call(11),
+    Choice(9), Literal("a", 0), PushSome(0), Commit(10),
+    PushNone,
+    Return,
+    PushList, Instruction::CharacterClass(&DIGIT, 2, true), Append(1),
+    Choice(18), Instruction::CharacterClass(&DIGIT, 2, true), Append(1), Commit(14),
+    Instruction::Action(0), Return
+  ];
+
+  fn run(input: &'static str) -> ParseState<StrStream<'static>, Value> {
+    Machine::new(&CODE, &EXPECTATIONS, &ACTIONS, &[]).run(0, input.into_state())
+  }
+
+  #[test]
+  fn test_machine() {
+    let (count, optional) = {
+      let mut fields = run("b123a").unwrap_data().into_fields();
+      (fields.next().unwrap().into_rust::<usize>(), fields.next().unwrap().into_optional(|_| ()))
+    };
+    assert_eq!(count, 3);
+    assert_eq!(optional, Some(()));
+    let err = run("c").into_prefix_result().unwrap_err();
+    assert_eq!(format!("{}", err), "1:1: unexpected `c`, expecting `a` or `b`.");
+    let err = run("a1x").into_full_result().unwrap_err();
+    assert_eq!(format!("{}", err), "1:3: unexpected `x`, expecting `a` or `[\"0-9\"]` or `<end-of-input>`.");
+  }
+}
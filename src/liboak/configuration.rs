@@ -0,0 +1,98 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Expansion of a grammar with `#[cfg(..)]` attributes: each configuration is compiled by a nested invocation, such as `oak_configuration! { [true, false] ... }`, annotated with the `#[cfg]` attribute of this configuration.
+//! The compiler only expands the invocation of the configuration it selects, therefore the errors of the other configurations are not reported.
+//! For instance, a rule defined under `unix` and under `windows` is not a duplicate in the configuration where both are enabled, since it is never selected.
+
+use oak_compiler::front::ast::FGrammar;
+use oak_compiler::middle::configuration::Configuration;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
+
+/// Compiles `ast` if it has a single configuration, otherwise expands the invocation `nested(assignment)` for each configuration.
+pub fn expand<F>(ast: FGrammar, nested: F) -> TokenStream where
+  F: Fn(&[bool]) -> TokenStream
+{
+  match Configuration::all(&ast) {
+    None => TokenStream::new(),
+    Some(ref configs) if configs.len() == 1 => oak_compiler::compile_configuration(ast, &configs[0]),
+    Some(configs) => configs.iter()
+      .map(|config| {
+        let cfg = config.cfg_attribute();
+        let nested = nested(config.assignment());
+        quote!(#cfg #nested)
+      })
+      .collect()
+  }
+}
+
+/// Compiles `ast` in the configuration given by `assignment`.
+pub fn compile(ast: FGrammar, assignment: &[bool]) -> TokenStream {
+  let config = Configuration::all(&ast)
+    .and_then(|configs| configs.into_iter().find(|config| config.assignment() == assignment));
+  match config {
+    Some(config) => oak_compiler::compile_configuration(ast, &config),
+    None => TokenStream::new()
+  }
+}
+
+/// The assignment of the atomic predicates given to a nested invocation, e.g. `[true, false]`.
+pub struct Assignment(pub Vec<bool>);
+
+impl Parse for Assignment
+{
+  fn parse(input: ParseStream) -> Result<Self> {
+    let content;
+    syn::bracketed!(content in input);
+    let values: Punctuated<syn::LitBool, syn::Token![,]> = content.parse_terminated(syn::LitBool::parse)?;
+    Ok(Assignment(values.iter().map(|value| value.value).collect()))
+  }
+}
+
+/// The input of `oak_configuration!`, an assignment followed by the grammar.
+pub struct ConfiguredGrammar
+{
+  pub assignment: Assignment,
+  pub grammar: FGrammar
+}
+
+impl Parse for ConfiguredGrammar
+{
+  fn parse(input: ParseStream) -> Result<Self> {
+    Ok(ConfiguredGrammar {
+      assignment: input.parse()?,
+      grammar: input.parse()?
+    })
+  }
+}
+
+/// The input of `oak_file_configuration!`, the path of the grammar file followed by an assignment.
+pub struct ConfiguredFile
+{
+  pub path: syn::LitStr,
+  pub assignment: Assignment
+}
+
+impl Parse for ConfiguredFile
+{
+  fn parse(input: ParseStream) -> Result<Self> {
+    let path = input.parse()?;
+    input.parse::<syn::Token![,]>()?;
+    Ok(ConfiguredFile { path, assignment: input.parse()? })
+  }
+}
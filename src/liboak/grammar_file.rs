@@ -0,0 +1,121 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Expansion of `oak_file!("grammars/calc.oak")`, which compiles the grammar written in a file relative to the root of the crate.
+//! The file contains what is usually written inside `oak! { ... }`.
+//!
+//! The tokens of the compiler parsed from the file all have the span of the invocation, since a span of the compiler cannot refer to a position in another file.
+//! Therefore, the grammar is first analysed with the fallback spans of `proc_macro2`, which know their lines and columns in the file, only to compute the positions of the diagnostics.
+//! These diagnostics are emitted on the invocation of the macro with their positions in the file written in the message.
+//! Then, the code is generated from the tokens of the compiler, and the diagnostics of this second analysis are ignored since they were already emitted.
+
+use configuration;
+use oak_compiler::diagnostic::{self, Diagnostic, SpanDiagnostic};
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Compiles the grammar file `path` in each of its configurations, or only in the configuration `assignment` when it is given.
+pub fn expand(path_lit: syn::LitStr, assignment: Option<Vec<bool>>) -> TokenStream {
+  let span = path_lit.span();
+  let path = path_lit.value();
+  let full_path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join(&path);
+  let source = match fs::read_to_string(&full_path) {
+    Ok(source) => source,
+    Err(err) => {
+      span.error(format!("cannot read the grammar file `{}`: {}.", full_path.display(), err)).emit();
+      return diagnostic::expand_diagnostics();
+    }
+  };
+  let assignment = assignment.as_deref();
+  let diagnostics = located_diagnostics(&source, &path, assignment, span);
+  let error = diagnostics.iter().any(Diagnostic::is_error);
+  for diagnostic in diagnostics {
+    diagnostic.emit();
+  }
+  // The file is included to be recompiled when it changes.
+  let full_path = full_path.display().to_string();
+  let mut result = quote_spanned!(span=> const _: &[u8] = include_bytes!(#full_path););
+  if !error {
+    let (code, _) = diagnostic::collect_diagnostics(|| compile(&source, assignment, |assignment|
+      quote!(::oak::oak_file_configuration!(#path_lit, [#(#assignment),*]))));
+    result.extend(code);
+  }
+  result.extend(diagnostic::expand_diagnostics());
+  result
+}
+
+/// The grammar `source` compiled in the configuration `assignment`, or in each of its configurations where `nested` expands the invocation compiling one configuration.
+fn compile<F>(source: &str, assignment: Option<&[bool]>, nested: F) -> Option<TokenStream> where
+  F: Fn(&[bool]) -> TokenStream
+{
+  oak_compiler::parse(source).map(|ast| match assignment {
+    Some(assignment) => configuration::compile(ast, assignment),
+    None => configuration::expand(ast, nested)
+  })
+}
+
+/// The diagnostics of the grammar `source` relocated on `span`, with their positions in the grammar file `path`.
+fn located_diagnostics(source: &str, path: &str, assignment: Option<&[bool]>, span: Span) -> Vec<Diagnostic> {
+  let (_, diagnostics) = diagnostic::collect_diagnostics(|| {
+    let _fallback = FallbackSpans::force();
+    // The code is dropped while the fallback is forced, the nested invocations are not needed to analyse the grammar.
+    compile(source, assignment, |_| TokenStream::new());
+  });
+  diagnostics.iter().map(|diagnostic| diagnostic.relocate(span, path)).collect()
+}
+
+/// Uses the fallback implementation of `proc_macro2` while it is alive, even inside the procedural macro.
+/// The tokens and spans created meanwhile must not be mixed with those of the compiler.
+struct FallbackSpans;
+
+impl FallbackSpans
+{
+  fn force() -> Self {
+    proc_macro2::fallback::force();
+    FallbackSpans
+  }
+}
+
+impl Drop for FallbackSpans
+{
+  fn drop(&mut self) {
+    proc_macro2::fallback::unforce();
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_located_diagnostics() {
+    let path = "tests/grammars/oak_file.oak";
+    let source = fs::read_to_string(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(path)).unwrap();
+    assert!(located_diagnostics(&source, path, None, Span::call_site()).is_empty());
+    // The duplicate rule commented at the line 7 of the file.
+    let source = source.replace("// number = \"x\"", "number = \"x\"");
+    let diagnostics: Vec<String> = located_diagnostics(&source, path, None, Span::call_site()).iter()
+      .map(|diagnostic| diagnostic.render(path))
+      .collect();
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].contains("duplicate definition of rule with name `number`"), "{}", diagnostics[0]);
+    assert!(diagnostics[0].contains("tests/grammars/oak_file.oak:7:1"), "{}", diagnostics[0]);
+    assert!(diagnostics[0].contains("tests/grammars/oak_file.oak:4:1"), "{}", diagnostics[0]);
+  }
+}
@@ -14,29 +14,52 @@
 
 //! This is the developer documentation of Oak, if you do not intend to contribute, please read the [user manual](http://hyc.io/oak) instead. This library proposes a syntax extension for a parser generator based on [Parsing Expression Grammar (PEG)](https://en.wikipedia.org/wiki/Parsing_expression_grammar). It aims at simplifying the construction of the AST by typing the parsing rules. This is an experimental library.
 
-#![feature(proc_macro_diagnostic, proc_macro_span)]
-
-extern crate partial;
+extern crate oak_compiler;
 extern crate syn;
 extern crate quote;
 extern crate proc_macro;
 extern crate proc_macro2;
 
+use oak_compiler::{front, diagnostic};
 use proc_macro::TokenStream;
+use quote::quote;
 use syn::parse_macro_input;
 
-mod ast;
-mod visitor;
-mod front;
-mod middle;
-mod back;
-mod identifier;
+mod configuration;
+mod grammar_file;
 
 #[proc_macro]
 pub fn oak(input: TokenStream) -> TokenStream {
+  let tokens = proc_macro2::TokenStream::from(input.clone());
   let ast = parse_macro_input!(input as front::ast::FGrammar);
   // println!("parsing successful!");
-  let tast = middle::typecheck(ast);
-  // println!("typing successful!");
-  proc_macro::TokenStream::from(back::compile(tast))
+  let mut result = configuration::expand(ast, |assignment|
+    quote!(::oak::oak_configuration! { [#(#assignment),*] #tokens }));
+  result.extend(diagnostic::expand_diagnostics());
+  proc_macro::TokenStream::from(result)
+}
+
+/// Compiles the grammar of `oak!` in a single configuration, `oak!` expands one invocation per configuration of a grammar with `#[cfg(..)]` attributes.
+#[doc(hidden)]
+#[proc_macro]
+pub fn oak_configuration(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as configuration::ConfiguredGrammar);
+  let mut result = configuration::compile(input.grammar, &input.assignment.0);
+  result.extend(diagnostic::expand_diagnostics());
+  proc_macro::TokenStream::from(result)
+}
+
+/// Compiles the grammar in the file given as a string literal, its path is relative to the root of the crate, e.g. `oak_file!("grammars/calc.oak")`.
+#[proc_macro]
+pub fn oak_file(input: TokenStream) -> TokenStream {
+  let path = parse_macro_input!(input as syn::LitStr);
+  proc_macro::TokenStream::from(grammar_file::expand(path, None))
+}
+
+/// Compiles the grammar file of `oak_file!` in a single configuration, as `oak_configuration!` does for `oak!`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn oak_file_configuration(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as configuration::ConfiguredFile);
+  proc_macro::TokenStream::from(grammar_file::expand(input.path, Some(input.assignment.0)))
 }
@@ -0,0 +1,189 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The bytecode backend `#![bytecode]` produces the same values and errors as the default backend.
+//! The same grammar is compiled with both backends.
+
+macro_rules! grammar {
+  ($($bytecode:tt)*) => {
+    use oak::oak;
+    use oak_runtime::*;
+
+    oak! {
+      $($bytecode)*
+      #![start(program)]
+
+      program = spacing statement+
+
+      statement
+        = let_kw (.. identifier) bind_op expression ";" spacing > let_stmt
+        / expression ";" spacing > expr_stmt
+
+      expression = term (term_op term)* > fold_left
+      term = factor (mul_op factor)* > fold_left
+
+      factor
+        = number > box Expr::Number
+        / "-" spacing factor > box Expr::Neg
+        / identifier > box Expr::Variable
+        / &"(" "(" spacing expression ")" spacing
+        / "true"i spacing > box Expr::True
+
+      number = ["0-9"]+ ("." ["0-9"]+)? spacing > to_number
+      identifier = !keyword ["\\p{XID_Start}_"] ["\\p{XID_Continue}"]* spacing > to_ident
+
+      keyword = let_kw
+      let_kw = "let"i !["a-zA-Z0-9_"] spacing
+      bind_op = "=" spacing
+      term_op
+        = "+" spacing > add
+        / "-" spacing > sub
+      mul_op = "*" spacing > mul
+      spacing = ([" \n\t"] / "#" (!"\n" .)*)*:(^)
+
+      pub pairs = (["a-z"] ["0-9"])*
+      pub pair = (["a-z"] ["0-9"])?
+      pub spanned = .. ["a-z"]+ "-" .
+
+      use self::Expr::*;
+
+      pub type PExpr = Box<Expr>;
+
+      #[derive(Debug)]
+      pub enum Stmt {
+        Let(Span, String, PExpr),
+        Expression(PExpr)
+      }
+
+      #[derive(Debug)]
+      pub enum Expr {
+        Number(f64),
+        Neg(PExpr),
+        Variable(String),
+        True,
+        Binary(BinOp, PExpr, PExpr)
+      }
+
+      #[derive(Debug)]
+      pub enum BinOp {
+        Add, Sub, Mul
+      }
+
+      fn let_stmt(span: Span, var: String, value: PExpr) -> Stmt {
+        Stmt::Let(span, var, value)
+      }
+
+      fn expr_stmt(expr: PExpr) -> Stmt {
+        Stmt::Expression(expr)
+      }
+
+      fn fold_left(head: PExpr, rest: Vec<(BinOp, PExpr)>) -> PExpr {
+        rest.into_iter().fold(head, |accu, (op, expr)| Box::new(Binary(op, accu, expr)))
+      }
+
+      fn to_number(integer: Vec<char>, fraction: Option<Vec<char>>) -> f64 {
+        let mut text: String = integer.into_iter().collect();
+        if let Some(fraction) = fraction {
+          text.push('.');
+          text.extend(fraction);
+        }
+        text.parse().unwrap()
+      }
+
+      fn to_ident(first: char, rest: Vec<char>) -> String {
+        Some(first).into_iter().chain(rest).collect()
+      }
+
+      fn add() -> BinOp { BinOp::Add }
+      fn sub() -> BinOp { BinOp::Sub }
+      fn mul() -> BinOp { BinOp::Mul }
+    }
+  }
+}
+
+mod compiled {
+  grammar!();
+}
+
+mod bytecode {
+  grammar!(#![bytecode]);
+}
+
+mod external {
+  use oak::oak;
+
+  oak! {
+    #![bytecode]
+
+    sum = super::compiled::number ("+" super::compiled::number)* > add
+
+    fn add(x: f64, rest: Vec<f64>) -> f64 {
+      rest.iter().fold(x, |x, y| x + y)
+    }
+
+    // range = ... ["a-z"]+    // ERROR: the range expression `...` is not supported by `#![bytecode]`, consider the span expression `..` instead.
+    // name = ["a-z"]+ > to_name    // ERROR: the type of the rule `name` borrows data in `&'a str`, the values of `#![bytecode]` are stored as `Box<dyn Any>` and must be `'static`.
+    // fn to_name<'a>(_letters: Vec<char>) -> &'a str { "name" }
+  }
+}
+
+use oak_runtime::*;
+
+#[test]
+fn test_same_values_and_errors() {
+  let inputs = [
+    "let x = 1 + 2*3;",
+    "LET été = -(x + 1.5) * true; x;",
+    "x # comment\n - 2 ;",
+    "let letter = 1;",
+    "1 + ;",
+    "let = 2;",
+    "1.;",
+    "(1",
+    ""
+  ];
+  for input in inputs.iter() {
+    assert_eq!(format!("{:?}", bytecode::parse(*input)), format!("{:?}", compiled::parse(*input)));
+  }
+  match bytecode::parse("let x = -1;").unwrap().pop() {
+    Some(bytecode::Stmt::Let(span, var, _)) => {
+      assert_eq!(span, make_span(4, 6));
+      assert_eq!(var, "x");
+    }
+    stmt => panic!("unexpected statement `{:?}`", stmt)
+  }
+  assert_eq!(bytecode::parse("1 + ;").unwrap_err().location, "1:5");
+}
+
+#[test]
+fn test_rule_functions() {
+  for input in ["a1b2", "a1b", ""].iter() {
+    assert_eq!(
+      format!("{:?}", bytecode::parse_pairs(input.into_state()).into_full_result()),
+      format!("{:?}", compiled::parse_pairs(input.into_state()).into_full_result()));
+    assert_eq!(
+      format!("{:?}", bytecode::parse_pair(input.into_state()).into_prefix_result().map(|(pair, _)| pair)),
+      format!("{:?}", compiled::parse_pair(input.into_state()).into_prefix_result().map(|(pair, _)| pair)));
+  }
+  let (span, letters, c) = bytecode::parse_spanned("ab-c".into_state()).unwrap_data();
+  assert_eq!((span, letters, c), (make_span(0, 4), vec!['a', 'b'], 'c'));
+  assert!(bytecode::recognize_keyword("let ".into_state()).into_full_result().is_ok());
+  assert!(bytecode::recognize_keyword("letter".into_state()).into_full_result().is_err());
+  assert_eq!(bytecode::parse_prefix("1; rest").unwrap().1.bytes_offset(), 3);
+}
+
+#[test]
+fn test_external_parser() {
+  assert_eq!(external::parse_sum("1+2.5+3".into_state()).into_full_result(), Ok(6.5));
+}
@@ -70,6 +70,7 @@ oak! {
 
   kw_tail = !calc2::ident_char spacing
 
+  #[allow(unused)]
   keyword = let_kw / in_kw
   let_kw = "let" kw_tail
   in_kw = "in" kw_tail
@@ -0,0 +1,51 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Rules and alternatives annotated with `#[cfg(..)]`.
+
+use oak::oak;
+use oak_runtime::*;
+
+oak! {
+  list = item (separator item)*
+  item = letters / #[cfg(test)] digits / #[cfg(not(test))] upper
+
+  letters = ["a-z"]+
+
+  /// Digits are only accepted in tests.
+  #[cfg(test)]
+  digits = ["0-9"]+
+  #[cfg(not(test))]
+  digits = "0"
+
+  #[cfg(not(test))]
+  upper = ["A-Z"]+
+
+  // Both definitions are never enabled together, and the grammar is only analysed in the configuration selected by the compiler.
+  #[cfg(unix)]
+  separator = ","
+  #[cfg(windows)]
+  separator = "," / ";"
+
+  // bad = upper              // ERROR: the rule `upper` is disabled in the configuration `#[cfg(all(test, ..))]`.
+  // Only reported outside of the tests, the configurations not selected by the compiler are not analysed.
+  // bad2 = #[cfg(test)] "a"  // ERROR: all the alternatives of this choice are disabled in the configuration `#[cfg(all(not(test), ..))]`.
+}
+
+#[test]
+fn test_cfg() {
+  assert!(recognize_list("ab,12,c".into_state()).into_full_result().is_ok());
+  assert!(!recognize_list("AB".into_state()).is_successful());
+}
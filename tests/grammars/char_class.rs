@@ -0,0 +1,80 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Character classes: normalization of the intervals, negation, escapes and named sets.
+
+use oak::oak;
+use oak_runtime::*;
+
+oak! {
+  // The rules are tested independently of each other.
+  #![allow(unused)]
+
+  ident = ["a-zA-Z_0-9"]+
+  letters = ["n-za-m"]+
+  sign = ["+-"]
+
+  string = "\"" ([^"\"\\\\"] / "\\" .)* "\""
+  not_quote = [^"\"\\\\"]+
+  escaped = ["\\-\\]\\n"]+
+  posix = ["[:digit:][:upper:]_"]+
+  identifier = ["\\p{XID_Start}_"] ["\\p{XID_Continue}"]*
+  not_letter = [^"\\p{L}"]+
+  backslash = ["\\"]                  // OK: a trailing backslash is not an escape.
+  not_quote_unescaped = [^"\"\\"]+
+
+  // unknown_posix = ["[:letter:]"]     // ERROR: unknown named set `[:letter:]`.
+  // unknown_unicode = ["\\p{Letter}"] // ERROR: unknown Unicode set `\p{Letter}`.
+  // bad_escape = ["\\q"]              // ERROR: unknown escape sequence `\q`.
+
+  // reversed = ["z-a"]       // ERROR: the interval `z-a` is reversed and matches no character.
+  // duplicate = ["a-zb-d"]   // WARNING: `b-d` is already matched by `a-z` in this character class.
+  // overlap = ["a-mf-z"]     // WARNING: `f-z` overlaps with `a-m` in this character class.
+}
+
+#[test]
+fn test_char_class() {
+  assert!(parse_ident("a_Z09".into_state()).into_full_result().is_ok());
+  assert!(parse_letters("xyzabc".into_state()).into_full_result().is_ok());
+  let error = parse_ident("!".into_state()).into_full_result().unwrap_err();
+  assert_eq!(error.expected, vec!["[\"0-9A-Z_a-z\"]"]);
+  let error = parse_letters("A".into_state()).into_full_result().unwrap_err();
+  assert_eq!(error.expected, vec!["[\"a-z\"]"]);
+  let error = parse_sign("a".into_state()).into_full_result().unwrap_err();
+  assert_eq!(error.expected, vec!["[\"-+\"]"]);
+}
+
+#[test]
+fn test_negation_escapes_and_sets() {
+  assert!(parse_not_quote("ab c".into_state()).into_full_result().is_ok());
+  assert!(!parse_not_quote("\"".into_state()).is_successful());
+  assert!(!parse_not_quote("\\".into_state()).is_successful());
+  assert!(parse_escaped("-]\n".into_state()).into_full_result().is_ok());
+  assert!(!parse_escaped("\\".into_state()).is_successful());
+  assert!(parse_posix("09AZ_".into_state()).into_full_result().is_ok());
+  assert!(!parse_posix("a".into_state()).is_successful());
+  assert!(parse_identifier("_été2".into_state()).into_full_result().is_ok());
+  assert!(parse_identifier("ξ_1".into_state()).into_full_result().is_ok());
+  assert!(!parse_identifier("1a".into_state()).is_successful());
+  assert!(parse_not_letter("12 +".into_state()).into_full_result().is_ok());
+  assert!(!parse_not_letter("é".into_state()).is_successful());
+  let error = parse_not_quote("\"".into_state()).into_full_result().unwrap_err();
+  assert_eq!(error.expected, vec!["[^\"\\\"\\\\\"]"]);
+  let error = parse_identifier("1".into_state()).into_full_result().unwrap_err();
+  assert_eq!(error.expected, vec!["[\"_\\p{XID_Start}\"]"]);
+  assert!(parse_string("\"a\\\"b\"".into_state()).into_full_result().is_ok());
+  assert!(parse_backslash("\\".into_state()).into_full_result().is_ok());
+  assert!(parse_not_quote_unescaped("ab c".into_state()).into_full_result().is_ok());
+  assert!(!parse_not_quote_unescaped("\\".into_state()).is_successful());
+}
@@ -15,6 +15,8 @@
 use oak::oak;
 
 oak! {
+  // The rules are tested independently of each other.
+  #![allow(unused)]
   str_literal = "return"
 
   sequence = "if" " " "then" " " "else"
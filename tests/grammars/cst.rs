@@ -0,0 +1,317 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use oak::oak;
+
+oak! {
+  #![cst]
+  #![start(sum)]
+  // #![bytecode]    // ERROR: `#![cst]` is not supported by `#![bytecode]`.
+
+  sum = spacing term ("+" spacing term)* > add
+
+  term
+    = number "!" spacing > factorial
+    / number spacing
+    / &"(" "(" spacing sum ")" spacing
+
+  number = ["0-9"]+ > to_number
+  #[trivia]
+  spacing = ([" \n"] / comment)*:(^)
+  comment = "#" (!"\n" .)*
+  // token = "t"    // ERROR: the visitors generated by `#![cst]` already have a method `visit_token`.
+  // use std::fmt as visitor;    // ERROR: the module `visitor` generated by `#![cst]` clashes with an item of the grammar.
+  // struct printer;    // ERROR: the module `printer` generated by `#![cst]` clashes with an item of the grammar.
+
+  fn add(x: u64, rest: Vec<u64>) -> u64 {
+    rest.into_iter().fold(x, |x, y| x + y)
+  }
+
+  fn factorial(n: u64) -> u64 {
+    (1..n + 1).product()
+  }
+
+  fn to_number(digits: Vec<char>) -> u64 {
+    digits.into_iter().collect::<String>().parse().unwrap()
+  }
+}
+
+mod statements {
+  use oak::oak;
+
+  oak! {
+    #![cst]
+    #![start(program)]
+
+    program = spacing (statement semi)*
+    statement = "print"i spacing name
+    name = ["a-z"]+ spacing
+    semi = ";"? spacing
+    #[trivia]
+    spacing = [" "]*:(^)
+  }
+}
+
+use oak_runtime::*;
+use oak_runtime::cst::SyntaxNode;
+
+#[test]
+fn test_lossless_tree() {
+  let input = " 3! + # three\n(1 +2)";
+  let (value, tree) = parse_cst(input).unwrap();
+  assert_eq!(value, 9);
+  assert_eq!(tree.text(), input);
+  assert_eq!(format!("{:?}", tree), "\
+sum@0..20
+  spacing@0..1
+    \" \"@0..1
+  term@1..4
+    number@1..2
+      \"3\"@1..2
+    \"!\"@2..3
+    spacing@3..4
+      \" \"@3..4
+  \"+\"@4..5
+  spacing@5..14
+    \" \"@5..6
+    comment@6..13
+      \"# three\"@6..13
+    \"\\n\"@13..14
+  term@14..20
+    \"(\"@14..15
+    sum@15..19
+      term@15..17
+        number@15..16
+          \"1\"@15..16
+        spacing@16..17
+          \" \"@16..17
+      \"+\"@17..18
+      term@18..19
+        number@18..19
+          \"2\"@18..19
+    \")\"@19..20
+");
+}
+
+#[test]
+fn test_navigation() {
+  let (_, tree) = parse_cst("1 + 2").unwrap();
+  assert_eq!(tree.kind(), "sum");
+  assert!(tree.parent().is_none());
+  let kinds: Vec<_> = tree.children().into_iter().map(|node| node.kind()).collect();
+  assert_eq!(kinds, vec!["term", "spacing", "term"]);
+
+  let number = tree.find("number").unwrap();
+  assert_eq!(number.span(), make_span(0, 1));
+  assert_eq!(number.ancestors().iter().map(|node| node.kind()).collect::<Vec<_>>(), vec!["term", "sum"]);
+  let spacing = number.next_sibling().unwrap().into_node().unwrap();
+  let space = spacing.children_with_tokens().pop().unwrap().into_token().unwrap();
+  assert_eq!((space.text(), space.span()), (" ", make_span(1, 2)));
+  assert_eq!(space.parent(), spacing);
+  let plus = number.parent().unwrap().next_sibling().unwrap();
+  assert_eq!(plus.text(), "+");
+  assert_eq!(plus.parent(), Some(tree.clone()));
+  assert_eq!(tree.children()[2].prev_sibling().unwrap().into_node().unwrap().kind(), "spacing");
+  assert!(tree.children()[0].prev_sibling().is_none());
+}
+
+#[test]
+fn test_backtracking() {
+  // The first alternative of `term` parses `number` before failing on `!`, and the syntactic predicate parses `(`, they must not appear in the tree.
+  let (_, tree) = parse_cst("(7)").unwrap();
+  assert_eq!(format!("{:?}", tree), "\
+sum@0..3
+  term@0..3
+    \"(\"@0..1
+    sum@1..2
+      term@1..2
+        number@1..2
+          \"7\"@1..2
+    \")\"@2..3
+");
+  assert!(parse_cst("").is_err());
+  assert_eq!(parse_cst("1 +").unwrap_err().location, "1:4");
+  // The root is created even if no node is recorded.
+  let state = parse_sum("".into_state().with_cst());
+  assert_eq!(state.syntax_tree("sum", oak_runtime::stream::Stream::stream("")).children_with_tokens().len(), 0);
+}
+
+#[test]
+fn test_incremental_reparsing() {
+  use oak_runtime::incremental::TextEdit;
+  use std::rc::Rc;
+
+  let edits = [
+    ("1 + 2 + 3 + 44", TextEdit::new(4..5, "7")),
+    ("1 + 2 + 3 + 44", TextEdit::new(13..14, "")),
+    ("1 + 2", TextEdit::new(1..1, "!")),
+    ("1! + 2", TextEdit::new(1..2, "")),
+    ("(1 + 2) + 3", TextEdit::new(6..7, "")),
+    ("1 # x\n+ 2", TextEdit::new(5..6, " ")),
+    ("1 + 2", TextEdit::new(0..5, "34"))
+  ];
+  for &(input, ref edit) in edits.iter() {
+    let (_, tree) = parse_cst(input).unwrap();
+    let text = edit.apply(input);
+    let reparse = reparse_cst(&tree, edit, &text);
+    assert_eq!(
+      format!("{:?}", reparse.as_ref().map(|reparse| &reparse.tree)),
+      format!("{:?}", parse_cst(&*text).map(|(_, tree)| tree)));
+  }
+
+  let input = "1 + 2 + 3 + 44";
+  let (_, tree) = parse_cst(input).unwrap();
+  let edit = TextEdit::new(4..5, "77");
+  let text = edit.apply(input);
+  let reparse = reparse_cst(&tree, &edit, &text).unwrap();
+  assert_eq!(reparse.tree.text(), "1 + 77 + 3 + 44");
+  assert_eq!(reparse.changed, 4..6);
+  let terms = |tree: &SyntaxNode| -> Vec<SyntaxNode> {
+    tree.children().into_iter().filter(|node| node.kind() == "term").collect()
+  };
+  let (old_terms, new_terms) = (terms(&tree), terms(&reparse.tree));
+  // The nodes after the edit are reused, the first one is parsed again because the parser examined the input after it.
+  assert!(!Rc::ptr_eq(old_terms[0].green(), new_terms[0].green()));
+  assert!(Rc::ptr_eq(old_terms[2].green(), new_terms[2].green()));
+  assert!(Rc::ptr_eq(old_terms[3].green(), new_terms[3].green()));
+  assert_eq!(new_terms[3].span(), make_span(13, 15));
+
+  let edit = TextEdit::new(2..3, "*");
+  assert_eq!(reparse_cst(&tree, &edit, &edit.apply(input)).err().map(|err| err.location), Some("1:3".to_string()));
+
+  // `reparse_cst` is `reparse` with the recognizer of the start rule and the lookahead of the grammar.
+  assert_eq!(OAK_LOOKAHEAD, 4);
+  assert_eq!(statements::OAK_LOOKAHEAD, 20);
+  let edit = TextEdit::new(4..5, "77");
+  let reparse = oak_runtime::incremental::reparse(&tree, &edit, &text, OAK_LOOKAHEAD, recognize_sum).unwrap();
+  assert_eq!(reparse.tree.text(), "1 + 77 + 3 + 44");
+  assert_eq!(reparse.changed, 4..6);
+}
+
+#[test]
+fn test_visitors() {
+  use self::visitor::*;
+  use oak_runtime::cst::*;
+  use std::rc::Rc;
+
+  struct Numbers(Vec<String>);
+  impl Visitor for Numbers {
+    fn visit_number(&mut self, node: &SyntaxNode) {
+      self.0.push(node.text());
+    }
+    // Numbers are not nested in comments, we do not walk their children.
+    fn visit_comment(&mut self, _node: &SyntaxNode) {}
+  }
+
+  let input = "1 + (22 + 3!) # 4";
+  let (_, tree) = parse_cst(input).unwrap();
+  let mut numbers = Numbers(vec![]);
+  numbers.visit_node(&tree);
+  assert_eq!(numbers.0, vec!["1", "22", "3"]);
+
+  struct Double;
+  impl VisitorMut for Double {
+    fn visit_number_mut(&mut self, node: &mut GreenNode) {
+      node.update_children(|children| {
+        let doubled = match children[0] {
+          GreenElement::Token(ref digits) => format!("{}{}", digits, digits),
+          GreenElement::Node(_) => unreachable!()
+        };
+        children[0] = GreenElement::Token(doubled.into());
+      });
+    }
+  }
+
+  let mut green = (**tree.green()).clone();
+  Double.visit_node_mut(&mut green);
+  let doubled = SyntaxNode::new_root(Rc::new(green), 0);
+  assert_eq!(doubled.text(), "11 + (2222 + 33!) # 4");
+  assert_eq!(doubled.span(), make_span(0, 21));
+  // The original tree is not modified.
+  assert_eq!(tree.text(), input);
+
+  struct StripComments;
+  impl Fold for StripComments {
+    fn fold_spacing(&mut self, node: Rc<GreenNode>) -> Rc<GreenNode> {
+      let children = node.children().iter()
+        .filter(|child| match **child {
+          GreenElement::Node(ref node) => node.kind() != "comment",
+          GreenElement::Token(_) => true
+        })
+        .cloned()
+        .collect();
+      Rc::new(GreenNode::new(node.kind(), children))
+    }
+  }
+
+  let stripped = SyntaxNode::new_root(StripComments.fold_node(tree.green().clone()), 0);
+  assert_eq!(stripped.text(), "1 + (22 + 3!) ");
+  assert_eq!(parse_cst(&*stripped.text()).unwrap().0, 29);
+}
+
+#[test]
+fn test_printer() {
+  use self::printer::Printer;
+
+  struct Identity;
+  impl Printer for Identity {}
+
+  let input = " 3! + # three\n(1 +2)";
+  let (_, tree) = parse_cst(input).unwrap();
+  assert_eq!(Identity.print(&tree), input);
+
+  // Normalizes the spacing and the numbers, and keeps the comments.
+  struct Normalize;
+  impl Printer for Normalize {
+    fn format_number(&mut self, text: &str) -> String {
+      text.parse::<u64>().unwrap().to_string()
+    }
+
+    fn spacing(&mut self, previous: &str, trivia: &str, next: &str) -> String {
+      if let Some(start) = trivia.find('#') {
+        format!(" {}\n", trivia[start..].trim_end())
+      }
+      else if previous.is_empty() || next.is_empty() || previous == "(" || next == ")" || next == "!" {
+        String::new()
+      }
+      else {
+        " ".to_string()
+      }
+    }
+  }
+
+  let input = "  007! +(1+ 02 ) # seven\n+3";
+  let (value, tree) = parse_cst(input).unwrap();
+  let printed = Normalize.print(&tree);
+  assert_eq!(printed, "7! + (1 + 2) # seven\n+ 3");
+  assert_eq!(parse_cst(&*printed).unwrap().0, value);
+}
+
+#[test]
+fn test_printer_literals() {
+  use self::statements::printer::Printer;
+
+  struct SingleSpace;
+  impl Printer for SingleSpace {
+    fn spacing(&mut self, previous: &str, _trivia: &str, next: &str) -> String {
+      if previous.is_empty() || next.is_empty() { String::new() } else { " ".to_string() }
+    }
+  }
+
+  // `semi` can match the empty input but it is not trivia, and the literals ignoring the case keep their text.
+  let (_, tree) = statements::parse_cst("  PRINT x;print  y").unwrap();
+  let printed = SingleSpace.print(&tree);
+  assert_eq!(printed, "PRINT x ; print y");
+  assert!(statements::parse_cst(&*printed).is_ok());
+}
@@ -0,0 +1,60 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Doc comments on rules and `#![grammar_reference]`.
+
+use oak::oak;
+use oak_runtime::*;
+
+oak! {
+  #![grammar_reference]
+
+  /// A list of numbers separated by commas.
+  /// For example: `1,2`.
+  pub list = number ("," number)* > make_list
+
+  /// A natural number.
+  number = ["0-9"]+ > to_number
+
+  #[allow(unused)]
+  sign = (("-" / "+")? !.):(^)
+
+  fn make_list(x: u32, mut rest: Vec<u32>) -> Vec<u32> {
+    rest.insert(0, x);
+    rest
+  }
+
+  fn to_number(raw_text: Vec<char>) -> u32 {
+    let text: String = raw_text.into_iter().collect();
+    text.parse().unwrap()
+  }
+}
+
+#[test]
+fn test_grammar_reference() {
+  assert_eq!(GRAMMAR_REFERENCE, "# Grammar reference\n\
+    \n## `list`\n\
+    \nA list of numbers separated by commas.\nFor example: `1,2`.\n\
+    \n```text\nlist = number (\",\" number)* > make_list\n```\n\
+    \nType: `Vec<u32>`\n\
+    \n## `number`\n\
+    \nA natural number.\n\
+    \n```text\nnumber = [\"0-9\"]+ > to_number\n```\n\
+    \nType: `u32`\n\
+    \n## `sign`\n\
+    \n```text\nsign = ((\"-\" / \"+\")? !.):(^)\n```\n\
+    \nType: `()`\n");
+  assert_eq!(parse_list("1,2".into_state()).unwrap_data(), vec![1, 2]);
+}
@@ -0,0 +1,33 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `#[explain_type]` emits a note tracing the type inference of the rule.
+
+use oak::oak;
+use oak_runtime::*;
+
+oak! {
+
+  // NOTE: the type of the rule `list` is `(Vec<char>, Vec<Vec<char>>)`: ...
+  #[explain_type]
+  list = "[" number ("," number)* "]"
+  number = ["0-9"]+
+}
+
+#[test]
+fn test_explain_type() {
+  let state = parse_list("[1,23]".into_state());
+  let data: (Vec<char>, Vec<Vec<char>>) = state.unwrap_data();
+  assert_eq!(data, (vec!['1'], vec![vec!['2', '3']]));
+}
@@ -0,0 +1,61 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `#![export_typing(path = "...")]` writes the inferred types in a JSON document.
+
+use oak::oak;
+
+oak! {
+  #![export_typing(path = "target/export_typing.json")]
+
+  list = "[" number ("," number)* "]"
+  number = ["0-9"]+
+  #[allow(unused)]
+  sign = !"-" "+"?
+}
+
+#[test]
+fn test_export_typing() {
+  let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("target/export_typing.json");
+  let json = std::fs::read_to_string(path).unwrap();
+  assert!(json.contains(r#"{"name": "list", "expr": 7, "type": "(Vec<char>, Vec<Vec<char>>)"}"#));
+  assert!(json.contains(r#"{"name": "number", "expr": 9, "type": "Vec<char>"}"#));
+  assert!(json.contains(r#""expr": "\"[\"", "#));
+  assert!(json.contains(r#""type": "(^)", "invisible": "string literals are invisible"}"#));
+  assert!(json.contains(r#""invisible": "syntactic predicates are invisible"}"#));
+  assert!(json.contains(r#""invisible": "sub-expression of the invisible expression `!\"-\" \"+\"?`"}"#));
+  assert!(json.contains(r#""recursive_types": []"#));
+}
+
+mod configurations {
+  use oak::oak;
+
+  oak! {
+    #![export_typing(path = "target/export_typing_cfg.json")]
+
+    #[cfg(unix)]
+    number = ["0-9"]+
+    #[cfg(not(unix))]
+    number = ["0-9"]
+  }
+
+  #[test]
+  fn test_export_typing_per_configuration() {
+    let target = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("target");
+    let unix = std::fs::read_to_string(target.join("export_typing_cfg.unix.json")).unwrap();
+    let not_unix = std::fs::read_to_string(target.join("export_typing_cfg.not_unix.json")).unwrap();
+    assert!(unix.contains(r#"{"name": "number", "expr": 1, "type": "Vec<char>"}"#));
+    assert!(not_unix.contains(r#"{"name": "number", "expr": 0, "type": "char"}"#));
+  }
+}
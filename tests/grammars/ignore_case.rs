@@ -0,0 +1,42 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Case-insensitive string literals `"select"i`.
+
+use oak::oak;
+use oak_runtime::*;
+
+oak! {
+  // The rules are tested independently of each other.
+  #![allow(unused)]
+
+  query = "select"i " " ["a-z"]+ " " "from"i " " ["a-z"]+
+  header = "content-type"i ":"
+  greek = "σοφία"i
+
+  // bad_suffix = "select"x   // ERROR: unknown suffix `x` on the string literal.
+}
+
+#[test]
+fn test_ignore_case() {
+  assert!(recognize_query("select a from t".into_state()).into_full_result().is_ok());
+  assert!(recognize_query("SELECT a FROM t".into_state()).into_full_result().is_ok());
+  assert!(recognize_query("SeLeCt a fRoM t".into_state()).into_full_result().is_ok());
+  assert!(!recognize_query("selec a from t".into_state()).is_successful());
+  assert!(recognize_header("Content-Type:".into_state()).into_full_result().is_ok());
+  assert!(recognize_greek("ΣΟΦΊΑ".into_state()).into_full_result().is_ok());
+  assert!(recognize_greek("σοφίΑ".into_state()).into_full_result().is_ok());
+  let error = parse_header("content-length:".into_state()).into_full_result().unwrap_err();
+  assert_eq!(error.expected, vec!["\"content-type\"i"]);
+}
@@ -16,7 +16,7 @@ use oak::oak;
 
 oak! {
   underscore = "_"
-  digits = (underscore* digit)+ > id
+  pub digits = (underscore* digit)+ > id
   digit = ["0-9"]
 
   fn id(v: Vec<char>) -> Vec<char> {
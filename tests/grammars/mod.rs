@@ -27,3 +27,16 @@ pub mod unreachable_rule;
 pub mod issue94;
 mod stream_span;
 mod typing;
+mod start;
+mod visibility;
+mod doc_comments;
+mod cfg;
+mod unused_rule;
+mod char_class;
+mod ignore_case;
+mod export_typing;
+mod explain_type;
+mod oak_file;
+mod bytecode;
+mod cst;
+mod nested_choice;
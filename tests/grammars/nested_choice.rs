@@ -0,0 +1,30 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A choice nested in the first alternative of another choice, where the success continuation of the inner choice is shared by its alternatives in a closure.
+
+use oak::oak;
+
+oak! {
+  keyword = ("let" / "fn") "!" / "if"
+}
+
+#[test]
+fn test_nested_choice() {
+  use oak_runtime::*;
+  assert!(recognize_keyword("let!".into_state()).into_full_result().is_ok());
+  assert!(recognize_keyword("fn!".into_state()).into_full_result().is_ok());
+  assert!(recognize_keyword("if".into_state()).into_full_result().is_ok());
+  assert!(recognize_keyword("fn".into_state()).into_full_result().is_err());
+}
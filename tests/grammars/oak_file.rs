@@ -0,0 +1,29 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `oak_file!` compiles a grammar written in a file relative to the root of the crate.
+
+use oak::oak_file;
+use oak_runtime::*;
+
+oak_file!("tests/grammars/oak_file.oak");
+
+// ERROR: cannot read the grammar file `.../tests/grammars/missing.oak`: ...
+// oak_file!("tests/grammars/missing.oak");
+
+#[test]
+fn test_oak_file() {
+  let state = parse_sum("1+22+3".into_state());
+  assert_eq!(state.unwrap_data(), 26);
+}
@@ -17,6 +17,8 @@
 use oak::oak;
 
 oak! {
+  // The rules are tested independently of each other.
+  #![allow(unused)]
 
   r = "a" r
     / "b"
@@ -0,0 +1,72 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Entry points generated with `#![start(..)]`.
+
+mod single {
+  use oak::oak;
+
+  oak! {
+    #![start(sum)]
+
+    sum = number ("+" number)* > add
+    number = ["0-9"]+ > to_number
+
+    fn add(x: u32, rest: Vec<u32>) -> u32 {
+      rest.iter().fold(x, |x,y| x+y)
+    }
+
+    fn to_number(raw_text: Vec<char>) -> u32 {
+      let text: String = raw_text.into_iter().collect();
+      text.parse().unwrap()
+    }
+  }
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(parse("7+2+1"), Ok(10));
+    let err = parse("7+2a").unwrap_err();
+    assert_eq!(err.location, "1:4");
+    assert_eq!(format!("{}", err), "1:4: unexpected `a`, expecting `+` or `[\"0-9\"]` or `<end-of-input>`.");
+    let err = parse("7+a").unwrap_err();
+    assert_eq!(format!("{}", err), "1:3: unexpected `a`, expecting `[\"0-9\"]`.");
+  }
+
+  #[test]
+  fn test_parse_prefix() {
+    let (sum, rest) = parse_prefix("7+2 rest").unwrap();
+    assert_eq!(sum, 9);
+    assert_eq!(rest.bytes_offset(), 3);
+    assert!(parse_prefix("a").is_err());
+  }
+}
+
+mod several {
+  use oak::oak;
+
+  oak! {
+    #![start(letters, digits)]
+
+    letters = ["a-z"]+
+    digits = ["0-9"]+
+  }
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(letters::parse("abc"), Ok(vec!['a', 'b', 'c']));
+    assert_eq!(digits::parse("12"), Ok(vec!['1', '2']));
+    assert!(digits::parse("abc").is_err());
+    assert_eq!(letters::parse_prefix("ab1").unwrap().0, vec!['a', 'b']);
+  }
+}
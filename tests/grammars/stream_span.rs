@@ -18,14 +18,15 @@ oak! {
   // Optional stream declaration.
   type Stream<'a> = StrStream<'a>;
 
-  expr = .. span_a . (.. .) "b" > make_expr
+  pub expr = .. span_a . (.. .) "b" > make_expr
 
-  expr2 = .. span_a . inner_expr "b" > make_expr
+  pub expr2 = .. span_a . inner_expr "b" > make_expr
+  #[allow(unreachable_alternative)]
   inner_expr = (.. (./.))
 
   span_a = .. "a"
 
-  expr3 = .. ("a" / "b") "c"
+  pub expr3 = .. ("a" / "b") "c"
 
   pub struct Expr {
     pub full_sp: Span,
@@ -15,6 +15,8 @@
 use oak::oak;
 
 oak! {
+  // The rules are tested independently of each other.
+  #![allow(unused)]
   lparen = "(" spacing
 
   type_name = auto_infer_kw &(lparen / not_eof / comma)
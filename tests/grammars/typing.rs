@@ -2,6 +2,8 @@ use oak::*;
 
 
 oak! {
+  // The rules are tested independently of each other.
+  #![allow(unused)]
   ra = "a" ra / "b" .
   // rb = "a" rb . / "b" .
   // rc = ra . / .
@@ -34,6 +36,15 @@ oak! {
   fn test3() -> () {}
   fn test4() {}
 
+  // Semantic actions are checked against the types of their arguments.
+  pair = number "," identifier > make_pair
+  // pair2 = number "," identifier > make_number // ERROR: the semantic action `make_number` takes 1 parameter but `number "," identifier` produces 2 values.
+  // pair4 = "(" number ")" > make_char // ERROR: the parameter `c` of the semantic action `make_char` has type `char` but `"(" number ")"` has type `Vec<char>`.
+
+  fn make_pair(n: Vec<char>, id: Vec<char>) -> (Vec<char>, Vec<char>) { (n, id) }
+  fn make_number(n: Vec<char>) -> Vec<char> { n }
+  fn make_char(c: char) -> char { c }
+
   rule4 = r#""foo""#
   rule5 = r##"foo #"# bar"##
 }
@@ -12,57 +12,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+
+//! Alternatives shadowed by a previous alternative of the same choice.
+//! The unreachable cases are commented to avoid warnings while compiling the tests.
+
 use oak::oak;
 
 oak! {
-    // test0 =  "a"*
-    //         /"a"+  // is detected
-
-    // test1 =  "a"
-    //         /"ab"
-    //
-    test2 =  "ab"
-            / "a"
-    // r1 = ["a-c"] -> () / "b" -> () // problem of span
-    //
-    // test3 =  "a"
-    //         /"a"
-    //
-    // test4 =  "abcd"
-    //         /"a" "bc"
-    //
-    // test4bis = "a" "bc"
-    //          / "abcd"
-
-    // keyword = "proc"/"par"/"space"/"end"/"pre"
-    //      / "read" / "write" / "readwrite" / "or" / "and" / "not"
-    //      / "when" / "then" / "else" / "loop" / "pause up"
-    //      / "pause" / "stop" / "in" / "word_line" / "singe_time"
-    //      / "single_space" / "bot" / "top" / "ref" / "module"
-    //      / "run" / "true" / "false" / "unknown" / "nothing"
-    //      / "universe" / "suspend" / "abort" / "java_kw"
-
-    // test5 =  "a"*
-    //         /"a"?  // is detected
-
-    // test6 =  "a"
-    //         /!"a"
-    //
-    // test7 = !"a"
-    //         /"a"
-    //
-    // test8 =  "a" !"a"
-    //         /"a"
-    //
-    // test9 = "a"
-    //         /&"a"
-    //
-    // test10 = &"a"
-    //         /"a"
-    //
-    // test11 = "b"
-    //         /!"a"
-
-    // test12 = .
-    //         /"a" // is detected
+  // The rules are tested independently of each other.
+  #![allow(unused)]
+
+  // test0 = "a"* / "a"+                      // WARNING: dead alternative, reported by the well-formedness analysis.
+
+  // test1 = "a" / "ab"                       // WARNING: unreachable, suggests `"ab" / "a"`.
+  test2 = "ab" / "a"                          // OK
+
+  // eq = "=" / "=="                          // WARNING: unreachable, suggests `"==" / "="`.
+
+  // test3 = "a" / "a"                        // WARNING: unreachable, suggests removing one alternative.
+
+  test4 = "abcd" / "a" "bc"                   // OK
+  // test4bis = "a" "bc" / "abcd"             // WARNING: unreachable.
+
+  // test5 = ["a-c"] / "b" "c" / "d"          // WARNING: `"b" "c"` is unreachable.
+
+  // keyword = ident / "let"                  // WARNING: unreachable, `ident` succeeds on `let`.
+  ident = (["a-zA-Z_"] ["a-zA-Z0-9_"]*):(^)
+  keyword2 = "let" / ident                    // OK
+
+  test6 = "a" / !"a"                          // OK: `!"a"` matches inputs not starting with `a`.
+  test7 = !"a" / "a"                          // OK
+  test8 = "a" !"a" / "a"                      // OK: `"a" !"a"` fails on `aa`.
+  test9 = "a"+ "a" / "aa"                     // OK: `"a"+` is greedy, so the first alternative never succeeds.
+  test10 = ("ab" / "a") "bc" / "abc"          // OK: `"ab"` is chosen on `abc` and then `"bc"` fails.
+
+  // test11 = . / "a"                         // WARNING: unreachable.
+
+  #[allow(unreachable_alternative)]
+  test12 = "a" / "ab"                         // OK: the warning is silenced.
 }
@@ -0,0 +1,54 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Rules that are not reachable from the entry rules.
+//! The unused cases are commented to avoid warnings while compiling the tests.
+
+mod private_by_default {
+  use oak::oak;
+
+  oak! {
+    #![private_rules]
+    #![start(list)]
+
+    list = number ("," number)*
+    number = digit+
+    digit = ["0-9"]
+    pub letter = ["a-z"]                // OK: the rules with a visibility are exposed.
+
+    // semicolon = ";"                  // WARNING: rule `semicolon` is never used.
+
+    #[allow(unused)]
+    lparen = "("                        // OK: the warning is silenced.
+  }
+}
+
+mod public_by_default {
+  use oak::oak;
+
+  oak! {
+    #![start(list)]
+
+    list = number ("," number)*
+    number = digit+
+    digit = ["0-9"]
+    pub letter = ["a-z"]                // OK: the rules declared public are entry rules.
+
+    // semicolon = ";"                  // WARNING: rule `semicolon` is never used, even if it is exposed.
+
+    #[allow(unused)]
+    lparen = "("                        // OK: the warning is silenced.
+  }
+}
@@ -0,0 +1,89 @@
+// Copyright 2026 Pierre Talbot
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Visibility modifiers on rules and `#![private_rules]`.
+
+mod private_by_default {
+  pub mod grammar {
+    use oak::oak;
+
+    oak! {
+      #![private_rules]
+
+      pub list = lparen (number (comma number)*)? rparen > make_list
+      pub(crate) number = ["0-9"]+ spacing > to_number
+
+      lparen = "(" spacing
+      rparen = ")" spacing
+      comma = "," spacing
+      spacing = [" "]*:(^)
+
+      #[allow(unused)]
+      unused = "x"
+
+      fn make_list(list: Option<(u32, Vec<u32>)>) -> Vec<u32> {
+        match list {
+          None => vec![],
+          Some((x, mut rest)) => { rest.insert(0, x); rest }
+        }
+      }
+
+      fn to_number(raw_text: Vec<char>) -> u32 {
+        let text: String = raw_text.into_iter().collect();
+        text.parse().unwrap()
+      }
+    }
+
+    // The functions of the unused private rule are not generated, so these names are free.
+    #[allow(dead_code)]
+    fn recognize_unused() {}
+    #[allow(dead_code)]
+    fn parse_unused() {}
+  }
+
+  use self::grammar::*;
+  use oak_runtime::*;
+
+  #[test]
+  fn test_public_rules() {
+    assert_eq!(parse_list("(1, 22 ,3)".into_state()).unwrap_data(), vec![1, 22, 3]);
+    assert!(recognize_list("()".into_state()).is_successful());
+    assert_eq!(parse_number("42".into_state()).unwrap_data(), 42);
+  }
+}
+
+mod public_by_default {
+  use oak::oak;
+
+  oak! {
+    word = ["a-z"]+ digit?
+    pub(self) digit = ["0-9"]
+
+    #[allow(unused)]
+    pub(self) unused = "x"
+  }
+
+  // `pub(self)` is private, so the functions of the unused rule are not generated and these names are free.
+  #[allow(dead_code)]
+  fn recognize_unused() {}
+  #[allow(dead_code)]
+  fn parse_unused() {}
+
+  #[test]
+  fn test_rules() {
+    use oak_runtime::*;
+    assert!(recognize_word("abc1".into_state()).is_successful());
+    assert!(parse_digit("1".into_state()).is_successful());
+  }
+}
@@ -17,6 +17,8 @@
 use oak::oak;
 
 oak! {
+  // The rules are tested independently of each other.
+  #![allow(unused)]
 
   // a = a "a" / "b"          // ERROR: left recursion
 
@@ -35,10 +37,13 @@ oak! {
   // f2 = "b"* f
 
   g = . / !g1 .               // OK
+  #[allow(unreachable_alternative)]
   g1 = . / "a"? g2 / "c" .
   g2 = "b"+ g
 
+  #[allow(unreachable_alternative)]
   h = . / ("a" "b")+ !h1 .    // OK
+  #[allow(unreachable_alternative)]
   h1 = . / "a"? h / "c" .
 
   // i = . i1               // ERROR: left recursion
@@ -47,10 +52,12 @@ oak! {
   // j = . j1               // ERROR: left recursion
   // j1 = ("" !j)+
 
-  // m = !""                // ERROR: never succeed
-  // n = (!"")*             // ERROR: never succeed
+  // m = !""                // ERROR: rule never succeeds, WARNING: `!""` never matches
+  // n = (!"")*             // ERROR: always succeed, never consume, WARNING: `!""` never matches
 
-  // w = !(.*)              // ERROR: never succeed
+  // w = !(.*)              // ERROR: rule never succeeds, WARNING: `!.*` never matches
+  // w0 = "a" / "b" !w01    // WARNING: `!w01` never matches
+  // w01 = "c"*
 
   // o = (!.)*              // ERROR: loop repeat
 
@@ -65,11 +72,20 @@ oak! {
   s = &["a-z"] "a" / "A"+     // OK
   s2 = s*
 
-  // t = ["a-z"]* / "A"+       // ERROR: unreachable branch
+  // t = ["a-z"]* / "A"+       // WARNING: dead alternative
 
-  // u = "a" .+ / .* / "Z" .+  // ERROR: unreachable branch
+  // u = "a" .+ / .* / "Z" .+  // WARNING: dead alternative
 
-  // v = "a" .+ / "" / "Z" .+  // ERROR: unreachable branch
+  // v = "a" .+ / "" / "Z" .+  // WARNING: dead alternative
+
+  #[allow(dead_alternative)]
+  t2 = ["a-z"]* / ["A"]+      // OK
+
+  // x1 = ""?                 // WARNING: redundant optional
+  // x2 = x3?                 // WARNING: redundant optional
+  // x3 = "a"+ / "b"*
+  #[allow(redundant_optional)]
+  x4 = ("a" / "")?            // OK
 
 
   // u1 = ("x"?)? // Warning useless chaining
@@ -92,6 +108,6 @@ oak! {
   // w2 = &("x"*)  // ERROR: always succeed, never consume
   // w3 = &("x"?)  // ERROR: always succeed, never consume
   w4 = !("x"+)
-  // w5 = !("x"*)  // ERROR: never succeed
-  // w6 = !("x"?)  // ERROR: never succeed
+  // w5 = !("x"*)  // ERROR: rule never succeeds, WARNING: `!"x"*` never matches
+  // w6 = !("x"?)  // ERROR: rule never succeeds, WARNING: `!"x"?` never matches
 }